@@ -0,0 +1,213 @@
+use std::{
+    collections::HashMap,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use sysinfo::Pid;
+
+use crate::{
+    filter::ProcessFilter,
+    pressure::SystemPressure,
+    snapshot::Snapshot,
+    source::MetricsSource,
+    tree::{Layout, ProcessTree},
+};
+
+enum Command {
+    Sample,
+    Configure { interval: Duration, running: bool },
+    View(Box<TreeView>),
+    Cgroup(Option<String>),
+    Stop,
+}
+
+// Come l'interfaccia mostra i processi: con questa il collector prepara
+// l'albero di ogni campione
+#[derive(Clone)]
+pub struct TreeView {
+    pub layout: Layout,
+    pub filter: ProcessFilter,
+    pub expanded_pids: HashMap<Pid, bool>,
+    // Il filtro per cui sono già stati aperti gli antenati dei match
+    pub expanded_filter: Option<ProcessFilter>,
+    // Cresce a ogni modifica della vista, per riconoscere gli alberi vecchi
+    pub generation: u64,
+}
+
+impl TreeView {
+    // Aggiorna l'albero con i processi del campione e restituisce le righe visibili
+    pub fn prepare(&mut self, tree: &mut ProcessTree, snapshot: &Snapshot) -> Vec<(usize, usize)> {
+        let added = tree.update(&snapshot.processes, self.layout, &mut self.expanded_pids);
+        if !self.filter.is_active() {
+            self.expanded_filter = None;
+        } else if self.expanded_filter.as_ref() != Some(&self.filter) {
+            tree.expand_matches(&self.filter, &mut self.expanded_pids);
+            self.expanded_filter = Some(self.filter.clone());
+        } else {
+            tree.expand_new_matches(&self.filter, &added, &mut self.expanded_pids);
+        }
+        tree.flatten(&self.filter)
+    }
+}
+
+// Albero già aggiornato, filtrato e ordinato nel thread del collector
+pub struct PreparedTree {
+    pub tree: ProcessTree,
+    pub rows: Vec<(usize, usize)>,
+    pub expanded_pids: HashMap<Pid, bool>,
+    pub generation: u64,
+}
+
+// Lavoro da fare nel thread del collector sui campioni periodici, prima di
+// passarli all'interfaccia (es. la valutazione degli allarmi)
+pub type SampleHook = Box<dyn FnMut(&Snapshot) + Send>;
+
+pub struct Sample {
+    pub snapshot: Snapshot,
+    // false per i campioni chiesti con force_refresh, che non vanno nelle history
+    pub periodic: bool,
+    // None finché l'interfaccia non ha mandato una vista
+    pub tree: Option<PreparedTree>,
+    // Pressure del cgroup chiesto con watch_cgroup
+    pub cgroup_pressure: Option<(String, SystemPressure)>,
+}
+
+// Raccoglie i campioni in un thread separato: l'interfaccia non aspetta mai
+// la scansione dei processi, riceve solo campioni già pronti
+pub struct Collector {
+    commands: Sender<Command>,
+    samples: Receiver<Sample>,
+    handle: Option<JoinHandle<()>>,
+    interval: Duration,
+    running: bool,
+    cgroup: Option<String>,
+}
+
+impl Collector {
+    pub fn spawn(
+        mut source: Box<dyn MetricsSource>,
+        interval: Duration,
+        running: bool,
+        mut hook: Option<SampleHook>,
+    ) -> Self {
+        let (commands, command_rx) = mpsc::channel();
+        let (sample_tx, samples) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            let mut interval = interval;
+            let mut running = running;
+            let mut next = Instant::now() + interval;
+            let mut view: Option<TreeView> = None;
+            let mut tree = ProcessTree::default();
+            let mut cgroup: Option<String> = None;
+
+            loop {
+                let timeout = next.saturating_duration_since(Instant::now());
+                let periodic = match command_rx.recv_timeout(timeout) {
+                    Ok(Command::Sample) => false,
+                    Ok(Command::Configure { interval: new_interval, running: new_running }) => {
+                        interval = new_interval;
+                        running = new_running;
+                        next = Instant::now() + interval;
+                        continue;
+                    }
+                    Ok(Command::View(new_view)) => {
+                        view = Some(*new_view);
+                        continue;
+                    }
+                    Ok(Command::Cgroup(new_cgroup)) => {
+                        cgroup = new_cgroup;
+                        continue;
+                    }
+                    Ok(Command::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+                    Err(RecvTimeoutError::Timeout) => {
+                        next = Instant::now() + interval;
+                        if !running {
+                            continue;
+                        }
+                        true
+                    }
+                };
+
+                let snapshot = source.sample();
+                if periodic && let Some(hook) = &mut hook {
+                    hook(&snapshot);
+                }
+                // L'interfaccia riceve una copia: il collector continua ad
+                // aggiornare la sua al campione successivo
+                let tree = view.as_mut().map(|view| {
+                    let rows = view.prepare(&mut tree, &snapshot);
+                    PreparedTree {
+                        tree: tree.clone(),
+                        rows,
+                        expanded_pids: view.expanded_pids.clone(),
+                        generation: view.generation,
+                    }
+                });
+                let cgroup_pressure = cgroup
+                    .as_ref()
+                    .and_then(|cgroup| Some((cgroup.clone(), source.cgroup_pressure(cgroup)?)));
+                let sample = Sample {
+                    snapshot,
+                    periodic,
+                    tree,
+                    cgroup_pressure,
+                };
+                if sample_tx.send(sample).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            commands,
+            samples,
+            handle: Some(handle),
+            interval,
+            running,
+            cgroup: None,
+        }
+    }
+
+    // Chiede un campione subito, senza attenderlo
+    pub fn request_sample(&self) {
+        self.commands.send(Command::Sample).ok();
+    }
+
+    // Comunica intervallo e pausa solo quando cambiano
+    pub fn configure(&mut self, interval: Duration, running: bool) {
+        if interval != self.interval || running != self.running {
+            self.interval = interval;
+            self.running = running;
+            self.commands.send(Command::Configure { interval, running }).ok();
+        }
+    }
+
+    // Da chiamare a ogni modifica di ordinamento, filtri o nodi espansi
+    pub fn set_view(&self, view: TreeView) {
+        self.commands.send(Command::View(Box::new(view))).ok();
+    }
+
+    // Il cgroup di cui leggere la pressure, comunicato solo quando cambia
+    pub fn watch_cgroup(&mut self, cgroup: Option<&str>) {
+        if self.cgroup.as_deref() != cgroup {
+            self.cgroup = cgroup.map(str::to_string);
+            self.commands.send(Command::Cgroup(self.cgroup.clone())).ok();
+        }
+    }
+
+    pub fn drain(&self) -> Vec<Sample> {
+        self.samples.try_iter().collect()
+    }
+}
+
+impl Drop for Collector {
+    fn drop(&mut self) {
+        self.commands.send(Command::Stop).ok();
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::Cpu;
+
+pub const CPU_ROOT: &str = "/sys/devices/system/cpu";
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CpuFreqInfo {
+    pub current_mhz: u64,
+    pub min_mhz: Option<u64>,
+    pub max_mhz: Option<u64>,
+    pub governor: Option<String>,
+    pub throttle_count: Option<u64>,
+    pub throttled: bool,
+}
+
+// I valori in cpufreq sono espressi in kHz
+fn read_sysfs_khz(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse::<u64>().ok()
+}
+
+// Legge le informazioni di frequenza dal sysfs, usando il valore di sysinfo come fallback
+pub fn read_cpu_freq(root: &Path, core: usize, fallback_mhz: u64) -> CpuFreqInfo {
+    let base = root.join(format!("cpu{}", core));
+    let cpufreq = base.join("cpufreq");
+
+    let current_mhz = read_sysfs_khz(&cpufreq.join("scaling_cur_freq"))
+        .map(|khz| khz / 1000)
+        .unwrap_or(fallback_mhz);
+    let min_mhz = read_sysfs_khz(&cpufreq.join("cpuinfo_min_freq")).map(|khz| khz / 1000);
+    let max_mhz = read_sysfs_khz(&cpufreq.join("cpuinfo_max_freq")).map(|khz| khz / 1000);
+    let governor = fs::read_to_string(cpufreq.join("scaling_governor"))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    let throttle_count = read_sysfs_khz(&base.join("thermal_throttle/core_throttle_count"));
+
+    CpuFreqInfo {
+        current_mhz,
+        min_mhz,
+        max_mhz,
+        governor,
+        throttle_count,
+        throttled: false,
+    }
+}
+
+// Un core è considerato in throttling se il kernel ha registrato nuovi eventi termici
+// oppure se sotto carico la frequenza scende sotto il 60% del massimo
+pub fn is_throttled(current: &CpuFreqInfo, previous: Option<&CpuFreqInfo>, usage: f32) -> bool {
+    if let (Some(now), Some(before)) = (current.throttle_count, previous.and_then(|p| p.throttle_count))
+        && now > before
+    {
+        return true;
+    }
+
+    match current.max_mhz {
+        Some(max) if max > 0 && usage > 80.0 => (current.current_mhz as f64) < max as f64 * 0.6,
+        _ => false,
+    }
+}
+
+pub fn format_frequency(mhz: u64) -> String {
+    if mhz >= 1000 {
+        format!("{:.2} GHz", mhz as f64 / 1000.0)
+    } else {
+        format!("{} MHz", mhz)
+    }
+}
+
+// `previous` è la lettura precedente, serve a riconoscere il throttling
+pub fn read_cpu_frequencies(cpus: &[Cpu], previous: &[CpuFreqInfo]) -> Vec<CpuFreqInfo> {
+    cpus.iter()
+        .enumerate()
+        .map(|(i, cpu)| {
+            let mut info = read_cpu_freq(Path::new(CPU_ROOT), i, cpu.frequency());
+            info.throttled = is_throttled(&info, previous.get(i), cpu.cpu_usage());
+            info
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::{CpuFreqInfo, format_frequency, is_throttled, read_cpu_freq};
+
+    fn fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("rtop-cpufreq-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&root).ok();
+        for (file, value) in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, format!("{}\n", value)).unwrap();
+        }
+        root
+    }
+
+    fn sample(current_mhz: u64, max_mhz: Option<u64>, throttle_count: Option<u64>) -> CpuFreqInfo {
+        CpuFreqInfo {
+            current_mhz,
+            max_mhz,
+            throttle_count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reads_cpufreq_in_mhz() {
+        let root = fixture(
+            "full",
+            &[
+                ("cpu1/cpufreq/scaling_cur_freq", "2400000"),
+                ("cpu1/cpufreq/cpuinfo_min_freq", "800000"),
+                ("cpu1/cpufreq/cpuinfo_max_freq", "4200000"),
+                ("cpu1/cpufreq/scaling_governor", "powersave"),
+                ("cpu1/thermal_throttle/core_throttle_count", "7"),
+            ],
+        );
+        let info = read_cpu_freq(&root, 1, 1000);
+        assert_eq!(info.current_mhz, 2400);
+        assert_eq!(info.min_mhz, Some(800));
+        assert_eq!(info.max_mhz, Some(4200));
+        assert_eq!(info.governor.as_deref(), Some("powersave"));
+        assert_eq!(info.throttle_count, Some(7));
+        fs::remove_dir_all(root).ok();
+    }
+
+    #[test]
+    fn missing_cpufreq_falls_back() {
+        let root = fixture(
+            "empty",
+            &[
+                ("cpu0/cpufreq/scaling_governor", ""),
+                ("cpu0/cpufreq/cpuinfo_max_freq", "n/a"),
+            ],
+        );
+        let info = read_cpu_freq(&root, 0, 1800);
+        assert_eq!(info.current_mhz, 1800);
+        assert_eq!(info.max_mhz, None);
+        assert_eq!(info.governor, None);
+        assert_eq!(info.throttle_count, None);
+        fs::remove_dir_all(root).ok();
+    }
+
+    #[test]
+    fn throttling_from_counter_or_frequency() {
+        // Nuovi eventi termici contano anche a riposo
+        let now = sample(4000, Some(4200), Some(5));
+        assert!(is_throttled(&now, Some(&sample(4000, Some(4200), Some(4))), 0.0));
+        assert!(!is_throttled(&now, Some(&sample(4000, Some(4200), Some(5))), 0.0));
+        assert!(!is_throttled(&now, None, 0.0));
+
+        // Sotto carico la frequenza deve restare sopra il 60% del massimo
+        assert!(is_throttled(&sample(2000, Some(4200), None), None, 95.0));
+        assert!(!is_throttled(&sample(3000, Some(4200), None), None, 95.0));
+        assert!(!is_throttled(&sample(2000, Some(4200), None), None, 50.0));
+        assert!(!is_throttled(&sample(2000, None, None), None, 95.0));
+        assert!(!is_throttled(&sample(0, Some(0), None), None, 95.0));
+    }
+
+    #[test]
+    fn formats_frequency() {
+        assert_eq!(format_frequency(800), "800 MHz");
+        assert_eq!(format_frequency(2400), "2.40 GHz");
+    }
+}
@@ -0,0 +1,583 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use sysinfo::Pid;
+
+use crate::{
+    fuzzy,
+    tree::{ProcessInfo, ProcessTree},
+};
+
+// Come viene interpretato il testo di ricerca
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchKind {
+    // Parte del testo, senza distinzione di maiuscole
+    #[default]
+    Substring,
+    // Lettere del nome nello stesso ordine, anche non vicine
+    Fuzzy,
+    Regex,
+}
+
+impl SearchKind {
+    pub fn next(self) -> Self {
+        match self {
+            SearchKind::Substring => SearchKind::Fuzzy,
+            SearchKind::Fuzzy => SearchKind::Regex,
+            SearchKind::Regex => SearchKind::Substring,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchKind::Substring => "substring",
+            SearchKind::Fuzzy => "fuzzy",
+            SearchKind::Regex => "regex",
+        }
+    }
+}
+
+// Filtri della tabella dei processi: un processo passa se li soddisfa tutti
+#[derive(Clone, Default, PartialEq)]
+pub struct ProcessFilter {
+    // Cercato nel nome, nel PID, nella riga di comando, nell'eseguibile,
+    // nel nome utente e nel cgroup
+    pub search: String,
+    pub search_kind: SearchKind,
+    // UID come testo
+    pub user: Option<String>,
+    pub status: Option<String>,
+    // Soglie minime: percentuale di CPU e byte di memoria
+    pub cpu: Option<f32>,
+    pub memory: Option<u64>,
+    // Filtro PID da riga di comando
+    pub pids: Option<HashSet<Pid>>,
+}
+
+// Maiuscole e minuscole carattere per carattere, così gli indici restano
+// quelli del testo originale
+fn lowercase_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn lowercase_chars(text: &str) -> Vec<char> {
+    text.chars().map(lowercase_char).collect()
+}
+
+fn find_chars(text: &[char], query: &[char]) -> Option<usize> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    text.windows(query.len()).position(|window| window == query)
+}
+
+// Confronti senza copiare il testo: la query è già in minuscolo
+fn starts_with_lowercase(text: &str, query: &[char]) -> bool {
+    let mut chars = text.chars().map(lowercase_char);
+    query.iter().all(|&c| chars.next() == Some(c))
+}
+
+fn equals_lowercase(text: &str, query: &[char]) -> bool {
+    let mut chars = text.chars().map(lowercase_char);
+    query.iter().all(|&c| chars.next() == Some(c)) && chars.next().is_none()
+}
+
+fn contains_lowercase(text: &str, query: &[char]) -> bool {
+    text.char_indices().any(|(start, _)| starts_with_lowercase(&text[start..], query))
+}
+
+thread_local! {
+    // L'ultima regex compilata: la ricerca viene applicata a ogni processo
+    static REGEX: RefCell<Option<(String, Option<Regex>)>> = const { RefCell::new(None) };
+    // L'ultima query in minuscolo, per lo stesso motivo
+    static QUERY: RefCell<(String, Vec<char>)> = const { RefCell::new((String::new(), Vec::new())) };
+}
+
+fn with_lowercase_query<R>(search: &str, f: impl FnOnce(&[char]) -> R) -> R {
+    QUERY.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.0 != search {
+            *cache = (search.to_string(), lowercase_chars(search));
+        }
+        f(&cache.1)
+    })
+}
+
+fn compiled_regex(pattern: &str) -> Option<Regex> {
+    REGEX.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.as_ref().is_none_or(|(cached, _)| cached != pattern) {
+            let regex = RegexBuilder::new(pattern).case_insensitive(true).build().ok();
+            *cache = Some((pattern.to_string(), regex));
+        }
+        cache.as_ref().and_then(|(_, regex)| regex.clone())
+    })
+}
+
+impl ProcessFilter {
+    pub fn is_active(&self) -> bool {
+        !self.search.is_empty()
+            || self.user.is_some()
+            || self.status.is_some()
+            || self.cpu.is_some()
+            || self.memory.is_some()
+            || self.pids.is_some()
+    }
+
+    // Una regex non valida non trova nulla; l'interfaccia lo segnala
+    pub fn invalid_regex(&self) -> bool {
+        self.search_kind == SearchKind::Regex && !self.search.is_empty() && compiled_regex(&self.search).is_none()
+    }
+
+    // Quanto il processo corrisponde alla ricerca, None se non corrisponde.
+    // Il nome conta più del PID e degli altri campi, così il match migliore
+    // può essere selezionato per primo
+    pub fn search_score(&self, info: &ProcessInfo) -> Option<i32> {
+        if self.search.is_empty() {
+            return Some(0);
+        }
+        let pid = || info.pid.to_string();
+        let others = [info.cmd.as_str(), &info.exe, &info.user, &info.cgroup];
+
+        match self.search_kind {
+            SearchKind::Substring | SearchKind::Fuzzy => with_lowercase_query(&self.search, |query| {
+                let by_name = if equals_lowercase(&info.name, query) {
+                    Some(100)
+                } else if starts_with_lowercase(&info.name, query) {
+                    Some(80)
+                } else if contains_lowercase(&info.name, query) {
+                    Some(60)
+                } else if self.search_kind == SearchKind::Fuzzy {
+                    // Il fuzzy resta sul nome: sulle righe di comando lunghe
+                    // troverebbe quasi tutto
+                    fuzzy::score(&self.search, &info.name).map(|score| score.min(59))
+                } else {
+                    None
+                };
+
+                by_name
+                    .or_else(|| (pid() == self.search).then_some(90))
+                    .or_else(|| pid().contains(&self.search).then_some(40))
+                    .or_else(|| {
+                        others
+                            .iter()
+                            .any(|field| contains_lowercase(field, query))
+                            .then_some(20)
+                    })
+            }),
+            SearchKind::Regex => {
+                let regex = compiled_regex(&self.search)?;
+                if regex.is_match(&info.name) {
+                    Some(60)
+                } else if regex.is_match(&pid()) {
+                    Some(40)
+                } else {
+                    others.iter().any(|field| regex.is_match(field)).then_some(20)
+                }
+            }
+        }
+    }
+
+    // Indici dei caratteri del nome da evidenziare
+    pub fn name_highlights(&self, name: &str) -> Vec<usize> {
+        if self.search.is_empty() {
+            return Vec::new();
+        }
+        let substring = || {
+            let query = lowercase_chars(&self.search);
+            find_chars(&lowercase_chars(name), &query).map(|start| (start..start + query.len()).collect())
+        };
+
+        match self.search_kind {
+            SearchKind::Substring => substring().unwrap_or_default(),
+            SearchKind::Fuzzy => substring().or_else(|| fuzzy::positions(&self.search, name)).unwrap_or_default(),
+            SearchKind::Regex => {
+                let Some(found) = compiled_regex(&self.search).and_then(|regex| regex.find(name)) else {
+                    return Vec::new();
+                };
+                name.char_indices()
+                    .enumerate()
+                    .filter(|(_, (byte, _))| found.range().contains(byte))
+                    .map(|(index, _)| index)
+                    .collect()
+            }
+        }
+    }
+
+    pub fn matches(&self, info: &ProcessInfo) -> bool {
+        if let Some(ref pids) = self.pids
+            && !pids.contains(&info.pid)
+        {
+            return false;
+        }
+
+        // Filtro ricerca
+        if self.search_score(info).is_none() {
+            return false;
+        }
+
+        // Filtro utente
+        if let Some(ref user) = self.user {
+            if let Some(uid) = info.user_id {
+                if !uid.to_string().contains(user) {
+                    return false;
+                }
+            } else {
+                return false;
+            }
+        }
+
+        // Filtro stato - case insensitive
+        if let Some(ref status) = self.status {
+            let status_lower = info.status.to_lowercase();
+            let filter_lower = status.to_lowercase();
+
+            if !status_lower.contains(&filter_lower) {
+                return false;
+            }
+        }
+
+        // Filtro soglia CPU
+        if let Some(threshold) = self.cpu
+            && info.cpu_usage < threshold
+        {
+            return false;
+        }
+
+        // Filtro soglia memoria
+        if let Some(threshold) = self.memory
+            && info.memory < threshold
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+impl ProcessTree {
+    // Righe visibili come (profondità, slot): i processi che passano il filtro,
+    // i loro antenati per dare contesto e i figli dei soli nodi espansi
+    pub fn flatten(&self, filter: &ProcessFilter) -> Vec<(usize, usize)> {
+        let mut result = Vec::with_capacity(self.len());
+        // Con un filtro attivo si decide in un solo passaggio quali
+        // sottoalberi contengono un match
+        let matched = filter.is_active().then(|| {
+            let mut matched = vec![false; self.slot_count()];
+            for &slot in self.roots() {
+                self.mark_matches(slot, filter, &mut matched);
+            }
+            matched
+        });
+        for &slot in self.roots() {
+            self.flatten_node(slot, 0, matched.as_deref(), &mut result);
+        }
+        result
+    }
+
+    fn flatten_node(&self, slot: usize, depth: usize, matched: Option<&[bool]>, result: &mut Vec<(usize, usize)>) {
+        let Some(node) = self.get(slot) else {
+            return;
+        };
+
+        // Skippo il subtree se ne il nodo ne il processo figlio hanno un match
+        if matched.is_some_and(|matched| !matched[slot]) {
+            return;
+        }
+
+        // Aggiungo il nodo al risultato per dare contesto
+        result.push((depth, slot));
+
+        // Se il nodo è expanded appiattischo tutti i processi figli
+        if node.expanded {
+            for &child in &node.children {
+                self.flatten_node(child, depth + 1, matched, result);
+            }
+        }
+    }
+
+    // Visita in post-ordine: un nodo è segnato se passa il filtro lui o un discendente
+    fn mark_matches(&self, slot: usize, filter: &ProcessFilter, matched: &mut [bool]) -> bool {
+        let Some(node) = self.get(slot) else {
+            return false;
+        };
+        let mut any = filter.matches(&node.info);
+        for &child in &node.children {
+            any |= self.mark_matches(child, filter, matched);
+        }
+        matched[slot] = any;
+        any
+    }
+
+    // Espande gli antenati dei processi che passano il filtro, così i match
+    // in profondità sono visibili senza aprire ogni livello a mano
+    pub fn expand_matches(&mut self, filter: &ProcessFilter, expanded_pids: &mut HashMap<Pid, bool>) {
+        for slot in self.roots().to_vec() {
+            self.expand_node_matches(slot, filter, expanded_pids);
+        }
+    }
+
+    // Come expand_matches ma solo per i processi appena comparsi, così un nuovo
+    // match è visibile senza riaprire i rami che l'utente ha chiuso a mano
+    pub fn expand_new_matches(&mut self, filter: &ProcessFilter, added: &[usize], expanded_pids: &mut HashMap<Pid, bool>) {
+        for &slot in added {
+            if !self.get(slot).is_some_and(|node| filter.matches(&node.info)) {
+                continue;
+            }
+            let mut current = slot;
+            while let Some(parent) = self.parent_slot(current)
+                && let Some(node) = self.get_mut(parent)
+            {
+                if expanded_pids.get(&node.info.pid) == Some(&false) {
+                    break;
+                }
+                node.expanded = true;
+                expanded_pids.insert(node.info.pid, true);
+                current = parent;
+            }
+        }
+    }
+
+    // Restituisce true se il nodo o un suo discendente passa il filtro
+    fn expand_node_matches(&mut self, slot: usize, filter: &ProcessFilter, expanded_pids: &mut HashMap<Pid, bool>) -> bool {
+        let Some(node) = self.get(slot) else {
+            return false;
+        };
+        let matches = filter.matches(&node.info);
+
+        let mut children_match = false;
+        for child in node.children.clone() {
+            children_match |= self.expand_node_matches(child, filter, expanded_pids);
+        }
+        if children_match && let Some(node) = self.get_mut(slot) {
+            node.expanded = true;
+            expanded_pids.insert(node.info.pid, true);
+        }
+        matches || children_match
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use sysinfo::Pid;
+
+    use super::{ProcessFilter, SearchKind};
+    use crate::{
+        snapshot::ProcessSample,
+        tree::{Layout, ProcessTree, SortColumn},
+    };
+
+    fn process(pid: u32, parent: Option<u32>, name: &str, cpu_usage: f32) -> ProcessSample {
+        ProcessSample {
+            pid,
+            parent,
+            name: name.to_string(),
+            cpu_usage,
+            memory: 0,
+            virtual_memory: 0,
+            user_id: Some(1000),
+            status: "Sleeping".to_string(),
+            start_time: 0,
+            cmd: Vec::new(),
+            exe: String::new(),
+            user: String::new(),
+            cgroup: String::new(),
+            read_bytes: 0,
+            written_bytes: 0,
+        }
+    }
+
+    fn names(tree: &ProcessTree, rows: &[(usize, usize)]) -> Vec<(usize, String)> {
+        rows.iter()
+            .map(|&(depth, slot)| (depth, tree.get(slot).unwrap().info.name.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn matches_keep_their_ancestors() {
+        let processes = vec![
+            process(10, None, "sshd", 0.0),
+            process(20, Some(10), "bash", 1.0),
+            process(30, Some(20), "cargo", 50.0),
+            process(40, None, "firefox", 10.0),
+        ];
+        let layout = Layout {
+            tree_mode: true,
+            sort_column: SortColumn::Pid,
+            reverse_sort: false,
+        };
+        // I figli compaiono solo sotto i nodi espansi
+        let mut expanded = HashMap::from([(Pid::from_u32(10), true), (Pid::from_u32(20), true)]);
+        let mut tree = ProcessTree::default();
+        tree.update(&processes, layout, &mut expanded);
+
+        assert_eq!(tree.flatten(&ProcessFilter::default()).len(), 4);
+
+        let filter = ProcessFilter {
+            search: "CARGO".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&tree, &tree.flatten(&filter)),
+            [(0, "sshd".to_string()), (1, "bash".to_string()), (2, "cargo".to_string())]
+        );
+
+        let filter = ProcessFilter {
+            cpu: Some(5.0),
+            ..Default::default()
+        };
+        let rows = tree.flatten(&filter);
+        assert_eq!(names(&tree, &rows).last().unwrap(), &(0, "firefox".to_string()));
+        assert!(!filter.matches(&tree.get(rows[0].1).unwrap().info));
+    }
+
+    #[test]
+    fn expanding_depths_and_matches() {
+        let processes = vec![
+            process(10, None, "sshd", 0.0),
+            process(20, Some(10), "bash", 1.0),
+            process(30, Some(20), "cargo", 50.0),
+            process(40, None, "firefox", 10.0),
+        ];
+        let layout = Layout {
+            tree_mode: true,
+            sort_column: SortColumn::Pid,
+            reverse_sort: false,
+        };
+        let mut expanded = HashMap::new();
+        let mut tree = ProcessTree::default();
+        tree.update(&processes, layout, &mut expanded);
+        let all = ProcessFilter::default();
+        assert_eq!(tree.flatten(&all).len(), 2);
+
+        tree.expand_to_depth(Some(1), &mut expanded);
+        assert_eq!(tree.flatten(&all).len(), 3);
+        tree.expand_to_depth(None, &mut expanded);
+        assert_eq!(tree.flatten(&all).len(), 4);
+        tree.expand_to_depth(Some(0), &mut expanded);
+        assert!(expanded.is_empty());
+
+        let filter = ProcessFilter {
+            search: "cargo".to_string(),
+            ..Default::default()
+        };
+        tree.expand_matches(&filter, &mut expanded);
+        assert_eq!(
+            names(&tree, &tree.flatten(&filter)),
+            [(0, "sshd".to_string()), (1, "bash".to_string()), (2, "cargo".to_string())]
+        );
+        assert_eq!(expanded.len(), 2);
+    }
+
+    #[test]
+    fn search_kinds_and_fields() {
+        let mut tree = ProcessTree::default();
+        let mut expanded = HashMap::new();
+        let layout = Layout {
+            tree_mode: false,
+            sort_column: SortColumn::Pid,
+            reverse_sort: false,
+        };
+        let mut postgres = process(10, None, "postgres", 0.0);
+        postgres.cmd = vec!["postgres".to_string(), "-D".to_string(), "/var/lib/pgdata".to_string()];
+        postgres.user = "postgres".to_string();
+        postgres.cgroup = "/system.slice/postgresql.service".to_string();
+        tree.update(&[postgres], layout, &mut expanded);
+        let info = &tree.get(tree.roots()[0]).unwrap().info;
+        assert_eq!(info.cmd, "postgres -D /var/lib/pgdata");
+
+        let search = |search_kind: SearchKind, search: &str| ProcessFilter {
+            search: search.to_string(),
+            search_kind,
+            ..Default::default()
+        };
+        assert_eq!(search(SearchKind::Substring, "pgdata").search_score(info), Some(20));
+        assert_eq!(search(SearchKind::Substring, "postgresql.service").search_score(info), Some(20));
+        assert_eq!(search(SearchKind::Substring, "POST").search_score(info), Some(80));
+        assert_eq!(search(SearchKind::Substring, "Postgres").search_score(info), Some(100));
+        assert_eq!(search(SearchKind::Substring, "TGRE").search_score(info), Some(60));
+        assert_eq!(search(SearchKind::Substring, "10").search_score(info), Some(90));
+        assert_eq!(search(SearchKind::Substring, "PGDATA").search_score(info), Some(20));
+        assert!(search(SearchKind::Substring, "pgs").search_score(info).is_none());
+        assert!(search(SearchKind::Fuzzy, "pgs").search_score(info).is_some());
+        assert!(search(SearchKind::Regex, "^post.*s$").matches(info));
+        assert!(search(SearchKind::Regex, "system\\.slice").matches(info));
+        assert!(!search(SearchKind::Regex, "^gres").matches(info));
+        assert!(search(SearchKind::Regex, "[").invalid_regex());
+
+        assert_eq!(search(SearchKind::Substring, "gre").name_highlights("postgres"), [4, 5, 6]);
+        assert_eq!(search(SearchKind::Fuzzy, "pgs").name_highlights("postgres"), [0, 4, 7]);
+        assert_eq!(search(SearchKind::Regex, "s.g").name_highlights("postgres"), [2, 3, 4]);
+    }
+
+    #[test]
+    fn children_are_relinked_when_the_parent_appears() {
+        let layout = Layout {
+            tree_mode: true,
+            sort_column: SortColumn::Pid,
+            reverse_sort: false,
+        };
+        let mut expanded = HashMap::from([(Pid::from_u32(10), true)]);
+        let mut tree = ProcessTree::default();
+
+        // Il figlio arriva in un campione precedente al parent
+        tree.update(&[process(20, Some(10), "bash", 0.0)], layout, &mut expanded);
+        assert_eq!(names(&tree, &tree.flatten(&ProcessFilter::default())), [(0, "bash".to_string())]);
+
+        let processes = [process(20, Some(10), "bash", 0.0), process(10, None, "sshd", 0.0)];
+        tree.update(&processes, layout, &mut expanded);
+        assert_eq!(
+            names(&tree, &tree.flatten(&ProcessFilter::default())),
+            [(0, "sshd".to_string()), (1, "bash".to_string())]
+        );
+        assert_eq!(tree.roots().len(), 1);
+    }
+
+    #[test]
+    fn new_matches_expand_their_ancestors_unless_collapsed() {
+        let layout = Layout {
+            tree_mode: true,
+            sort_column: SortColumn::Pid,
+            reverse_sort: false,
+        };
+        let filter = ProcessFilter {
+            search: "cargo".to_string(),
+            ..Default::default()
+        };
+        let mut expanded = HashMap::new();
+        let mut tree = ProcessTree::default();
+        let mut processes = vec![
+            process(10, None, "sshd", 0.0),
+            process(20, Some(10), "bash", 0.0),
+            process(40, None, "tmux", 0.0),
+            process(50, Some(40), "zsh", 0.0),
+        ];
+        tree.update(&processes, layout, &mut expanded);
+        tree.expand_matches(&filter, &mut expanded);
+        assert!(tree.flatten(&filter).is_empty());
+
+        // L'utente chiude tmux a mano
+        expanded.insert(Pid::from_u32(40), false);
+        processes.push(process(30, Some(20), "cargo", 0.0));
+        processes.push(process(60, Some(50), "cargo", 0.0));
+        let added = tree.update(&processes, layout, &mut expanded);
+        tree.expand_new_matches(&filter, &added, &mut expanded);
+        assert_eq!(
+            names(&tree, &tree.flatten(&filter)),
+            [
+                (0, "sshd".to_string()),
+                (1, "bash".to_string()),
+                (2, "cargo".to_string()),
+                (0, "tmux".to_string())
+            ]
+        );
+        assert_eq!(expanded.get(&Pid::from_u32(40)), Some(&false));
+    }
+}
@@ -0,0 +1,53 @@
+// Corrispondenza approssimata: le lettere del pattern devono comparire nel
+// testo nello stesso ordine, senza distinguere le maiuscole. Gli spazi del
+// pattern sono ignorati, così "sort cpu" trova "Sort by CPU usage"
+pub fn score(pattern: &str, text: &str) -> Option<i32> {
+    find(pattern, text).map(|(score, _)| score)
+}
+
+// Indici (in caratteri) delle lettere trovate, per evidenziarle
+pub fn positions(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    find(pattern, text).map(|(_, positions)| positions)
+}
+
+fn find(pattern: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    let text: Vec<char> = text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect();
+    let mut score = 0;
+    let mut positions = Vec::new();
+    let mut position = 0;
+
+    for c in pattern.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase) {
+        let found = position + text[position..].iter().position(|&t| t == c)?;
+
+        score += 1;
+        // Lettere consecutive e inizi di parola valgono di più
+        if positions.last().is_some_and(|&p| p + 1 == found) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 6;
+        }
+        score -= (found - position).min(3) as i32;
+
+        positions.push(found);
+        position = found + 1;
+    }
+    Some((score, positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{positions, score};
+
+    #[test]
+    fn subsequences_ranked_by_word_starts() {
+        assert!(score("xyz", "Sort by CPU usage").is_none());
+        assert!(score("", "anything").is_some());
+
+        let sort_cpu = score("sort cpu", "Sort by CPU usage").unwrap();
+        let scattered = score("sort cpu", "Show resources, tracking cpu").unwrap();
+        assert!(sort_cpu > scattered);
+        assert!(score("tt", "toggle tree") > score("tt", "attach"));
+        assert_eq!(positions("ff", "Firefox"), Some(vec![0, 4]));
+    }
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    symbols::Marker,
+    text::Span,
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType},
+};
+
+use crate::{gui::theme::Theme, helpers::utils::History};
+
+// Converte una history in punti (secondi prima di `now`, valore). La x viene dal
+// timestamp di ogni campione, così resta giusta anche se l'intervallo cambia
+pub fn history_points<T: Copy>(
+    history: &History<T>,
+    now: i64,
+    value: impl Fn(T) -> f64,
+) -> Vec<(f64, f64)> {
+    history
+        .iter()
+        .map(|&(time, sample)| ((time - now) as f64 / 1000.0, value(sample)))
+        .collect()
+}
+
+pub fn format_span(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+// Asse temporale che copre l'intera lunghezza della history configurata
+pub fn time_axis(theme: &Theme, history_length: usize, interval: Duration) -> Axis<'static> {
+    let span = history_length.saturating_sub(1) as f64 * interval.as_secs_f64();
+    Axis::default()
+        .style(Style::default().fg(theme.muted))
+        .bounds([-span, 0.0])
+        .labels(vec![
+            Span::raw(format!("-{}", format_span(span))),
+            Span::raw(format!("-{}", format_span(span / 2.0))),
+            Span::raw("now"),
+        ])
+}
+
+pub fn value_axis(theme: &Theme, max: f64, format_value: fn(f64) -> String) -> Axis<'static> {
+    Axis::default()
+        .style(Style::default().fg(theme.muted))
+        .bounds([0.0, max])
+        .labels(vec![
+            Span::raw(format_value(0.0)),
+            Span::raw(format_value(max / 2.0)),
+            Span::raw(format_value(max)),
+        ])
+}
+
+pub fn line_dataset<'a>(name: &'a str, points: &'a [(f64, f64)], color: Color) -> Dataset<'a> {
+    Dataset::default()
+        .name(name)
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(color))
+        .data(points)
+}
+
+pub fn history_chart<'a>(
+    title: String,
+    datasets: Vec<Dataset<'a>>,
+    x_axis: Axis<'a>,
+    y_axis: Axis<'a>,
+) -> Chart<'a> {
+    Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .x_axis(x_axis)
+        .y_axis(y_axis)
+}
+
+// Grafico braille senza assi né bordi, pensato per righe alte una cella
+pub fn draw_inline_chart(
+    f: &mut Frame,
+    area: Rect,
+    points: &[(f64, f64)],
+    x_span: f64,
+    max: f64,
+    color: Color,
+) {
+    if area.width == 0 || area.height == 0 {
+        return;
+    }
+
+    let dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(color))
+        .data(points);
+
+    let chart = Chart::new(vec![dataset])
+        .x_axis(Axis::default().bounds([-x_span, 0.0]))
+        .y_axis(Axis::default().bounds([0.0, max]));
+
+    f.render_widget(chart, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::history_points;
+
+    #[test]
+    fn points_are_placed_by_timestamp() {
+        // Intervallo passato da 1s a 250ms a metà della history
+        let history = VecDeque::from([(10_000, 1.0), (11_000, 2.0), (11_250, 3.0), (11_500, 4.0)]);
+        let points = history_points(&history, 11_500, |v| v);
+        assert_eq!(points, [(-1.5, 1.0), (-0.5, 2.0), (-0.25, 3.0), (0.0, 4.0)]);
+
+        // Un campione chiesto a mano dopo l'ultimo sposta tutto indietro
+        let points = history_points(&history, 12_000, |v| v * 10.0);
+        assert_eq!(points[3], (-0.5, 40.0));
+    }
+}
@@ -1,72 +1,242 @@
-use crate::App;
+use crate::{App, helpers::keymap::Action};
+use chrono::{Local, TimeZone};
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Color, Style},
-    text::Line,
+    style::{Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
 
-pub fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
-    let text = if app.search_mode {
-        vec![Line::from(vec![
-            ratatui::text::Span::raw("Search: "),
-            ratatui::text::Span::styled(&app.search_query, Style::default().fg(Color::Yellow)),
-            ratatui::text::Span::raw(" | ↑↓: Navigate | ESC: Cancel | Enter: Confirm"),
-        ])]
+pub fn draw_footer(f: &mut Frame, app: &mut App, area: Rect) {
+    // "2/5": match selezionato e numero di match della ricerca con salto
+    let (current, total) = app.find_counter();
+    let counter = format!("{}/{}", current.map_or("-".to_string(), |current| current.to_string()), total);
+
+    // Ogni span con l'azione eseguita cliccandolo
+    let spans: Vec<(Span, Option<Action>)> = if app.find.typing {
+        let mut spans = vec![
+            (Span::raw(format!("Find ({}): ", app.find.query.search_kind.label())), None),
+            (Span::styled(app.find.query.search.clone(), Style::default().fg(app.theme.header)), None),
+        ];
+        if app.find.query.invalid_regex() {
+            spans.push((Span::styled(" (invalid regex)", Style::default().fg(app.theme.critical)), None));
+        }
+        spans.push((
+            Span::raw(format!(" [{}] | Tab: Mode | ↑↓: Previous/Next | ESC: Cancel | Enter: Confirm", counter)),
+            None,
+        ));
+        spans
+    } else if app.search_mode {
+        let mut spans = vec![
+            (Span::raw(format!("Search ({}): ", app.filter.search_kind.label())), None),
+            (Span::styled(app.filter.search.clone(), Style::default().fg(app.theme.header)), None),
+        ];
+        if app.filter.invalid_regex() {
+            spans.push((Span::styled(" (invalid regex)", Style::default().fg(app.theme.critical)), None));
+        }
+        spans.push((Span::raw(" | Tab: Mode | ↑↓: Navigate | ESC: Cancel | Enter: Confirm"), None));
+        spans
     } else {
         let update_ms = app.update_interval.as_millis();
         let filters = get_active_filters_detailed(app);
-        
-        vec![Line::from(vec![
-            ratatui::text::Span::raw("?: Help | 1: Processes | 2: Stats | /: Search | i: Interval | k: Kill | "),
-            ratatui::text::Span::raw("p/n/c/m: Sort | "),
-            ratatui::text::Span::raw("+/-: Speed ("),
-                if app.refresh == true {
-                    ratatui::text::Span::styled(    
+        let key = |action| app.keymap.hint(action);
+        let entry = |text: String, action| (Span::raw(text), Some(action));
+        let separator = |text: &'static str| (Span::raw(text), None);
+
+        let mut spans = Vec::new();
+        let alerts: Vec<String> = app
+            .alerts
+            .lock()
+            .unwrap()
+            .active()
+            .map(|alert| match (alert.pid, alert.process) {
+                (Some(pid), Some(process)) => format!(
+                    "{} ({} {}: {})",
+                    alert.rule.name,
+                    pid,
+                    process,
+                    alert.rule.metric.format_value(alert.value)
+                ),
+                _ => format!("{} ({})", alert.rule.name, alert.rule.metric.format_value(alert.value)),
+            })
+            .collect();
+        if !alerts.is_empty() {
+            // Con molti allarmi attivi il footer mostra solo i primi
+            let mut banner = alerts.iter().take(3).cloned().collect::<Vec<_>>().join(", ");
+            if alerts.len() > 3 {
+                banner.push_str(&format!(" +{} more", alerts.len() - 3));
+            }
+            spans.push((
+                Span::styled(
+                    format!("ALERT: {} | ", banner),
+                    Style::default().fg(app.theme.critical).add_modifier(Modifier::BOLD),
+                ),
+                None,
+            ));
+        }
+        if !app.find.query.search.is_empty() {
+            spans.extend([
+                (
+                    Span::styled(
+                        format!("FIND \"{}\" [{}] ", app.find.query.search, counter),
+                        Style::default().fg(app.theme.highlight),
+                    ),
+                    None,
+                ),
+                entry(key(Action::FindNext), Action::FindNext),
+                separator("/"),
+                entry(key(Action::FindPrev), Action::FindPrev),
+                separator(": Next/Prev | "),
+            ]);
+        }
+        if let Some(host) = &app.remote {
+            spans.push((
+                Span::styled(format!("REMOTE {} | ", host), Style::default().fg(app.theme.highlight)),
+                None,
+            ));
+        }
+        if let Some(replay) = &app.replay {
+            let time = Local
+                .timestamp_millis_opt(app.snapshot.timestamp)
+                .single()
+                .map_or("-".to_string(), |t| t.format("%Y-%m-%d %H:%M:%S").to_string());
+            spans.push((
+                Span::styled(
+                    format!(
+                        "REPLAY {}{} [{}/{}] ",
+                        replay.hostname.as_deref().map_or(String::new(), |h| format!("{} ", h)),
+                        time,
+                        replay.position + 1,
+                        replay.snapshots.len()
+                    ),
+                    Style::default().fg(app.theme.highlight),
+                ),
+                None,
+            ));
+            spans.extend([
+                entry(key(Action::ReplayStepBack), Action::ReplayStepBack),
+                separator("/"),
+                entry(key(Action::ReplayStepForward), Action::ReplayStepForward),
+                separator(": Step | "),
+                entry(key(Action::ReplaySeekBack), Action::ReplaySeekBack),
+                separator("/"),
+                entry(key(Action::ReplaySeekForward), Action::ReplaySeekForward),
+                separator(": Seek | "),
+            ]);
+        }
+
+        spans.extend([
+            entry(format!("{}: Help", key(Action::ShowHelp)), Action::ShowHelp),
+            separator(" | "),
+            entry(format!("{}: Processes", key(Action::ShowProcesses)), Action::ShowProcesses),
+            separator(" | "),
+            entry(format!("{}: Stats", key(Action::ShowStats)), Action::ShowStats),
+            separator(" | "),
+            entry(format!("{}: Search", key(Action::Search)), Action::Search),
+            separator(" | "),
+            entry(format!("{}: Interval", key(Action::SetInterval)), Action::SetInterval),
+            separator(" | "),
+            entry(format!("{}: Kill", key(Action::Kill)), Action::Kill),
+            separator(" | "),
+            entry(
+                format!(
+                    "{}/{}/{}/{}: Sort",
+                    key(Action::SortPid),
+                    key(Action::SortName),
+                    key(Action::SortCpu),
+                    key(Action::SortMemory),
+                ),
+                Action::CycleSort,
+            ),
+            separator(" | "),
+            entry(key(Action::FasterRefresh), Action::FasterRefresh),
+            separator("/"),
+            entry(key(Action::SlowerRefresh), Action::SlowerRefresh),
+            separator(": Speed ("),
+            (
+                if app.refresh {
+                    Span::styled(    
                         format!("{}ms", update_ms),
-                        Style::default().fg(Color::Yellow)
+                        Style::default().fg(app.theme.header)
                     )
                 } else {
-                    ratatui::text::Span::styled(    
-                        format!("{}ms - STOPPED", update_ms),
-                        Style::default().fg(Color::Red)
+                    Span::styled(    
+                        format!(
+                            "{}ms - {}",
+                            update_ms,
+                            if app.replay.is_some() { "PAUSED" } else { "STOPPED" }
+                        ),
+                        Style::default().fg(app.theme.critical)
                     )
                 },
-            ratatui::text::Span::raw(")"),
+                Some(Action::ToggleAutoRefresh),
+            ),
+            separator(")"),
             if !filters.is_empty() {
-                ratatui::text::Span::styled(
-                    format!(" | Active: {}", filters),
-                    Style::default().fg(Color::Magenta)
+                (
+                    Span::styled(
+                        format!(" | Active: {}", filters),
+                        Style::default().fg(app.theme.highlight)
+                    ),
+                    Some(Action::ClearFilters),
                 )
             } else {
-                ratatui::text::Span::raw("| w: Select filter")
+                entry(format!("| {}: Select filter", key(Action::SelectFilter)), Action::SelectFilter)
             },
-            ratatui::text::Span::raw(" | q: Exit"),
-        ])]
+            separator(" | "),
+            entry(format!("{}: Exit", key(Action::Quit)), Action::Quit),
+        ]);
+        spans
     };
 
-    let footer = Paragraph::new(text)
+    record_footer_targets(app, area, &spans);
+
+    let footer = Paragraph::new(Line::from(spans.into_iter().map(|(span, _)| span).collect::<Vec<_>>()))
         .block(Block::default().borders(Borders::ALL))
         .alignment(ratatui::layout::Alignment::Center)
-        .style(Style::default().fg(Color::Cyan));
+        .style(Style::default().fg(app.theme.accent));
 
     f.render_widget(footer, area);
 }
 
+// Riproduce il centramento di Paragraph per sapere dove cade ogni voce
+fn record_footer_targets(app: &mut App, area: Rect, spans: &[(Span, Option<Action>)]) {
+    let inner_width = area.width.saturating_sub(2);
+    let line_width = spans.iter().map(|(span, _)| span.width() as u16).sum::<u16>();
+    let mut x = area.x + 1 + (inner_width / 2).saturating_sub(line_width.min(inner_width) / 2);
+
+    app.footer_area = area;
+    app.footer_targets.clear();
+    for (span, action) in spans {
+        let width = span.width() as u16;
+        // Le voci tagliate a destra non sono cliccabili
+        if x + width >= area.right() {
+            break;
+        }
+        if let Some(action) = action {
+            app.footer_targets.push((x, x + width, *action));
+        }
+        x += width;
+    }
+}
+
 fn get_active_filters_detailed(app: &App) -> String {
     let mut filters = Vec::new();
-    
-    if let Some(ref user) = app.user_filter {
+
+    if let Some(ref pids) = app.filter.pids {
+        filters.push(format!("PIDs:{}", pids.len()));
+    }
+    if let Some(ref user) = app.filter.user {
         filters.push(format!("User:{}", user));
     }
-    if let Some(ref status) = app.status_filter {
+    if let Some(ref status) = app.filter.status {
         filters.push(format!("Status:{}", status));
     }
-    if let Some(threshold) = app.cpu_threshold {
+    if let Some(threshold) = app.filter.cpu {
         filters.push(format!("CPU≥{:.1}%", threshold));
     }
-    if let Some(threshold) = app.memory_threshold {
+    if let Some(threshold) = app.filter.memory {
         filters.push(format!("Mem≥{}MB", threshold / 1024 / 1024));
     }
     
@@ -1,156 +1,147 @@
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect, Alignment},
-    style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{Block, Borders, Paragraph, Row, Table},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
 };
 
-use crate::{App, gui::overlay::draw_input_overlay};
+use crate::{App, gui::overlay::draw_input_overlay, helpers::keymap::Action};
 
 pub fn draw_help(f: &mut Frame, app: &mut App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(5)])
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
         .split(area);
 
     let title_block = Block::default().borders(Borders::ALL).title(Span::styled(
         " Help / Cheatsheet ",
         Style::default()
-            .fg(Color::Yellow)
+            .fg(app.theme.header)
             .add_modifier(Modifier::BOLD),
     ));
 
-    let title_paragraph = Paragraph::new("List of available keybindings")
+    let back = app.keymap.hint(Action::Back);
+    let subtitle = if app.help.searching || !app.help.query.is_empty() {
+        let cursor = if app.help.searching { "_" } else { "" };
+        format!("Search: {}{}", app.help.query, cursor)
+    } else if app.help.overlay.is_some() {
+        format!("Keys of the open overlay, {}: back to it", back)
+    } else {
+        format!(
+            "{}/{}: scroll | {}: search | {}: back",
+            app.keymap.hint(Action::SelectUp),
+            app.keymap.hint(Action::SelectDown),
+            app.keymap.hint(Action::Search),
+            back
+        )
+    };
+
+    let title_paragraph = Paragraph::new(subtitle)
         .block(title_block)
         .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(app.theme.text));
 
     f.render_widget(title_paragraph, chunks[0]);
 
-    let bindings = vec![
-        ("Navigation", ""),
-        ("↑/↓", "Move selection up/down"),
-        ("PageUp/PageDown", "Navigate by page"),
-        ("t", "Jump to top"),
-        ("b", "Jump to bottom"),
-        ("", ""),
-        ("Actions", ""),
-        ("Enter/Space", "Expand/collapse process tree"),
-        ("k/Del", "Kill process (with confirmation for critical)"),
-        ("s", "Suspend process (SIGSTOP)"),
-        ("r", "Resume process (SIGCONT)"),
-        ("x", "View a process's open files"),
-        ("Ctrl+r", "Force refresh"),
-        ("", ""),
-        ("Sorting", ""),
-        ("p", "Sort by PID"),
-        ("n", "Sort by name"),
-        ("c", "Sort by CPU usage"),
-        ("m", "Sort by memory usage"),
-        ("", ""),
-        ("Search & Filter", ""),
-        ("/ or Ctrl+F", "Search processes"),
-        ("w", "Select the filtering mode"),
-        ("l", "Clear all filters"),
-        ("", ""),
-        ("View & Settings", ""),
-        ("1 or F1", "Process view"),
-        ("2 or F2", "System stats view"),
-        ("3 or F3 or h or ?", "Help screen"),
-        ("i", "Set custom update interval"),
-        ("+ / -", "Increase/decrease update speed"),
-        ("z", "Toggle auto refresh"),
-        ("", ""),
-        ("General", ""),
-        ("q or Esc", "Quit (saves preferences)"),
-        ("Ctrl+C", "Force quit (saves preferences)"),
-    ];
-
-    let mid = (bindings.len() + 1) / 2;
-    let (left_bindings, right_bindings) = bindings.split_at(mid);
-
-    fn make_rows<'a>(left: &'a [(&'a str, &'a str)], right: &'a [(&'a str, &'a str)]) -> Vec<Row<'a>> {
-        let max_len = left.len().max(right.len());
-        let mut rows = Vec::with_capacity(max_len);
-
-        for i in 0..max_len {
-            let (lk, ld) = left.get(i).copied().unwrap_or(("", ""));
-            let (rk, rd) = right.get(i).copied().unwrap_or(("", ""));
-
-            rows.push(Row::new(vec![
-                Span::styled(
-                    lk,
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::raw(ld),
-                Span::raw(" "),
+    // Generata dalla keymap attiva, così riflette preset e tasti personalizzati
+    let sections = app.help_sections();
+    let key_width = sections
+        .iter()
+        .flat_map(|section| section.entries.iter())
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(30);
+
+    let mut lines = Vec::new();
+    for section in &sections {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            section.title,
+            Style::default()
+                .fg(app.theme.header)
+                .add_modifier(Modifier::BOLD),
+        )));
+        for (keys, description) in &section.entries {
+            lines.push(Line::from(vec![
                 Span::styled(
-                    rk,
+                    format!("  {:<width$}  ", keys, width = key_width),
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(app.theme.accent)
                         .add_modifier(Modifier::BOLD),
                 ),
-                Span::raw(rd),
+                Span::raw(description.as_str()),
             ]));
         }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("No keybindings match \"{}\"", app.help.query),
+            Style::default().fg(app.theme.muted),
+        )));
+    }
 
-        rows
+    // Lo scorrimento si ferma all'ultima pagina
+    let height = chunks[1].height.saturating_sub(2) as usize;
+    let max_scroll = lines.len().saturating_sub(height);
+    app.help.height = height;
+    app.help.scroll = app.help.scroll.min(max_scroll);
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Keybindings ")
+        .title_alignment(Alignment::Center);
+    if max_scroll > 0 {
+        block = block.title_bottom(
+            Line::from(format!(
+                " {}-{} of {} ",
+                app.help.scroll + 1,
+                (app.help.scroll + height).min(lines.len()),
+                lines.len()
+            ))
+            .right_aligned(),
+        );
     }
 
-    let rows = make_rows(left_bindings, right_bindings);
-
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(20),
-            Constraint::Length(35),
-            Constraint::Length(10),
-            Constraint::Length(20),
-            Constraint::Min(25),
-        ],
-    )
-    .header(Row::new(vec![
-        Span::styled(
-            "Key",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            "Action",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            " ",
-            Style::default()
-        ),
-        Span::styled(
-            "Key",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            "Action",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        ),
-    ]))
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title(" Keybindings ")
-            .title_alignment(Alignment::Center),
-    )
-    .style(Style::default().fg(Color::White));
-
-    f.render_widget(table, chunks[1]);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((app.help.scroll as u16, 0))
+        .style(Style::default().fg(app.theme.text));
+    f.render_widget(paragraph, chunks[1]);
+
+    if max_scroll > 0 {
+        let mut state = ScrollbarState::new(max_scroll).position(app.help.scroll);
+        f.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None),
+            chunks[1].inner(Margin { vertical: 1, horizontal: 0 }),
+            &mut state,
+        );
+    }
 
     draw_input_overlay(f, app);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::draw_help;
+    use crate::helpers::fake;
+
+    #[test]
+    fn small_terminal_scrolls() {
+        let mut app = fake::app(vec![fake::machine()]);
+        app.open_help();
+        let lines = fake::render(70, 16, |f| draw_help(f, &mut app, f.area()));
+        fake::assert_snapshot("help_small", &lines);
+
+        // Oltre la fine si ferma all'ultima riga
+        app.help.scroll = usize::MAX;
+        fake::render(70, 16, |f| draw_help(f, &mut app, f.area()));
+        let last = app.help.scroll;
+        app.scroll_help(-1);
+        fake::render(70, 16, |f| draw_help(f, &mut app, f.area()));
+        assert_eq!(app.help.scroll, last - 1);
+    }
+}
@@ -0,0 +1,212 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Tabs},
+};
+
+use crate::{
+    App,
+    gui::overlay::draw_input_overlay,
+    helpers::{
+        inspector::{InspectorLine, InspectorTab},
+        keymap::Action,
+    },
+};
+
+// Spezza il testo in righe della larghezza data, senza tagliare nulla
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars.chunks(width.max(1)).map(|chunk| chunk.iter().collect()).collect()
+}
+
+pub fn draw_inspector(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(area);
+
+    // Titoli completi se entrano, altrimenti quelli corti
+    let inner_width = chunks[0].width.saturating_sub(2) as usize;
+    let full_width = InspectorTab::ALL.iter().map(|tab| tab.title().len() + 3).sum::<usize>() - 1;
+    let titles: Vec<&str> = InspectorTab::ALL
+        .iter()
+        .map(|tab| if full_width <= inner_width { tab.title() } else { tab.short_title() })
+        .collect();
+
+    // Tabs disegna " titolo " separati da "│"
+    app.inspector.tab_targets.clear();
+    let mut x = chunks[0].x + 1;
+    for (index, title) in titles.iter().enumerate() {
+        let width = title.len() as u16 + 2;
+        app.inspector.tab_targets.push((Rect::new(x, chunks[0].y + 1, width, 1).intersection(chunks[0]), index));
+        x += width + 1;
+    }
+
+    let pid = app.inspector.pid.map_or("-".to_string(), |pid| pid.to_string());
+    let title = format!(
+        " Inspector: {} ({}){} ",
+        pid,
+        app.inspector.name,
+        if app.inspector.exited { " - exited" } else { "" }
+    );
+    let hint = format!(" Tab/←→: switch tab | {}: back ", app.keymap.hint(Action::Back));
+    let tabs = Tabs::new(titles)
+        .select(app.inspector.tab)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(
+                    title,
+                    Style::default()
+                        .fg(if app.inspector.exited { app.theme.critical } else { app.theme.header })
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .title(Line::from(hint).right_aligned()),
+        )
+        .style(Style::default().fg(app.theme.text))
+        .highlight_style(
+            Style::default()
+                .fg(app.theme.selection_text)
+                .bg(app.theme.selection)
+                .add_modifier(Modifier::BOLD),
+        );
+    f.render_widget(tabs, chunks[0]);
+
+    let block_area = chunks[1];
+    let inner = block_area.inner(Margin { vertical: 1, horizontal: 1 });
+    let (header_area, body) = if app.inspector.content.header.is_some() {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(inner);
+        (Some(split[0]), split[1])
+    } else {
+        (None, inner)
+    };
+
+    // Una colonna resta libera per la barra di scorrimento
+    let width = body.width.saturating_sub(1) as usize;
+    let label_width = app
+        .inspector
+        .content
+        .lines
+        .iter()
+        .filter_map(|line| match line {
+            InspectorLine::Field(label, _) => Some(label.chars().count()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for line in &app.inspector.content.lines {
+        match line {
+            InspectorLine::Title(title) => lines.push(Line::from(Span::styled(
+                title.clone(),
+                Style::default()
+                    .fg(app.theme.header)
+                    .add_modifier(Modifier::BOLD),
+            ))),
+            InspectorLine::Field(label, value) => {
+                // I valori lunghi continuano sotto il valore, non sotto l'etichetta
+                let indent = label_width + 2;
+                for (index, chunk) in wrap(value, width.saturating_sub(indent)).into_iter().enumerate() {
+                    let label = if index == 0 { format!("{}:", label) } else { String::new() };
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("{:<indent$}", label), Style::default().fg(app.theme.accent)),
+                        Span::raw(chunk),
+                    ]));
+                }
+            }
+            InspectorLine::Text(text) => lines.extend(wrap(text, width).into_iter().map(Line::from)),
+            InspectorLine::Note(text) => lines.extend(
+                wrap(text, width)
+                    .into_iter()
+                    .map(|chunk| Line::from(Span::styled(chunk, Style::default().fg(app.theme.muted)))),
+            ),
+        }
+    }
+
+    // Lo scorrimento si ferma all'ultima pagina
+    let height = body.height as usize;
+    let max_scroll = lines.len().saturating_sub(height);
+    app.inspector.height = height;
+    app.inspector.scroll = app.inspector.scroll.min(max_scroll);
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ", InspectorTab::ALL[app.inspector.tab].title()))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().fg(app.theme.text));
+    if max_scroll > 0 {
+        block = block.title_bottom(
+            Line::from(format!(
+                " {}-{} of {} ",
+                app.inspector.scroll + 1,
+                (app.inspector.scroll + height).min(lines.len()),
+                lines.len()
+            ))
+            .right_aligned(),
+        );
+    }
+    f.render_widget(block, block_area);
+
+    if let (Some(header_area), Some(header)) = (header_area, &app.inspector.content.header) {
+        f.render_widget(
+            Paragraph::new(header.as_str()).style(Style::default().fg(app.theme.header).add_modifier(Modifier::BOLD)),
+            header_area,
+        );
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .scroll((app.inspector.scroll as u16, 0))
+        .style(Style::default().fg(app.theme.text));
+    f.render_widget(paragraph, body);
+
+    if max_scroll > 0 {
+        let mut state = ScrollbarState::new(max_scroll).position(app.inspector.scroll);
+        f.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None),
+            block_area.inner(Margin { vertical: 1, horizontal: 0 }),
+            &mut state,
+        );
+    }
+
+    draw_input_overlay(f, app);
+}
+
+#[cfg(test)]
+mod tests {
+    use sysinfo::Pid;
+
+    use super::draw_inspector;
+    use crate::helpers::fake;
+
+    #[test]
+    fn overview_wraps_the_command() {
+        let mut machine = fake::machine();
+        let firefox = machine.processes.iter_mut().find(|process| process.pid == 400).unwrap();
+        firefox.cmd = vec![
+            "/usr/lib/firefox/firefox".to_string(),
+            "-contentproc".to_string(),
+            "-childID".to_string(),
+            "42".to_string(),
+            "-isForBrowser".to_string(),
+            "-prefsLen".to_string(),
+            "31337".to_string(),
+            "-appDir".to_string(),
+            "/usr/lib/firefox/browser".to_string(),
+        ];
+        let mut app = fake::app(vec![machine]);
+        app.select_pid(Pid::from_u32(400));
+        app.open_inspector();
+
+        let lines = fake::render(80, 26, |f| draw_inspector(f, &mut app, f.area()));
+        fake::assert_snapshot("inspector_overview", &lines);
+    }
+}
@@ -2,4 +2,7 @@ pub mod processes;
 pub mod stats;
 pub mod footer;
 pub mod help;
-pub mod overlay;
\ No newline at end of file
+pub mod inspector;
+pub mod overlay;
+pub mod charts;
+pub mod theme;
\ No newline at end of file
@@ -1,10 +1,13 @@
-use crate::{App, InputMode};
+use crate::{
+    App, InputMode,
+    helpers::{context_menu::MenuItem, manage_process::is_critical_process},
+};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style, Stylize},
+    style::{Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 
 pub fn draw_input_overlay(f: &mut Frame, app: &App) {
@@ -17,23 +20,23 @@ pub fn draw_input_overlay(f: &mut Frame, app: &App) {
             let block = Block::default()
                 .title("Select a filter (0. Reset filters / 1. User / 2. Status / 3. CPU% / 4. Memory)")
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Black)
-                .fg(Color::Yellow).bold());
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header).bold());
 
             let text = vec![
                 Line::from(""),
                 Line::from(vec![
-                    Span::styled("Enter numer (0 - 4): ", Style::default().fg(Color::White).not_bold()),
-                    Span::styled(&app.input_buffer, Style::default().fg(Color::Green).not_bold()),
+                    Span::styled("Enter numer (0 - 4): ", Style::default().fg(app.theme.text).not_bold()),
+                    Span::styled(&app.input_buffer, Style::default().fg(app.theme.good).not_bold()),
                 ]),
                 Line::from(""),
-                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(Color::White).not_bold())),
+                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(app.theme.text).not_bold())),
             ];
 
             let paragraph = Paragraph::new(text)
                 .block(block)
                 .alignment(ratatui::layout::Alignment::Center)
-                .style(Style::default().bg(Color::Black));
+                .style(Style::default().bg(app.theme.background));
 
             f.render_widget(paragraph, area);
         }
@@ -46,23 +49,23 @@ pub fn draw_input_overlay(f: &mut Frame, app: &App) {
             let block = Block::default()
                 .title("Set Update Interval (ms)")
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Black)
-                .fg(Color::Yellow));
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
 
             let text = vec![
                 Line::from(""),
                 Line::from(vec![
-                    Span::styled("Enter interval (100-6000 ms): ", Style::default().fg(Color::White)),
-                    Span::styled(&app.input_buffer, Style::default().fg(Color::Green)),
+                    Span::styled("Enter interval (100-6000 ms): ", Style::default().fg(app.theme.text)),
+                    Span::styled(&app.input_buffer, Style::default().fg(app.theme.good)),
                 ]),
                 Line::from(""),
-                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(app.theme.text))),
             ];
 
             let paragraph = Paragraph::new(text)
                 .block(block)
                 .alignment(ratatui::layout::Alignment::Center)
-                .style(Style::default().bg(Color::Black));
+                .style(Style::default().bg(app.theme.background));
 
             f.render_widget(paragraph, area);
         }
@@ -71,28 +74,39 @@ pub fn draw_input_overlay(f: &mut Frame, app: &App) {
 
             f.render_widget(Clear, area);
 
+            let critical = app.pending_kill_pid.is_some_and(|pid| {
+                app.snapshot
+                    .process(pid.as_u32())
+                    .is_some_and(|p| is_critical_process(pid, &p.name))
+            });
+            let (title, warning) = if critical {
+                ("⚠ Confirm Kill Critical Process", "This appears to be a critical system process!")
+            } else {
+                ("⚠ Confirm Kill Process", "The selected process will be terminated.")
+            };
+
             let block = Block::default()
-                .title("⚠ Confirm Kill Critical Process")
+                .title(title)
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Black)
-                .fg(Color::LightRed));
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.critical));
 
             let text = vec![
                 Line::from(""),
                 Line::from(Span::styled(
-                    "This appears to be a critical system process!",
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    warning,
+                    Style::default().fg(app.theme.critical).add_modifier(Modifier::BOLD),
                 )),
                 Line::from(""),
-                Line::from(Span::styled("Are you sure you want to kill this process?", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Are you sure you want to kill this process?", Style::default().fg(app.theme.text))),
                 Line::from(""),
-                Line::from(Span::styled("Press Y to confirm, N or Esc to cancel", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Press Y to confirm, N or Esc to cancel", Style::default().fg(app.theme.text))),
             ];
 
             let paragraph = Paragraph::new(text)
                 .block(block)
                 .alignment(ratatui::layout::Alignment::Center)
-                .style(Style::default().bg(Color::Black));
+                .style(Style::default().bg(app.theme.background));
 
             f.render_widget(paragraph, area);
         }
@@ -104,26 +118,26 @@ pub fn draw_input_overlay(f: &mut Frame, app: &App) {
             let block = Block::default()
                 .title("Filter by User ID")
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Black)
-                .fg(Color::Yellow));
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
 
             let text = vec![
                 Line::from(""),
                 Line::from(vec![
-                    Span::styled("Enter User ID: ", Style::default().fg(Color::White)),
-                    Span::styled(&app.input_buffer, Style::default().fg(Color::Green)),
+                    Span::styled("Enter User ID: ", Style::default().fg(app.theme.text)),
+                    Span::styled(&app.input_buffer, Style::default().fg(app.theme.good)),
                 ]),
                 Line::from(""),
-                Line::from(Span::styled("Shows processes matching the specified user ID", Style::default().fg(Color::White))),
-                Line::from(Span::styled("Leave empty to clear filter", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Shows processes matching the specified user ID", Style::default().fg(app.theme.text))),
+                Line::from(Span::styled("Leave empty to clear filter", Style::default().fg(app.theme.text))),
                 Line::from(""),
-                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(app.theme.text))),
             ];
 
             let paragraph = Paragraph::new(text)
                 .block(block)
                 .alignment(ratatui::layout::Alignment::Center)
-                .style(Style::default().bg(Color::Black));
+                .style(Style::default().bg(app.theme.background));
 
             f.render_widget(paragraph, area);
         }
@@ -135,33 +149,33 @@ pub fn draw_input_overlay(f: &mut Frame, app: &App) {
             let block = Block::default()
                 .title("Filter by Status")
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Black)
-                .fg(Color::Yellow));
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
 
             let text = vec![
                 Line::from(""),
                 Line::from(vec![
-                    Span::styled("Enter Status: ", Style::default().fg(Color::White)),
-                    Span::styled(&app.input_buffer, Style::default().fg(Color::Green)),
+                    Span::styled("Enter Status: ", Style::default().fg(app.theme.text)),
+                    Span::styled(&app.input_buffer, Style::default().fg(app.theme.good)),
                 ]),
                 Line::from(""),
-                Line::from(Span::styled("Shows processes matching the specified status", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Shows processes matching the specified status", Style::default().fg(app.theme.text))),
                 Line::from(""),
                 Line::from(Span::styled(
                     "Common statuses:",
-                    Style::default().fg(Color::Cyan),
+                    Style::default().fg(app.theme.accent),
                 )),
-                Line::from(Span::styled("  • Running, Sleeping, Stopped, Zombie", Style::default().fg(Color::White).bold())),
+                Line::from(Span::styled("  • Running, Sleeping, Stopped, Zombie", Style::default().fg(app.theme.text).bold())),
                 Line::from(""),
-                Line::from(Span::styled("Leave empty to clear filter", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Leave empty to clear filter", Style::default().fg(app.theme.text))),
                 Line::from(""),
-                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(app.theme.text))),
             ];
 
             let paragraph = Paragraph::new(text)
                 .block(block)
                 .alignment(ratatui::layout::Alignment::Center)
-                .style(Style::default().bg(Color::Black));
+                .style(Style::default().bg(app.theme.background));
 
             f.render_widget(paragraph, area);
         }
@@ -173,26 +187,26 @@ pub fn draw_input_overlay(f: &mut Frame, app: &App) {
             let block = Block::default()
                 .title("Filter by CPU Threshold")
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Black)
-                .fg(Color::Yellow));
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
 
             let text = vec![
                 Line::from(""),
                 Line::from(vec![
-                    Span::styled("Enter minimum CPU% (0-100): ", Style::default().fg(Color::White)),
-                    Span::styled(&app.input_buffer, Style::default().fg(Color::Green)),
+                    Span::styled("Enter minimum CPU% (0-100): ", Style::default().fg(app.theme.text)),
+                    Span::styled(&app.input_buffer, Style::default().fg(app.theme.good)),
                 ]),
                 Line::from(""),
-                Line::from(Span::styled("Shows only processes using >= specified CPU%", Style::default().fg(Color::White))),
-                Line::from(Span::styled("Leave empty to clear filter", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Shows only processes using >= specified CPU%", Style::default().fg(app.theme.text))),
+                Line::from(Span::styled("Leave empty to clear filter", Style::default().fg(app.theme.text))),
                 Line::from(""),
-                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(app.theme.text))),
             ];
 
             let paragraph = Paragraph::new(text)
                 .block(block)
                 .alignment(ratatui::layout::Alignment::Center)
-                .style(Style::default().bg(Color::Black));
+                .style(Style::default().bg(app.theme.background));
 
             f.render_widget(paragraph, area);
         }
@@ -204,60 +218,265 @@ pub fn draw_input_overlay(f: &mut Frame, app: &App) {
             let block = Block::default()
                 .title("Filter by Memory Threshold")
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Black)
-                .fg(Color::Yellow));
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
 
             let text = vec![
                 Line::from(""),
                 Line::from(vec![
-                    Span::styled("Enter minimum Memory (MB): ", Style::default().fg(Color::White)),
-                    Span::styled(&app.input_buffer, Style::default().fg(Color::Green)),
+                    Span::styled("Enter minimum Memory (MB): ", Style::default().fg(app.theme.text)),
+                    Span::styled(&app.input_buffer, Style::default().fg(app.theme.good)),
                 ]),
                 Line::from(""),
-                Line::from(Span::styled("Shows only processes using >= specified MB", Style::default().fg(Color::White))),
-                Line::from(Span::styled("Leave empty to clear filter", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Shows only processes using >= specified MB", Style::default().fg(app.theme.text))),
+                Line::from(Span::styled("Leave empty to clear filter", Style::default().fg(app.theme.text))),
                 Line::from(""),
-                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(Color::White))),
+                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(app.theme.text))),
             ];
 
             let paragraph = Paragraph::new(text)
                 .block(block)
                 .alignment(ratatui::layout::Alignment::Center)
-                .style(Style::default().bg(Color::Black));
+                .style(Style::default().bg(app.theme.background));
 
             f.render_widget(paragraph, area);
         }
-        InputMode::Error => {
+        InputMode::SelectView => {
+            let area = centered_rect(50, 40, f.area());
+
+            f.render_widget(Clear, area);
+
+            let block = Block::default()
+                .title("Saved views")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
+
+            let names = app.view_names();
+            let mut lines = vec![Line::from("")];
+            if names.is_empty() {
+                lines.push(Line::from(Span::styled("No saved views yet", Style::default().fg(app.theme.muted))));
+            }
+            for (i, name) in names.iter().enumerate() {
+                let style = if i == app.view_selected {
+                    app.theme.selected_style()
+                } else {
+                    Style::default().fg(app.theme.text)
+                };
+                lines.push(Line::from(Span::styled(format!(" {} ", name), style)));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Enter: apply | s: save current as... | d: delete | Esc: close",
+                Style::default().fg(app.theme.muted),
+            )));
+
+            let paragraph = Paragraph::new(lines)
+                .block(block)
+                .alignment(ratatui::layout::Alignment::Center)
+                .style(Style::default().bg(app.theme.background));
+
+            f.render_widget(paragraph, area);
+        }
+        InputMode::SaveView => {
             let area = centered_rect(60, 20, f.area());
+
             f.render_widget(Clear, area);
 
             let block = Block::default()
-                .title("Errors occurred")
+                .title("Save current view")
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Black)
-                .fg(Color::Red));
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
 
-            let mut lines: Vec<Line> = app
-                .errors
-                .iter()
-                .map(|(label, message)| {
-                    Line::from(vec![
-                        Span::styled(format!("[{}] ", label), Style::default().fg(Color::Red)),
-                        Span::styled(message.clone(), Style::default().fg(Color::White)),
-                    ])
-                })
-                .collect();
+            let text = vec![
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled("View name: ", Style::default().fg(app.theme.text)),
+                    Span::styled(&app.input_buffer, Style::default().fg(app.theme.good)),
+                ]),
+                Line::from(""),
+                Line::from(Span::styled("Saves filters, search, sort order, columns and mode", Style::default().fg(app.theme.text))),
+                Line::from(Span::styled("An existing view with the same name is replaced", Style::default().fg(app.theme.text))),
+                Line::from(""),
+                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(app.theme.text))),
+            ];
+
+            let paragraph = Paragraph::new(text)
+                .block(block)
+                .alignment(ratatui::layout::Alignment::Center)
+                .style(Style::default().bg(app.theme.background));
+
+            f.render_widget(paragraph, area);
+        }
+        InputMode::Error => {
+            // Gli errori di configurazione possono occupare più righe
+            let height = if app.errors.iter().any(|(_, message)| message.contains('\n')) { 50 } else { 20 };
+            let area = centered_rect(60, height, f.area());
+            f.render_widget(Clear, area);
+
+            let block = Block::default()
+                .title("Errors occurred")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.critical));
+
+            let mut lines: Vec<Line> = Vec::new();
+            for (label, message) in &app.errors {
+                let mut message_lines = message.lines();
+                lines.push(Line::from(vec![
+                    Span::styled(format!("[{}] ", label), Style::default().fg(app.theme.critical)),
+                    Span::styled(
+                        message_lines.next().unwrap_or_default().to_string(),
+                        Style::default().fg(app.theme.text),
+                    ),
+                ]));
+                lines.extend(message_lines.map(|line| {
+                    Line::from(Span::styled(line.to_string(), Style::default().fg(app.theme.text)))
+                }));
+            }
 
             lines.push(Line::from(""));
             lines.push(Line::from(Span::styled(
                 "Press Enter to confirm, Esc to cancel",
-                Style::default().fg(Color::Gray),
+                Style::default().fg(app.theme.muted),
             )));
 
             let paragraph = Paragraph::new(lines)
                 .block(block)
+                .wrap(Wrap { trim: false })
                 .alignment(ratatui::layout::Alignment::Left)
-                .style(Style::default().bg(Color::Black));
+                .style(Style::default().bg(app.theme.background));
+
+            f.render_widget(paragraph, area);
+        }
+        InputMode::ContextMenu => {
+            let Some(menu) = &app.context_menu else {
+                return;
+            };
+            f.render_widget(Clear, menu.area);
+
+            let block = Block::default()
+                .title(format!("PID {}", menu.pid))
+                .borders(Borders::ALL)
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
+
+            let lines: Vec<Line> = MenuItem::ALL
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let style = if i == menu.selected {
+                        app.theme.selected_style()
+                    } else {
+                        Style::default().fg(app.theme.text)
+                    };
+                    Line::from(Span::styled(format!(" {:<width$}", item.label(), width = menu.area.width as usize - 3), style))
+                })
+                .collect();
+
+            let paragraph = Paragraph::new(lines)
+                .block(block)
+                .style(Style::default().bg(app.theme.background));
+
+            f.render_widget(paragraph, menu.area);
+        }
+        InputMode::SendSignal | InputMode::Renice => {
+            let area = centered_rect(60, 20, f.area());
+
+            f.render_widget(Clear, area);
+
+            let pid = app.context_menu.as_ref().map_or(0, |menu| menu.pid.as_u32());
+            let (title, prompt, hint) = if app.input_mode == InputMode::SendSignal {
+                (
+                    format!("Send a signal to PID {}", pid),
+                    "Signal name or number: ",
+                    "For example TERM, HUP, USR1 or 9",
+                )
+            } else {
+                (
+                    format!("Renice PID {}", pid),
+                    "New nice value (-20 - 19): ",
+                    "Lower values mean higher priority",
+                )
+            };
+
+            let block = Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
+
+            let text = vec![
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled(prompt, Style::default().fg(app.theme.text)),
+                    Span::styled(&app.input_buffer, Style::default().fg(app.theme.good)),
+                ]),
+                Line::from(""),
+                Line::from(Span::styled(hint, Style::default().fg(app.theme.text))),
+                Line::from(Span::styled("Press Enter to confirm, Esc to cancel", Style::default().fg(app.theme.text))),
+            ];
+
+            let paragraph = Paragraph::new(text)
+                .block(block)
+                .alignment(ratatui::layout::Alignment::Center)
+                .style(Style::default().bg(app.theme.background));
+
+            f.render_widget(paragraph, area);
+        }
+        InputMode::CommandPalette => {
+            let area = centered_rect(70, 60, f.area());
+
+            f.render_widget(Clear, area);
+
+            let block = Block::default()
+                .title("Command palette")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(app.theme.background)
+                .fg(app.theme.header));
+
+            let mut lines = vec![
+                Line::from(vec![
+                    Span::styled(": ", Style::default().fg(app.theme.accent).bold()),
+                    Span::styled(format!("{}_", app.input_buffer), Style::default().fg(app.theme.good)),
+                ]),
+                Line::from(""),
+            ];
+
+            // La lista scorre per tenere visibile la voce selezionata
+            let matches = app.palette_matches();
+            let width = area.width.saturating_sub(2) as usize;
+            let rows = area.height.saturating_sub(4) as usize;
+            let first = (app.palette.selected + 1).saturating_sub(rows);
+            if matches.is_empty() {
+                lines.push(Line::from(Span::styled("No matching commands", Style::default().fg(app.theme.muted))));
+            }
+            let command_width = matches
+                .iter()
+                .map(|entry| entry.name.chars().count() + entry.args.len() + 1)
+                .max()
+                .unwrap_or(0)
+                .min(width / 2);
+            for (i, entry) in matches.iter().enumerate().skip(first).take(rows) {
+                let command = format!(" {:<width$}", format!("{} {}", entry.name, entry.args), width = command_width);
+                let keys = format!("{} ", entry.keys);
+                let description_width = width.saturating_sub(command.chars().count() + keys.chars().count() + 2);
+                let description: String = entry.description.chars().take(description_width).collect();
+                let padding = width.saturating_sub(command.chars().count() + description.chars().count() + keys.chars().count() + 2);
+
+                let selected = i == app.palette.selected;
+                let style = |base: Style| if selected { app.theme.selected_style() } else { base };
+                lines.push(Line::from(vec![
+                    Span::styled(command, style(Style::default().fg(app.theme.text).bold())),
+                    Span::styled(format!("  {}{}", description, " ".repeat(padding)), style(Style::default().fg(app.theme.muted))),
+                    Span::styled(keys, style(Style::default().fg(app.theme.accent))),
+                ]));
+            }
+
+            let paragraph = Paragraph::new(lines)
+                .block(block)
+                .style(Style::default().bg(app.theme.background));
 
             f.render_widget(paragraph, area);
         }
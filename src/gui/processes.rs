@@ -2,12 +2,12 @@ use chrono::{DateTime, TimeZone, Utc};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph, Row, Table},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
 };
 
-use crate::{App, SortColumn, gui::overlay::draw_input_overlay};
+use crate::{App, Column, SortColumn, gui::overlay::draw_input_overlay};
 
 pub fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
     let min_width_needed = 10 + 10 + 20 + 12 + 15; // line# + PID + Name(min) + CPU + Memory
@@ -21,7 +21,8 @@ pub fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
             (table_pct.min(100), 100u16.saturating_sub(table_pct))
         }
     } else {
-        (70, 30)
+        let detail = app.preferences.processes.detail_width;
+        (100 - detail, detail)
     };
 
     let chunks = if detail_percent == 0 {
@@ -38,6 +39,7 @@ pub fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
     };
 
     app.table_area = chunks[0];
+    app.detail_area = chunks.get(1).copied().unwrap_or_default();
 
     let flat = app.flatten_processes().clone();
     let visible_rows = chunks[0].height.saturating_sub(4) as usize;
@@ -50,18 +52,16 @@ pub fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
     let line_num_width = max_line_num.to_string().len().max(3) as u16;
 
     let available_width = chunks[0].width.saturating_sub(4);
+    let columns = column_widths(app, available_width, line_num_width);
+    let name_width = columns
+        .iter()
+        .find(|(column, _)| *column == Column::Name)
+        .map_or(10, |(_, width)| *width);
 
-    let pid_width = 10u16;
-    let cpu_width = 12u16;
-    let mem_width = 15u16;
-    let fixed_total = line_num_width + 1 + pid_width + cpu_width + mem_width;
-
-    let name_width = if available_width > fixed_total {
-        available_width.saturating_sub(fixed_total).max(10)
-    } else {
-        10
-    };
-
+    let filtering = app.filter.is_active();
+    // La ricerca con salto, se attiva, decide le lettere evidenziate
+    let highlighter = if app.find.query.search.is_empty() { &app.filter } else { &app.find.query };
+    let alerts = app.alerts.lock().unwrap();
     let rows: Vec<Row> = visible_processes
         .iter()
         .enumerate()
@@ -75,23 +75,29 @@ pub fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
             } else {
                 "  "
             };
-            let name_raw = format!("{}{}{}", indent, expand_indicator, node.info.name);
+            let prefix = format!("{}{}", indent, expand_indicator);
+            let name_raw = format!("{}{}", prefix, node.info.name);
 
             let max_name_len = name_width.saturating_sub(3) as usize;
-            let name = if name_raw.len() > max_name_len {
-                format!("{}...", &name_raw[..max_name_len.saturating_sub(3)])
+            let (name, truncated) = if name_raw.chars().count() > max_name_len {
+                let kept: String = name_raw.chars().take(max_name_len.saturating_sub(3)).collect();
+                (kept, "...")
             } else {
-                name_raw
+                (name_raw, "")
             };
 
             let is_selected = Some(actual_idx) == app.table_state.selected();
             let style = if is_selected {
-                Style::default()
-                    .bg(Color::DarkGray)
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD)
+                app.theme.selected_style()
+            } else if alerts.is_flagged(node.info.pid.as_u32()) {
+                Style::default().fg(app.theme.critical).add_modifier(Modifier::BOLD)
+            } else if filtering && !app.filter.matches(&node.info) {
+                // Antenati mostrati solo per dare contesto ai match
+                Style::default().fg(app.theme.muted)
+            } else if filtering {
+                Style::default().fg(app.theme.text).add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::White)
+                Style::default().fg(app.theme.text)
             };
 
             let line_num = format!(
@@ -100,63 +106,88 @@ pub fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
                 width = line_num_width as usize
             );
 
-            Row::new(vec![
-                line_num,
-                format!("{}", node.info.pid.as_u32()),
-                name,
-                format!("{:.1}%", node.info.cpu_usage),
-                format!("{:.2} MB", node.info.memory as f64 / 1024.0 / 1024.0),
-            ])
-            .style(style)
+            // Lettere trovate dalla ricerca, spostate dopo rientro e freccia
+            let offset = prefix.chars().count();
+            let highlights: Vec<usize> = highlighter
+                .name_highlights(&node.info.name)
+                .into_iter()
+                .map(|index| index + offset)
+                .collect();
+            let highlight_style = if is_selected {
+                Style::default().add_modifier(Modifier::UNDERLINED | Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)
+            };
+            let mut name_spans: Vec<Span> = name
+                .chars()
+                .enumerate()
+                .map(|(index, c)| {
+                    if highlights.contains(&index) {
+                        Span::styled(c.to_string(), highlight_style)
+                    } else {
+                        Span::raw(c.to_string())
+                    }
+                })
+                .collect();
+            name_spans.push(Span::raw(truncated));
+
+            let mut cells = vec![Cell::from(line_num)];
+            for (column, _) in &columns {
+                cells.push(match column {
+                    Column::Pid => Cell::from(format!("{}", node.info.pid.as_u32())),
+                    Column::Name => Cell::from(Line::from(name_spans.clone())),
+                    Column::Cpu => Cell::from(format!("{:.1}%", node.info.cpu_usage)),
+                    Column::Memory => Cell::from(format!("{:.2} MB", node.info.memory as f64 / 1024.0 / 1024.0)),
+                    Column::User => Cell::from(node.info.user_id.map_or("-".to_string(), |uid| uid.to_string())),
+                    Column::Status => Cell::from(node.info.status.clone()),
+                });
+            }
+
+            Row::new(cells).style(style)
         })
         .collect();
+    drop(alerts);
+
+    let mut header_cells = vec!["#".to_string()];
+    for (column, _) in &columns {
+        header_cells.push(match column.sort_column() {
+            Some(sort_column) => get_header_with_indicator(column.title(), sort_column, app),
+            None => column.title().to_string(),
+        });
+    }
 
-    let pid_header = get_header_with_indicator("PID", SortColumn::Pid, app);
-    let name_header = get_header_with_indicator("Name", SortColumn::Name, app);
-    let cpu_header = get_header_with_indicator("CPU%", SortColumn::Cpu, app);
-    let mem_header = get_header_with_indicator("Memory", SortColumn::Memory, app);
-
-    let header = Row::new(vec![
-        "#",
-        &pid_header,
-        &name_header,
-        &cpu_header,
-        &mem_header,
-    ])
-    .style(
+    let header = Row::new(header_cells).style(
         Style::default()
-            .fg(Color::Yellow)
+            .fg(app.theme.header)
             .add_modifier(Modifier::BOLD),
     );
 
-    let title = if app.user_filter.is_some()
-        || app.status_filter.is_some()
-        || app.cpu_threshold.is_some()
-        || app.memory_threshold.is_some()
+    let mode = if app.tree_mode { "" } else { " [FLAT]" };
+    let title = if app.filter.user.is_some()
+        || app.filter.status.is_some()
+        || app.filter.cpu.is_some()
+        || app.filter.memory.is_some()
+        || app.filter.pids.is_some()
     {
         format!(
-            "Processes ({}/{}) [FILTERED]",
+            "Processes ({}/{}) [FILTERED]{}",
             flat.len(),
-            app.system.processes().len()
+            app.snapshot.processes.len(),
+            mode
         )
     } else {
         format!(
-            "Processes ({}/{})",
+            "Processes ({}/{}){}",
             flat.len(),
-            app.system.processes().len()
+            app.snapshot.processes.len(),
+            mode
         )
     };
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(line_num_width + 1),
-            Constraint::Length(pid_width),
-            Constraint::Length(name_width),
-            Constraint::Length(cpu_width),
-            Constraint::Length(mem_width),
-        ],
-    )
+    let mut widths = vec![Constraint::Length(line_num_width + 1)];
+    widths.extend(columns.iter().map(|(_, width)| Constraint::Length(*width)));
+
+    let table = Table::new(rows, widths)
     .header(header)
     .block(
         Block::default()
@@ -182,6 +213,30 @@ pub fn draw_processes(f: &mut Frame, app: &mut App, area: Rect) {
     draw_input_overlay(f, app);
 }
 
+// Larghezze delle colonne configurate; Name occupa lo spazio rimanente
+pub fn column_widths(app: &App, available_width: u16, line_num_width: u16) -> Vec<(Column, u16)> {
+    let columns = &app.preferences.processes.columns;
+    // Le larghezze trascinate con il mouse sostituiscono quelle predefinite
+    let fixed_width = |column: &Column| {
+        let custom = app.preferences.processes.column_widths.get(column).copied();
+        column.fixed_width().map(|width| custom.unwrap_or(width))
+    };
+    let fixed_total = line_num_width
+        + 1
+        + columns.iter().filter_map(fixed_width).sum::<u16>();
+
+    let name_width = if available_width > fixed_total {
+        available_width.saturating_sub(fixed_total).max(10)
+    } else {
+        10
+    };
+
+    columns
+        .iter()
+        .map(|column| (*column, fixed_width(column).unwrap_or(name_width)))
+        .collect()
+}
+
 fn get_header_with_indicator(name: &str, column: SortColumn, app: &App) -> String {
     if app.sort_column == column {
         let arrow = if app.reverse_sort { "↓" } else { "↑" };
@@ -191,7 +246,7 @@ fn get_header_with_indicator(name: &str, column: SortColumn, app: &App) -> Strin
     }
 }
 
-fn draw_detail_panel(f: &mut Frame, app: &App, area: Rect) {
+fn draw_detail_panel(f: &mut Frame, app: &mut App, area: Rect) {
     f.render_widget(Clear, area);
 
     let selected_node = app
@@ -199,35 +254,44 @@ fn draw_detail_panel(f: &mut Frame, app: &App, area: Rect) {
         .selected()
         .and_then(|idx| app.get_process_at_flat_index(idx));
 
+    let selected_pid = selected_node.map(|node| node.info.pid);
+    if selected_pid != app.detail_pid {
+        app.detail_pid = selected_pid;
+        app.detail_scroll = 0;
+    }
+    let selected_node = selected_pid
+        .and_then(|pid| app.processes.slot_of(pid))
+        .and_then(|slot| app.processes.get(slot));
+
     let content = if let Some(node) = selected_node {
-        let process = app.system.process(node.info.pid);
+        let process = app.snapshot.process(node.info.pid.as_u32());
 
         let mut lines = vec![
             Line::from(vec![
-                Span::styled("PID: ", Style::default().fg(Color::Cyan)),
-                Span::styled(format!("{}", node.info.pid.as_u32()), Style::default().fg(Color::White)),
+                Span::styled("PID: ", Style::default().fg(app.theme.accent)),
+                Span::styled(format!("{}", node.info.pid.as_u32()), Style::default().fg(app.theme.text)),
             ]),
             Line::from(vec![
-                Span::styled("Name: ", Style::default().fg(Color::Cyan)),
-                Span::styled(&node.info.name, Style::default().fg(Color::White)),
+                Span::styled("Name: ", Style::default().fg(app.theme.accent)),
+                Span::styled(&node.info.name, Style::default().fg(app.theme.text)),
             ]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("CPU Usage: ", Style::default().fg(Color::Cyan)),
+                Span::styled("CPU Usage: ", Style::default().fg(app.theme.accent)),
                 Span::styled(
                     format!("{:.2}%", node.info.cpu_usage),
                     Style::default().fg(if node.info.cpu_usage > 50.0 {
-                        Color::Red
+                        app.theme.critical
                     } else {
-                        Color::Green
+                        app.theme.good
                     }),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("Memory: ", Style::default().fg(Color::Cyan)),
+                Span::styled("Memory: ", Style::default().fg(app.theme.accent)),
                 Span::styled(
                     format!("{:.2} MB", node.info.memory as f64 / 1024.0 / 1024.0),
-                    Style::default().fg(Color::White),
+                    Style::default().fg(app.theme.text),
                 ),
             ]),
         ];
@@ -235,122 +299,120 @@ fn draw_detail_panel(f: &mut Frame, app: &App, area: Rect) {
         if let Some((read, write)) = app.calculate_process_io() {
             lines.push(Line::from(vec![Span::styled(
                 "Process I/O:",
-                Style::default().fg(Color::Cyan),
+                Style::default().fg(app.theme.accent),
             )]));
             lines.push(Line::from(vec![Span::styled(
                 format!("  Read: {:.2} MB", read as f64 / 1024.0 / 1024.0),
-                Style::default().fg(Color::White),
+                Style::default().fg(app.theme.text),
             )]));
             lines.push(Line::from(vec![Span::styled(
                 format!("  Write: {:.2} MB", write as f64 / 1024.0 / 1024.0),
-                Style::default().fg(Color::White),
+                Style::default().fg(app.theme.text),
             )]));
         } else {
             lines.push(Line::from(vec![
-                Span::styled("Process I/O: ", Style::default().fg(Color::Cyan)),
-                Span::styled("N/A", Style::default().fg(Color::White)),
+                Span::styled("Process I/O: ", Style::default().fg(app.theme.accent)),
+                Span::styled("N/A", Style::default().fg(app.theme.text)),
             ]));
         }
 
         if let Some(proc) = process {
             lines.push(Line::from(vec![
-                Span::styled("Virtual Memory: ", Style::default().fg(Color::Cyan)),
+                Span::styled("Virtual Memory: ", Style::default().fg(app.theme.accent)),
                 Span::styled(format!(
                     "{:.2} MB",
-                    proc.virtual_memory() as f64 / 1024.0 / 1024.0
-                ), Style::default().fg(Color::White)),
+                    proc.virtual_memory as f64 / 1024.0 / 1024.0
+                ), Style::default().fg(app.theme.text)),
             ]));
 
             lines.push(Line::from(""));
 
-            if let Some(parent_pid) = proc.parent() {
+            if let Some(parent_pid) = proc.parent {
                 lines.push(Line::from(vec![
-                    Span::styled("Parent PID: ", Style::default().fg(Color::Cyan)),
-                    Span::raw(format!("{}", parent_pid.as_u32())),
+                    Span::styled("Parent PID: ", Style::default().fg(app.theme.accent)),
+                    Span::raw(format!("{}", parent_pid)),
                 ]));
 
-                if let Some(parent_proc) = app.system.process(parent_pid) {
+                if let Some(parent_proc) = app.snapshot.process(parent_pid) {
                     lines.push(Line::from(vec![
-                        Span::styled("Parent process: ", Style::default().fg(Color::Cyan)),
+                        Span::styled("Parent process: ", Style::default().fg(app.theme.accent)),
                         Span::styled(
-                            format!("{}", parent_proc.name().to_string_lossy().to_string()),
-                            Style::default().fg(Color::White),
+                            parent_proc.name.clone(),
+                            Style::default().fg(app.theme.text),
                         ),
                     ]));
                 } else {
                     lines.push(Line::from(vec![
-                        Span::styled("Parent process: ", Style::default().fg(Color::Cyan)),
-                        Span::styled("Unknown", Style::default().fg(Color::White)),
+                        Span::styled("Parent process: ", Style::default().fg(app.theme.accent)),
+                        Span::styled("Unknown", Style::default().fg(app.theme.text)),
                     ]));
                 }
             } else {
                 lines.push(Line::from(vec![
-                    Span::styled("Parent process: ", Style::default().fg(Color::Cyan)),
-                    Span::styled("None", Style::default().fg(Color::White)),
+                    Span::styled("Parent process: ", Style::default().fg(app.theme.accent)),
+                    Span::styled("None", Style::default().fg(app.theme.text)),
                 ]));
             }
 
             lines.push(Line::from(vec![
-                Span::styled("Status: ", Style::default().fg(Color::Cyan)),
+                Span::styled("Status: ", Style::default().fg(app.theme.accent)),
                 Span::styled(
-                    format!("{:?}", proc.status()),
-                    Style::default().fg(Color::White),
+                    proc.status.clone(),
+                    Style::default().fg(app.theme.text),
                 ),
             ]));
 
             if let Some(uid) = node.info.user_id {
                 lines.push(Line::from(vec![
-                    Span::styled("User ID: ", Style::default().fg(Color::Cyan)),
-                    Span::styled(format!("{}", uid), Style::default().fg(Color::White)),
+                    Span::styled("User ID: ", Style::default().fg(app.theme.accent)),
+                    Span::styled(format!("{}", uid), Style::default().fg(app.theme.text)),
                 ]));
             }
 
             lines.push(Line::from(vec![
-                Span::styled("Children: ", Style::default().fg(Color::Cyan)),
+                Span::styled("Children: ", Style::default().fg(app.theme.accent)),
                 Span::styled(
                     format!("{}", node.children.len()),
-                    Style::default().fg(Color::White),
+                    Style::default().fg(app.theme.text),
                 ),
             ]));
 
             lines.push(Line::from(""));
             lines.push(Line::from(vec![
-                Span::styled("Run Time: ", Style::default().fg(Color::Cyan)),
+                Span::styled("Run Time: ", Style::default().fg(app.theme.accent)),
                 Span::styled(
-                    format!("{}s", proc.run_time()),
-                    Style::default().fg(Color::White),
+                    format!(
+                        "{}s",
+                        (app.snapshot.timestamp / 1000).saturating_sub(proc.start_time as i64)
+                    ),
+                    Style::default().fg(app.theme.text),
                 ),
             ]));
 
             let datetime: DateTime<Utc> = Utc
-                .timestamp_opt(proc.start_time() as i64, 0)
+                .timestamp_opt(proc.start_time as i64, 0)
                 .single()
                 .expect("Invalid timestamp");
 
             lines.push(Line::from(vec![
-                Span::styled("Start Time: ", Style::default().fg(Color::Cyan)),
-                Span::styled(format!("{}", datetime), Style::default().fg(Color::White)),
+                Span::styled("Start Time: ", Style::default().fg(app.theme.accent)),
+                Span::styled(format!("{}", datetime), Style::default().fg(app.theme.text)),
             ]));
 
             lines.push(Line::from(""));
             lines.push(Line::from(Span::styled(
                 "Command:",
-                Style::default().fg(Color::Cyan),
+                Style::default().fg(app.theme.accent),
             )));
-            let cmd_parts: Vec<String> = proc
-                .cmd()
-                .iter()
-                .map(|s| s.to_string_lossy().to_string())
-                .collect();
-            let cmd = cmd_parts.join(" ");
+            let cmd = proc.cmd.join(" ");
             let max_width = (area.width.saturating_sub(4)) as usize;
             if cmd.len() > max_width {
                 let truncated = format!("{}...", &cmd[..max_width.saturating_sub(3)]);
-                lines.push(Line::from(Span::styled(truncated, Style::default().fg(Color::White))));
+                lines.push(Line::from(Span::styled(truncated, Style::default().fg(app.theme.text))));
             } else if cmd.is_empty() {
-                lines.push(Line::from(Span::styled("N/A", Style::default().fg(Color::White))))
+                lines.push(Line::from(Span::styled("N/A", Style::default().fg(app.theme.text))))
             } else {
-                lines.push(Line::from(Span::styled(cmd, Style::default().fg(Color::White))));
+                lines.push(Line::from(Span::styled(cmd, Style::default().fg(app.theme.text))));
             }
         }
         lines
@@ -359,19 +421,65 @@ fn draw_detail_panel(f: &mut Frame, app: &App, area: Rect) {
             Line::from(""),
             Line::from(Span::styled(
                 "No process selected",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(app.theme.muted),
             )),
         ]
     };
 
+    // La rotella scorre il pannello fino all'ultima riga
+    let max_scroll = content.len().saturating_sub(area.height.saturating_sub(2) as usize) as u16;
+    let scroll = app.detail_scroll.min(max_scroll);
+
     let paragraph = Paragraph::new(content)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .title("Process Details")
-                .style(Style::default().fg(Color::White)),
+                .style(Style::default().fg(app.theme.text)),
         )
-        .style(Style::default().fg(Color::White));
+        .scroll((scroll, 0))
+        .style(Style::default().fg(app.theme.text));
 
     f.render_widget(paragraph, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::draw_processes;
+    use crate::{SortColumn, helpers::fake};
+
+    #[test]
+    fn tree_view() {
+        let mut app = fake::app(vec![fake::machine()]);
+        // Espande sshd e bash; toggle_expand lavora sulle righe dell'ultimo disegno
+        for row in [1, 2] {
+            app.flatten_processes();
+            app.table_state.select(Some(row));
+            app.toggle_expand();
+        }
+
+        let lines = fake::render(100, 12, |f| draw_processes(f, &mut app, f.area()));
+        fake::assert_snapshot("processes_tree", &lines);
+    }
+
+    #[test]
+    fn flat_view_sorted_by_memory() {
+        let mut app = fake::app(vec![fake::machine()]);
+        app.tree_mode = false;
+        app.sort_column = SortColumn::Memory;
+        app.force_refresh();
+
+        let lines = fake::render(100, 10, |f| draw_processes(f, &mut app, f.area()));
+        fake::assert_snapshot("processes_flat_memory", &lines);
+    }
+
+    #[test]
+    fn narrow_terminal_hides_details() {
+        let mut app = fake::app(vec![fake::machine()]);
+        app.tree_mode = false;
+        app.force_refresh();
+
+        let lines = fake::render(60, 10, |f| draw_processes(f, &mut app, f.area()));
+        fake::assert_snapshot("processes_narrow", &lines);
+    }
+}
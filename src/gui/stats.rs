@@ -6,33 +6,65 @@ use ratatui::{
     widgets::{Block, Borders, Gauge, Paragraph},
 };
 
+use rtop::{cpu_freq::format_frequency, pressure::PressureStats};
+
 use crate::{
     App,
-    gui::overlay::draw_input_overlay,
+    gui::{
+        charts::{
+            draw_inline_chart, history_chart, history_points, line_dataset, time_axis, value_axis,
+        },
+        overlay::draw_input_overlay,
+        theme::Theme,
+    },
     helpers::{
         memory, network,
-        utils::{calculate_avg_cpu, generate_sparkline, generate_sparkline_with_max},
+        utils::{History, calculate_avg_cpu},
     },
 };
 
+fn format_percent(value: f64) -> String {
+    format!("{:.0}%", value)
+}
+
+fn format_gigabytes(value: f64) -> String {
+    format!("{:.1} GB", value)
+}
+
+fn format_rate(value: f64) -> String {
+    if value >= 1024.0 * 1024.0 {
+        format!("{:.1} MB/s", value / 1024.0 / 1024.0)
+    } else if value >= 1024.0 {
+        format!("{:.0} KB/s", value / 1024.0)
+    } else {
+        format!("{:.0} B/s", value)
+    }
+}
+
 pub fn draw_stats(f: &mut Frame, app: &App, area: Rect) {
-    let num_cpus = app.system.cpus().len();
-    let rows_per_column = (num_cpus + 1) / 2;
+    let num_cpus = app.snapshot.cpu_usage.len();
+    let rows_per_column = num_cpus.div_ceil(2);
     let cpu_cores_height = (rows_per_column * 2) as u16;
-    let cpu_total_height = 3 + 2 + cpu_cores_height;
+    let cpu_total_height = 9 + 2 + cpu_cores_height;
+    let pressure_height = if app.pressure.is_some() { 8 } else { 3 };
+    let power_height = 2 + app.power.batteries.len().max(1) as u16;
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(cpu_total_height), // CPU
-            Constraint::Length(7),                // Memory
-            Constraint::Min(5),                   // Networ
+            Constraint::Length(11),               // Memory
+            Constraint::Length(pressure_height),  // Pressure
+            Constraint::Length(power_height),     // Power
+            Constraint::Min(13),                  // Network
         ])
         .split(area);
 
     draw_cpu_section(f, app, chunks[0]);
     draw_memory_section(f, app, chunks[1]);
-    draw_network_section(f, app, chunks[2]);
+    draw_pressure_section(f, app, chunks[2]);
+    draw_power_section(f, app, chunks[3]);
+    draw_network_section(f, app, chunks[4]);
 
     draw_input_overlay(f, app);
 }
@@ -40,96 +72,175 @@ pub fn draw_stats(f: &mut Frame, app: &App, area: Rect) {
 fn draw_cpu_section(f: &mut Frame, app: &App, area: Rect) {
     let avg_cpu: f32 = calculate_avg_cpu(app);
 
-    let cpu_gauge = Gauge::default()
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("CPU Usage (Overall)"),
-        )
-        .gauge_style(Style::default().fg(Color::Cyan))
-        .percent(avg_cpu as u16);
-
     let cpu_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .constraints([Constraint::Length(9), Constraint::Min(1)])
         .split(area);
 
-    f.render_widget(cpu_gauge, cpu_chunks[0]);
+    // Media di tutti i core per ogni campione della history
+    let cores = app.cpu_history.len().max(1) as f64;
+    let avg_history: History<f64> = app
+        .cpu_history
+        .first()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, &(time, _))| {
+            let sum: f64 = app
+                .cpu_history
+                .iter()
+                .map(|h| h.get(i).map_or(0.0, |&(_, usage)| usage as f64))
+                .sum();
+            (time, sum / cores)
+        })
+        .collect();
+    let avg_points = history_points(&avg_history, app.snapshot.timestamp, |v| v);
+
+    let cpu_chart = history_chart(
+        format!("CPU Usage (Overall): {:.1}%", avg_cpu),
+        vec![line_dataset("avg", &avg_points, app.theme.accent)],
+        time_axis(&app.theme, app.preferences.refresh.history_length, app.update_interval),
+        value_axis(&app.theme, 100.0, format_percent),
+    );
+
+    f.render_widget(cpu_chart, cpu_chunks[0]);
 
     let per_core_cols = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(cpu_chunks[1]);
 
-    let cpus = app.system.cpus();
-    let half = (cpus.len() + 1) / 2;
-
-    let build_core_lines = |slice: &[sysinfo::Cpu]| {
-        let mut lines = Vec::new();
-        for (i, cpu) in slice.iter().enumerate() {
-            let global_idx = app
-                .system
-                .cpus()
-                .iter()
-                .position(|c| std::ptr::eq(c, cpu))
-                .unwrap_or(i);
-            let usage = cpu.cpu_usage();
+    let cpus = &app.snapshot.cpu_usage;
+    let half = cpus.len().div_ceil(2);
 
-            let history = app
-                .cpu_history
-                .get(global_idx)
-                .map(|h| &h[..])
-                .unwrap_or(&[]);
-            let sparkline = if !history.is_empty() {
-                generate_sparkline(history)
-            } else {
-                String::from("▁".repeat(20))
-            };
-
-            let color = if usage > 80.0 {
-                Color::Red
-            } else if usage > 50.0 {
-                Color::Yellow
-            } else {
-                Color::Green
-            };
+    draw_core_column(f, app, 0..half, per_core_cols[0], "Per-Core Usage (1/2)");
+    draw_core_column(f, app, half..cpus.len(), per_core_cols[1], "Per-Core Usage (2/2)");
+}
 
-            lines.push(Line::from(vec![
-                Span::styled(
-                    format!("CPU{:2}: ", global_idx),
-                    Style::default().fg(Color::Cyan),
-                ),
-                Span::styled(format!("{:5.1}%", usage), Style::default().fg(color)),
-                Span::raw("  "),
-                Span::styled(sparkline, Style::default().fg(Color::Blue)),
-            ]));
+fn draw_core_column(
+    f: &mut Frame,
+    app: &App,
+    cores: std::ops::Range<usize>,
+    area: Rect,
+    title: &str,
+) {
+    let block = Block::default().borders(Borders::ALL).title(title.to_string());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let cpus = &app.snapshot.cpu_usage;
+    let x_span = app.preferences.refresh.history_length.saturating_sub(1) as f64
+        * app.update_interval.as_secs_f64();
+
+    for (row, core) in cores.enumerate() {
+        let y = inner.y + (row * 2) as u16;
+        if y >= inner.bottom() {
+            break;
+        }
+        let usage = cpus[core];
 
-            lines.push(Line::from(Span::raw(" ")));
+        let color = if usage > 80.0 {
+            app.theme.critical
+        } else if usage > 50.0 {
+            app.theme.warning
+        } else {
+            app.theme.good
+        };
+
+        let usage_line = Line::from(vec![
+            Span::styled(format!("CPU{:2}: ", core), Style::default().fg(app.theme.accent)),
+            Span::styled(format!("{:5.1}%", usage), Style::default().fg(color)),
+        ]);
+        let usage_points = app
+            .cpu_history
+            .get(core)
+            .map(|h| history_points(h, app.snapshot.timestamp, |v| v as f64))
+            .unwrap_or_default();
+        draw_line_with_chart(f, inner, y, usage_line, &usage_points, x_span, 100.0, app.theme.info);
+
+        if y + 1 >= inner.bottom() {
+            break;
         }
-        lines
-    };
 
-    let left_lines = build_core_lines(&cpus[..half]);
-    let right_lines = build_core_lines(&cpus[half..]);
+        // Storico frequenze scalato sul massimo del core (o sul picco osservato)
+        let freq_line = build_freq_line(app, core);
+        let (freq_points, freq_max) = match app.cpu_freq_history.get(core) {
+            Some(history) => {
+                let peak = history.iter().map(|&(_, mhz)| mhz).max().unwrap_or(0);
+                let max = app
+                    .cpu_freq
+                    .get(core)
+                    .and_then(|info| info.max_mhz)
+                    .unwrap_or(peak)
+                    .max(peak)
+                    .max(1);
+                (history_points(history, app.snapshot.timestamp, |v| v as f64), max as f64)
+            }
+            None => (Vec::new(), 1.0),
+        };
+        draw_line_with_chart(f, inner, y + 1, freq_line, &freq_points, x_span, freq_max, app.theme.muted);
+    }
+}
 
-    let left_widget = Paragraph::new(left_lines)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Per-Core Usage (1/2)"),
-        )
-        .alignment(Alignment::Left);
+// Disegna il testo della riga e, nello spazio rimanente, un grafico braille della history
+#[allow(clippy::too_many_arguments)]
+fn draw_line_with_chart(
+    f: &mut Frame,
+    inner: Rect,
+    y: u16,
+    line: Line<'static>,
+    points: &[(f64, f64)],
+    x_span: f64,
+    max: f64,
+    color: Color,
+) {
+    let text_width = (line.width() as u16 + 2).min(inner.width);
+    f.render_widget(
+        Paragraph::new(line),
+        Rect { x: inner.x, y, width: text_width, height: 1 },
+    );
+
+    let chart_area = Rect {
+        x: inner.x + text_width,
+        y,
+        width: inner.width.saturating_sub(text_width),
+        height: 1,
+    };
+    draw_inline_chart(f, chart_area, points, x_span, max, color);
+}
 
-    let right_widget = Paragraph::new(right_lines)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Per-Core Usage (2/2)"),
-        )
-        .alignment(Alignment::Left);
+fn build_freq_line(app: &App, core: usize) -> Line<'static> {
+    let Some(info) = app.cpu_freq.get(core) else {
+        return Line::from(Span::raw(" "));
+    };
 
-    f.render_widget(left_widget, per_core_cols[0]);
-    f.render_widget(right_widget, per_core_cols[1]);
+    let mut spans = vec![
+        Span::raw("        "),
+        Span::styled(
+            format!("{:>9}", format_frequency(info.current_mhz)),
+            Style::default().fg(if info.throttled { app.theme.critical } else { app.theme.text }),
+        ),
+    ];
+
+    if let (Some(min), Some(max)) = (info.min_mhz, info.max_mhz) {
+        spans.push(Span::styled(
+            format!(" [{}-{}]", format_frequency(min), format_frequency(max)),
+            Style::default().fg(app.theme.muted),
+        ));
+    }
+
+    if let Some(ref governor) = info.governor {
+        spans.push(Span::styled(
+            format!(" {}", governor),
+            Style::default().fg(app.theme.highlight),
+        ));
+    }
+
+    if info.throttled {
+        spans.push(Span::styled(" THROTTLED", Style::default().fg(app.theme.critical)));
+    }
+
+    Line::from(spans)
 }
 
 fn draw_memory_section(f: &mut Frame, app: &App, area: Rect) {
@@ -137,7 +248,7 @@ fn draw_memory_section(f: &mut Frame, app: &App, area: Rect) {
 
     let mem_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Length(4)])
+        .constraints([Constraint::Length(3), Constraint::Min(4)])
         .split(area);
 
     let mem_gauge = Gauge::default()
@@ -147,37 +258,220 @@ fn draw_memory_section(f: &mut Frame, app: &App, area: Rect) {
             total_mem,
             (used_mem / total_mem) * 100.0
         )))
-        .gauge_style(Style::default().fg(Color::Green))
+        .gauge_style(Style::default().fg(app.theme.good))
         .percent(mem_percent);
 
     f.render_widget(mem_gauge, mem_chunks[0]);
 
-    let history_width = mem_chunks[1].width.saturating_sub(4) as usize;
+    let mem_points = history_points(&app.memory_history, app.snapshot.timestamp, |v| v);
+    let mem_chart = history_chart(
+        "Memory Trend".to_string(),
+        vec![line_dataset("used", &mem_points, app.theme.good)],
+        time_axis(&app.theme, app.preferences.refresh.history_length, app.update_interval),
+        value_axis(&app.theme, total_mem.max(0.1), format_gigabytes),
+    );
 
-    let mem_sparkline = if app.memory_history.is_empty() {
-        "▁".repeat(history_width.min(60))
-    } else if app.memory_history.len() >= history_width {
-        let start_idx = app.memory_history.len() - history_width;
-        let sampled: Vec<f32> = app.memory_history[start_idx..]
-            .iter()
-            .map(|&x| x as f32)
-            .collect();
-        generate_sparkline_with_max(&sampled, total_mem as f32)
+    f.render_widget(mem_chart, mem_chunks[1]);
+}
+
+fn pressure_color(theme: &Theme, value: f32) -> Color {
+    if value > 40.0 {
+        theme.critical
+    } else if value > 10.0 {
+        theme.warning
     } else {
-        let mem_data: Vec<f32> = app.memory_history.iter().map(|&x| x as f32).collect();
-        generate_sparkline_with_max(&mem_data, total_mem as f32)
+        theme.good
+    }
+}
+
+fn pressure_row(theme: &Theme, label: &str, stats: &PressureStats) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled(format!("{:8}", label), Style::default().fg(theme.accent)),
+        Span::raw("some "),
+    ];
+    for value in [stats.some.avg10, stats.some.avg60, stats.some.avg300] {
+        spans.push(Span::styled(
+            format!("{:7.2}", value),
+            Style::default().fg(pressure_color(theme, value)),
+        ));
+    }
+
+    spans.push(Span::raw("  full "));
+    match stats.full {
+        Some(full) => {
+            for value in [full.avg10, full.avg60, full.avg300] {
+                spans.push(Span::styled(
+                    format!("{:7.2}", value),
+                    Style::default().fg(pressure_color(theme, value)),
+                ));
+            }
+        }
+        None => spans.push(Span::styled(
+            format!("{:>21}", "N/A"),
+            Style::default().fg(theme.muted),
+        )),
+    }
+
+    Line::from(spans)
+}
+
+fn draw_pressure_section(f: &mut Frame, app: &App, area: Rect) {
+    let Some(pressure) = app.pressure else {
+        let unavailable = Paragraph::new(Line::from(Span::styled(
+            "Pressure stall information not available (requires Linux 4.20+ with CONFIG_PSI)",
+            Style::default().fg(app.theme.muted),
+        )))
+        .block(Block::default().borders(Borders::ALL).title("Pressure Stall"));
+        f.render_widget(unavailable, area);
+        return;
     };
 
-    let history_text = vec![Line::from(vec![
-        Span::styled("History: ", Style::default().fg(Color::Cyan)),
-        Span::styled(mem_sparkline, Style::default().fg(Color::Green)),
-    ])];
+    let psi_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(
+                "{:13}{:>7}{:>7}{:>7}{:7}{:>7}{:>7}{:>7}",
+                "", "avg10", "avg60", "avg300", "", "avg10", "avg60", "avg300"
+            ),
+            Style::default().fg(app.theme.muted),
+        )),
+        pressure_row(&app.theme, "CPU", &pressure.cpu),
+        pressure_row(&app.theme, "Memory", &pressure.memory),
+        pressure_row(&app.theme, "I/O", &pressure.io),
+        Line::from(""),
+    ];
+
+    // PSI del cgroup del processo selezionato (solo cgroup v2)
+    match app.selected_cgroup_pressure() {
+        Some((name, cgroup)) => {
+            lines.push(Line::from(vec![
+                Span::styled("cgroup: ", Style::default().fg(app.theme.accent)),
+                Span::styled(name, Style::default().fg(app.theme.text)),
+                Span::raw("  some avg10 "),
+                Span::styled(
+                    format!("cpu {:.2}", cgroup.cpu.some.avg10),
+                    Style::default().fg(pressure_color(&app.theme, cgroup.cpu.some.avg10)),
+                ),
+                Span::raw(" / "),
+                Span::styled(
+                    format!("mem {:.2}", cgroup.memory.some.avg10),
+                    Style::default().fg(pressure_color(&app.theme, cgroup.memory.some.avg10)),
+                ),
+                Span::raw(" / "),
+                Span::styled(
+                    format!("io {:.2}", cgroup.io.some.avg10),
+                    Style::default().fg(pressure_color(&app.theme, cgroup.io.some.avg10)),
+                ),
+            ]));
+        }
+        None => {
+            lines.push(Line::from(Span::styled(
+                "cgroup: no pressure data for the selected process",
+                Style::default().fg(app.theme.muted),
+            )));
+        }
+    }
 
-    let history = Paragraph::new(history_text)
-        .block(Block::default().borders(Borders::ALL).title("Memory Trend"))
+    let values = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Pressure Stall (% of time stalled)"),
+        )
         .alignment(Alignment::Left);
 
-    f.render_widget(history, mem_chunks[1]);
+    f.render_widget(values, psi_chunks[0]);
+
+    let cpu_points = history_points(&app.pressure_history, app.snapshot.timestamp, |(cpu, _, _)| cpu as f64);
+    let mem_points = history_points(&app.pressure_history, app.snapshot.timestamp, |(_, mem, _)| mem as f64);
+    let io_points = history_points(&app.pressure_history, app.snapshot.timestamp, |(_, _, io)| io as f64);
+
+    // Scala adattiva: i valori PSI sono tipicamente bassi
+    let peak = app
+        .pressure_history
+        .iter()
+        .map(|&(_, (cpu, mem, io))| cpu.max(mem).max(io) as f64)
+        .fold(0.0, f64::max);
+    let max = (peak * 1.2).clamp(10.0, 100.0);
+
+    let history = history_chart(
+        "Pressure History (some avg10)".to_string(),
+        vec![
+            line_dataset("cpu", &cpu_points, app.theme.accent),
+            line_dataset("mem", &mem_points, app.theme.good),
+            line_dataset("io", &io_points, app.theme.info),
+        ],
+        time_axis(&app.theme, app.preferences.refresh.history_length, app.update_interval),
+        value_axis(&app.theme, max, format_percent),
+    );
+
+    f.render_widget(history, psi_chunks[1]);
+}
+
+fn draw_power_section(f: &mut Frame, app: &App, area: Rect) {
+    let ac = match app.power.ac_online {
+        Some(true) => Span::styled("AC: online", Style::default().fg(app.theme.good)),
+        Some(false) => Span::styled("AC: offline", Style::default().fg(app.theme.warning)),
+        None => Span::styled("AC: unknown", Style::default().fg(app.theme.muted)),
+    };
+
+    let lines: Vec<Line> = if app.power.batteries.is_empty() {
+        vec![Line::from(vec![
+            ac,
+            Span::styled("  |  No battery detected", Style::default().fg(app.theme.muted)),
+        ])]
+    } else {
+        app.power
+            .batteries
+            .iter()
+            .map(|battery| {
+                let capacity_color = match battery.capacity {
+                    Some(c) if c <= 15 => app.theme.critical,
+                    Some(c) if c <= 40 => app.theme.warning,
+                    _ => app.theme.good,
+                };
+
+                let mut spans = vec![
+                    Span::styled(format!("{}: ", battery.name), Style::default().fg(app.theme.accent)),
+                    Span::styled(
+                        battery.capacity.map_or("N/A".to_string(), |c| format!("{}%", c)),
+                        Style::default().fg(capacity_color),
+                    ),
+                    Span::styled(format!(" ({})", battery.status), Style::default().fg(app.theme.text)),
+                ];
+
+                if let Some(power) = battery.power_watts {
+                    spans.push(Span::styled(
+                        format!("  {:.1} W", power),
+                        Style::default().fg(app.theme.highlight),
+                    ));
+                }
+
+                if let Some(remaining) = battery.time_remaining {
+                    let secs = remaining.as_secs();
+                    let label = if battery.status == "Charging" { "until full" } else { "remaining" };
+                    spans.push(Span::styled(
+                        format!("  {}h{:02}m {}", secs / 3600, (secs % 3600) / 60, label),
+                        Style::default().fg(app.theme.text),
+                    ));
+                }
+
+                spans.push(Span::raw("  |  "));
+                spans.push(ac.clone());
+                Line::from(spans)
+            })
+            .collect()
+    };
+
+    let power = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Power"))
+        .alignment(Alignment::Left);
+
+    f.render_widget(power, area);
 }
 
 fn draw_network_section(f: &mut Frame, app: &App, area: Rect) {
@@ -185,55 +479,46 @@ fn draw_network_section(f: &mut Frame, app: &App, area: Rect) {
 
     let net_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(6), Constraint::Min(1)])
+        .constraints([Constraint::Length(10), Constraint::Min(3)])
         .split(area);
 
-    let inner_width = net_chunks[0].width.saturating_sub(4) as usize; // Remove borders
-    let label_width = "↓ Received: ".len();
-    let sparkline_width = inner_width.saturating_sub(label_width).max(20);
-
-    let sample_network = |history: &[(u64, u64)], extract_fn: fn(&(u64, u64)) -> u64| -> String {
-        if history.is_empty() {
-            return "▁".repeat(sparkline_width.min(60));
-        }
-
-        if history.len() >= sparkline_width {
-            let start_idx = history.len() - sparkline_width;
-            let sampled: Vec<f32> = history[start_idx..]
-                .iter()
-                .map(|item| extract_fn(item) as f32 / 1024.0 / 1024.0)
-                .collect();
-            generate_sparkline(&sampled)
-        } else {
-            let data: Vec<f32> = history
-                .iter()
-                .map(|item| extract_fn(item) as f32 / 1024.0 / 1024.0)
-                .collect();
-            generate_sparkline(&data)
-        }
-    };
-
-    let rx_sparkline = sample_network(&app.network_history, |&(rx, _)| rx);
-    let tx_sparkline = sample_network(&app.network_history, |&(_, tx)| tx);
+    // La history contiene i byte trasferiti dal campione precedente: li divido
+    // per il tempo passato tra i due, per avere byte/s
+    let mut previous = None;
+    let rates: History<(f64, f64)> = app
+        .network_history
+        .iter()
+        .map(|&(time, (rx, tx))| {
+            let secs = previous.map_or(app.update_interval.as_secs_f64(), |previous| {
+                (time - previous) as f64 / 1000.0
+            });
+            previous = Some(time);
+            let secs = secs.max(0.001);
+            (time, (rx as f64 / secs, tx as f64 / secs))
+        })
+        .collect();
+    let rx_points = history_points(&rates, app.snapshot.timestamp, |(rx, _)| rx);
+    let tx_points = history_points(&rates, app.snapshot.timestamp, |(_, tx)| tx);
 
-    let summary = Paragraph::new(vec![
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("↓ Received: ", Style::default().fg(Color::Green)),
-            Span::styled(rx_sparkline, Style::default().fg(Color::Green)),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("↑ Sent:     ", Style::default().fg(Color::Blue)),
-            Span::styled(tx_sparkline, Style::default().fg(Color::Blue)),
-        ]),
-    ])
-    .block(Block::default().borders(Borders::ALL).title(format!(
-        "Network History (Total: ↓ {:.2} MB / ↑ {:.2} MB)",
-        total_rx as f64 / 1024.0 / 1024.0,
-        total_tx as f64 / 1024.0 / 1024.0
-    )))
-    .alignment(Alignment::Left);
+    let peak = rx_points
+        .iter()
+        .chain(tx_points.iter())
+        .map(|&(_, v)| v)
+        .fold(0.0, f64::max);
+
+    let summary = history_chart(
+        format!(
+            "Network History (Total: ↓ {:.2} MB / ↑ {:.2} MB)",
+            total_rx as f64 / 1024.0 / 1024.0,
+            total_tx as f64 / 1024.0 / 1024.0
+        ),
+        vec![
+            line_dataset("↓ rx", &rx_points, app.theme.good),
+            line_dataset("↑ tx", &tx_points, app.theme.info),
+        ],
+        time_axis(&app.theme, app.preferences.refresh.history_length, app.update_interval),
+        value_axis(&app.theme, (peak * 1.2).max(1024.0), format_rate),
+    );
 
     f.render_widget(summary, net_chunks[0]);
 
@@ -242,13 +527,13 @@ fn draw_network_section(f: &mut Frame, app: &App, area: Rect) {
         .iter()
         .map(|(name, rx, tx)| {
             Line::from(vec![
-                Span::styled(format!("{:12}: ", name), Style::default().fg(Color::Cyan)),
+                Span::styled(format!("{:12}: ", name), Style::default().fg(app.theme.accent)),
                 Span::styled(
                     format!("↓ {:8.2} MB", rx),
-                    Style::default().fg(Color::Green),
+                    Style::default().fg(app.theme.good),
                 ),
                 Span::raw(" / "),
-                Span::styled(format!("↑ {:8.2} MB", tx), Style::default().fg(Color::Blue)),
+                Span::styled(format!("↑ {:8.2} MB", tx), Style::default().fg(app.theme.info)),
             ])
         })
         .collect();
@@ -263,3 +548,20 @@ fn draw_network_section(f: &mut Frame, app: &App, area: Rect) {
 
     f.render_widget(interfaces, net_chunks[1]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::draw_stats;
+    use crate::helpers::fake;
+
+    #[test]
+    fn stats_page() {
+        let mut app = fake::app(fake::timeline(6));
+        for _ in 0..5 {
+            app.refresh();
+        }
+
+        let lines = fake::render(100, 60, |f| draw_stats(f, &app, f.area()));
+        fake::assert_snapshot("stats", &lines);
+    }
+}
@@ -0,0 +1,255 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::helpers::config::ThemeConfig;
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub text: Color,
+    pub background: Color,
+    pub accent: Color,
+    pub header: Color,
+    pub selection: Color,
+    pub selection_text: Color,
+    pub muted: Color,
+    pub good: Color,
+    pub warning: Color,
+    pub critical: Color,
+    pub info: Color,
+    pub highlight: Color,
+    // Senza colori la selezione è resa con il video inverso
+    pub monochrome: bool,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            text: Color::White,
+            background: Color::Black,
+            accent: Color::Cyan,
+            header: Color::Yellow,
+            selection: Color::DarkGray,
+            selection_text: Color::White,
+            muted: Color::DarkGray,
+            good: Color::Green,
+            warning: Color::Yellow,
+            critical: Color::Red,
+            info: Color::Blue,
+            highlight: Color::Magenta,
+            monochrome: false,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            text: Color::Black,
+            background: Color::White,
+            accent: Color::Blue,
+            header: Color::Indexed(94),
+            selection: Color::Indexed(153),
+            selection_text: Color::Black,
+            muted: Color::Indexed(244),
+            good: Color::Indexed(28),
+            warning: Color::Indexed(130),
+            critical: Color::Indexed(160),
+            info: Color::Indexed(25),
+            highlight: Color::Magenta,
+            monochrome: false,
+        }
+    }
+
+    pub fn monochrome() -> Self {
+        Self {
+            text: Color::Reset,
+            background: Color::Reset,
+            accent: Color::Reset,
+            header: Color::Reset,
+            selection: Color::Reset,
+            selection_text: Color::Reset,
+            muted: Color::Reset,
+            good: Color::Reset,
+            warning: Color::Reset,
+            critical: Color::Reset,
+            info: Color::Reset,
+            highlight: Color::Reset,
+            monochrome: true,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            text: Color::White,
+            background: Color::Black,
+            accent: Color::LightCyan,
+            header: Color::LightYellow,
+            selection: Color::White,
+            selection_text: Color::Black,
+            muted: Color::Gray,
+            good: Color::LightGreen,
+            warning: Color::LightYellow,
+            critical: Color::LightRed,
+            info: Color::LightBlue,
+            highlight: Color::LightMagenta,
+            monochrome: false,
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "monochrome" | "mono" => Some(Self::monochrome()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    pub fn selected_style(&self) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            Style::default()
+                .bg(self.selection)
+                .fg(self.selection_text)
+                .add_modifier(Modifier::BOLD)
+        }
+    }
+
+    // Risolve [colors] partendo dal tema indicato e applicando gli override;
+    // con NO_COLOR impostato l'output è sempre monocromatico, ma gli errori
+    // della configurazione vengono riportati lo stesso
+    pub fn from_config(
+        colors: &ThemeConfig,
+        themes: &BTreeMap<String, ThemeConfig>,
+        no_color: bool,
+    ) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let theme = resolve(colors, themes, &mut errors, &mut Vec::new());
+        (if no_color { Self::monochrome() } else { theme }, errors)
+    }
+}
+
+pub fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+// `chain` contiene i temi utente che si stanno risolvendo: un tema con il
+// nome di uno predefinito lo sostituisce e può partire da quello
+fn resolve<'a>(
+    config: &'a ThemeConfig,
+    themes: &'a BTreeMap<String, ThemeConfig>,
+    errors: &mut Vec<String>,
+    chain: &mut Vec<&'a str>,
+) -> Theme {
+    let base_name = config.theme.as_deref().unwrap_or("dark");
+    let inherited = chain.contains(&base_name);
+
+    let mut theme = if let Some((name, user_theme)) = themes.get_key_value(base_name)
+        && !inherited
+    {
+        chain.push(name);
+        resolve(user_theme, themes, errors, chain)
+    } else if let Some(builtin) = Theme::builtin(base_name) {
+        builtin
+    } else if inherited {
+        errors.push(format!("Theme \"{}\" is based on itself", base_name));
+        Theme::dark()
+    } else {
+        errors.push(format!("Unknown theme \"{}\"", base_name));
+        Theme::dark()
+    };
+
+    let mut apply = |name: &str, value: &Option<String>, target: &mut Color| {
+        if let Some(value) = value {
+            match Color::from_str(value) {
+                Ok(color) => *target = color,
+                Err(_) => errors.push(format!("Unknown color \"{}\" for {}", value, name)),
+            }
+        }
+    };
+
+    apply("text", &config.text, &mut theme.text);
+    apply("background", &config.background, &mut theme.background);
+    apply("accent", &config.accent, &mut theme.accent);
+    apply("header", &config.header, &mut theme.header);
+    apply("selection", &config.selection, &mut theme.selection);
+    apply("selection_text", &config.selection_text, &mut theme.selection_text);
+    apply("muted", &config.muted, &mut theme.muted);
+    apply("good", &config.good, &mut theme.good);
+    apply("warning", &config.warning, &mut theme.warning);
+    apply("critical", &config.critical, &mut theme.critical);
+    apply("info", &config.info, &mut theme.info);
+    apply("highlight", &config.highlight, &mut theme.highlight);
+
+    theme
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ratatui::style::Color;
+
+    use super::Theme;
+    use crate::helpers::config::ThemeConfig;
+
+    fn based_on(base: &str) -> ThemeConfig {
+        ThemeConfig {
+            theme: Some(base.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn no_color_forces_monochrome() {
+        let (theme, errors) = Theme::from_config(&based_on("light"), &BTreeMap::new(), true);
+        assert!(theme.monochrome);
+        assert_eq!(theme.accent, Color::Reset);
+        assert!(errors.is_empty());
+
+        // Gli errori del config non spariscono con NO_COLOR
+        let (theme, errors) = Theme::from_config(&based_on("nope"), &BTreeMap::new(), true);
+        assert!(theme.monochrome);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn user_themes_resolve_and_override_builtins() {
+        let mut solarized = based_on("light");
+        solarized.accent = Some("#268bd2".to_string());
+        let mut dark = based_on("dark");
+        dark.critical = Some("magenta".to_string());
+        let themes = BTreeMap::from([("solarized".to_string(), solarized), ("dark".to_string(), dark)]);
+
+        let mut colors = based_on("solarized");
+        colors.header = Some("42".to_string());
+        let (theme, errors) = Theme::from_config(&colors, &themes, false);
+        assert!(errors.is_empty());
+        assert_eq!(theme.accent, Color::Rgb(0x26, 0x8b, 0xd2));
+        assert_eq!(theme.header, Color::Indexed(42));
+        assert_eq!(theme.text, Theme::light().text);
+
+        // Un tema utente "dark" sostituisce quello predefinito, anche come default
+        let (theme, errors) = Theme::from_config(&ThemeConfig::default(), &themes, false);
+        assert!(errors.is_empty());
+        assert_eq!(theme.critical, Color::Magenta);
+        assert_eq!(theme.accent, Theme::dark().accent);
+    }
+
+    #[test]
+    fn unknown_themes_and_colors_are_errors() {
+        let (_, errors) = Theme::from_config(&based_on("solarized"), &BTreeMap::new(), false);
+        assert_eq!(errors, ["Unknown theme \"solarized\""]);
+
+        let mut colors = based_on("dark");
+        colors.accent = Some("not-a-color".to_string());
+        let (theme, errors) = Theme::from_config(&colors, &BTreeMap::new(), false);
+        assert_eq!(errors, ["Unknown color \"not-a-color\" for accent"]);
+        assert_eq!(theme.accent, Theme::dark().accent);
+
+        let themes = BTreeMap::from([("a".to_string(), based_on("b")), ("b".to_string(), based_on("a"))]);
+        let (_, errors) = Theme::from_config(&based_on("a"), &themes, false);
+        assert_eq!(errors, ["Theme \"a\" is based on itself"]);
+    }
+}
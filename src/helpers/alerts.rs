@@ -0,0 +1,497 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+
+use rtop::{pressure::SystemPressure, snapshot::Snapshot};
+
+use crate::{App, InputMode};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    // Percentuali sul totale
+    Cpu,
+    MemoryUsed,
+    MemoryAvailable,
+    Swap,
+    // Media sugli ultimi 10 secondi, in percentuale
+    PressureCpu,
+    PressureMemory,
+    PressureIo,
+    // Valutate per ogni processo: percentuale e MB
+    ProcessCpu,
+    ProcessMemory,
+}
+
+impl AlertMetric {
+    fn is_process(self) -> bool {
+        matches!(self, AlertMetric::ProcessCpu | AlertMetric::ProcessMemory)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AlertMetric::Cpu => "cpu",
+            AlertMetric::MemoryUsed => "memory_used",
+            AlertMetric::MemoryAvailable => "memory_available",
+            AlertMetric::Swap => "swap",
+            AlertMetric::PressureCpu => "pressure_cpu",
+            AlertMetric::PressureMemory => "pressure_memory",
+            AlertMetric::PressureIo => "pressure_io",
+            AlertMetric::ProcessCpu => "process_cpu",
+            AlertMetric::ProcessMemory => "process_memory",
+        }
+    }
+
+    pub fn format_value(self, value: f64) -> String {
+        match self {
+            AlertMetric::ProcessMemory => format!("{:.0} MB", value),
+            _ => format!("{:.1}%", value),
+        }
+    }
+}
+
+fn default_hysteresis() -> f64 {
+    5.0
+}
+
+fn default_bell() -> bool {
+    true
+}
+
+// Una voce [[alerts]] del file di configurazione
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: AlertMetric,
+    // Solo per le metriche dei processi: parte del nome, senza distinzione di maiuscole
+    #[serde(default)]
+    pub process: Option<String>,
+    #[serde(default)]
+    pub above: Option<f64>,
+    #[serde(default)]
+    pub below: Option<f64>,
+    // Per quanto tempo la condizione deve restare vera prima di scattare
+    #[serde(default)]
+    pub for_secs: u64,
+    // Margine da superare in senso opposto prima che l'allarme rientri
+    #[serde(default = "default_hysteresis")]
+    pub hysteresis: f64,
+    #[serde(default = "default_bell")]
+    pub bell: bool,
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+impl AlertRule {
+    fn threshold(&self) -> f64 {
+        self.above.or(self.below).unwrap_or_default()
+    }
+
+    fn breached(&self, value: f64) -> bool {
+        match (self.above, self.below) {
+            (Some(limit), _) => value > limit,
+            (_, Some(limit)) => value < limit,
+            _ => false,
+        }
+    }
+
+    fn cleared(&self, value: f64) -> bool {
+        match (self.above, self.below) {
+            (Some(limit), _) => value <= limit - self.hysteresis,
+            (_, Some(limit)) => value >= limit + self.hysteresis,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Default)]
+struct AlertState {
+    // Timestamp del campione in cui la condizione è diventata vera
+    since: Option<i64>,
+    active: bool,
+    value: f64,
+    process: Option<String>,
+    // Ultima valutazione in cui la chiave era presente
+    seen: u64,
+}
+
+pub struct ActiveAlert<'a> {
+    pub rule: &'a AlertRule,
+    pub pid: Option<u32>,
+    pub process: Option<&'a str>,
+    pub value: f64,
+}
+
+// Al massimo uno per regola e per campione: i processi che scattano insieme
+// suonano e lanciano il comando una volta sola, con il valore peggiore
+struct Trigger {
+    rule: usize,
+    pid: Option<u32>,
+    process: Option<String>,
+    value: f64,
+    // Quanti processi hanno fatto scattare la regola
+    count: usize,
+}
+
+#[derive(Default)]
+pub struct Alerts {
+    rules: Vec<AlertRule>,
+    // Uno stato per regola, indicizzato per PID (0 per le metriche di sistema).
+    // Esiste solo mentre la condizione è vera o l'allarme è attivo
+    states: Vec<HashMap<u32, AlertState>>,
+    evaluations: u64,
+    // Comandi che non sono partiti, da mostrare nell'interfaccia
+    failures: Vec<String>,
+}
+
+impl Alerts {
+    // Le regole non valide vengono scartate e riportate come errori
+    pub fn from_config(rules: &[AlertRule]) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let mut valid = Vec::new();
+
+        for rule in rules {
+            let problem = if rule.above.is_some() == rule.below.is_some() {
+                Some("needs exactly one of \"above\" or \"below\"")
+            } else if rule.process.is_some() && !rule.metric.is_process() {
+                Some("\"process\" only applies to process_cpu and process_memory")
+            } else if rule.hysteresis < 0.0 {
+                Some("\"hysteresis\" can't be negative")
+            } else {
+                None
+            };
+
+            match problem {
+                Some(problem) => errors.push(format!("Alert \"{}\" {}", rule.name, problem)),
+                None => valid.push(rule.clone()),
+            }
+        }
+
+        let states = valid.iter().map(|_| HashMap::new()).collect();
+        (
+            Self {
+                rules: valid,
+                states,
+                evaluations: 0,
+                failures: Vec::new(),
+            },
+            errors,
+        )
+    }
+
+    pub fn active(&self) -> impl Iterator<Item = ActiveAlert<'_>> {
+        self.rules.iter().zip(&self.states).flat_map(|(rule, states)| {
+            states.iter().filter(|(_, state)| state.active).map(move |(pid, state)| ActiveAlert {
+                rule,
+                pid: rule.metric.is_process().then_some(*pid),
+                process: state.process.as_deref(),
+                value: state.value,
+            })
+        })
+    }
+
+    pub fn is_flagged(&self, pid: u32) -> bool {
+        self.rules
+            .iter()
+            .zip(&self.states)
+            .any(|(rule, states)| rule.metric.is_process() && states.get(&pid).is_some_and(|s| s.active))
+    }
+
+    fn evaluate(&mut self, snapshot: &Snapshot) -> Vec<Trigger> {
+        let mut triggers = Vec::new();
+        self.evaluations += 1;
+        let evaluation = self.evaluations;
+
+        for (index, (rule, states)) in self.rules.iter().zip(&mut self.states).enumerate() {
+            let mut fired: Option<Trigger> = None;
+            let mut count = 0;
+
+            for (key, process, value) in rule_values(rule, snapshot) {
+                // Senza `process` la regola vede tutti i processi: lo stato si
+                // crea solo per quelli che superano la soglia
+                let state = match states.get_mut(&key) {
+                    Some(state) => state,
+                    None if rule.breached(value) => states.entry(key).or_insert_with(|| AlertState {
+                        process: process.map(str::to_string),
+                        ..Default::default()
+                    }),
+                    None => continue,
+                };
+                state.seen = evaluation;
+                state.value = value;
+
+                if state.active {
+                    if rule.cleared(value) {
+                        state.active = false;
+                        state.since = None;
+                    }
+                    continue;
+                }
+
+                if !rule.breached(value) {
+                    state.since = None;
+                    continue;
+                }
+
+                let since = *state.since.get_or_insert(snapshot.timestamp);
+                if snapshot.timestamp - since >= rule.for_secs as i64 * 1000 {
+                    state.active = true;
+                    count += 1;
+                    let worse = fired.as_ref().is_none_or(|fired| {
+                        if rule.above.is_some() { value > fired.value } else { value < fired.value }
+                    });
+                    if worse {
+                        fired = Some(Trigger {
+                            rule: index,
+                            pid: rule.metric.is_process().then_some(key),
+                            process: state.process.clone(),
+                            value,
+                            count: 0,
+                        });
+                    }
+                }
+            }
+
+            // I processi terminati non possono più scattare né restare attivi
+            states.retain(|_, state| state.seen == evaluation && (state.active || state.since.is_some()));
+
+            if let Some(mut trigger) = fired {
+                trigger.count = count;
+                triggers.push(trigger);
+            }
+        }
+
+        triggers
+    }
+}
+
+// Valori correnti della metrica: (chiave, nome del processo, valore)
+fn rule_values<'a>(rule: &AlertRule, snapshot: &'a Snapshot) -> Vec<(u32, Option<&'a str>, f64)> {
+    let percent = |part: u64, total: u64| {
+        if total == 0 { 0.0 } else { part as f64 / total as f64 * 100.0 }
+    };
+    let pressure = |select: fn(&SystemPressure) -> f32| {
+        snapshot.pressure.as_ref().map(|p| select(p) as f64)
+    };
+
+    let system = match rule.metric {
+        AlertMetric::Cpu => {
+            let cores = snapshot.cpu_usage.len().max(1) as f64;
+            Some(snapshot.cpu_usage.iter().map(|&u| u as f64).sum::<f64>() / cores)
+        }
+        AlertMetric::MemoryUsed => Some(percent(snapshot.memory_used, snapshot.memory_total)),
+        AlertMetric::MemoryAvailable => Some(percent(
+            snapshot.memory_total.saturating_sub(snapshot.memory_used),
+            snapshot.memory_total,
+        )),
+        AlertMetric::Swap => Some(percent(snapshot.swap_used, snapshot.swap_total)),
+        AlertMetric::PressureCpu => pressure(|p| p.cpu.some.avg10),
+        AlertMetric::PressureMemory => pressure(|p| p.memory.some.avg10),
+        AlertMetric::PressureIo => pressure(|p| p.io.some.avg10),
+        AlertMetric::ProcessCpu | AlertMetric::ProcessMemory => {
+            let pattern = rule.process.as_ref().map(|p| p.to_lowercase());
+            return snapshot
+                .processes
+                .iter()
+                .filter(|p| pattern.as_ref().is_none_or(|pattern| p.name.to_lowercase().contains(pattern)))
+                .map(|p| {
+                    let value = if rule.metric == AlertMetric::ProcessCpu {
+                        p.cpu_usage as f64
+                    } else {
+                        p.memory as f64 / 1024.0 / 1024.0
+                    };
+                    (p.pid, Some(p.name.as_str()), value)
+                })
+                .collect();
+        }
+    };
+
+    system.map(|value| (0, None, value)).into_iter().collect()
+}
+
+impl Alerts {
+    // Valuta le regole e, per quelle che scattano, suona e lancia il comando.
+    // Con il collector gira nel suo thread, a ogni campione periodico
+    pub fn run(&mut self, snapshot: &Snapshot) {
+        for trigger in self.evaluate(snapshot) {
+            let rule = &self.rules[trigger.rule];
+
+            if rule.bell {
+                // Su stderr, per non sporcare l'output della modalità batch
+                let mut stderr = std::io::stderr();
+                stderr.write_all(b"\x07").ok();
+                stderr.flush().ok();
+            }
+
+            let Some(command) = &rule.command else {
+                continue;
+            };
+            let spawned = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .env("RTOP_ALERT", &rule.name)
+                .env("RTOP_METRIC", rule.metric.name())
+                .env("RTOP_VALUE", format!("{:.2}", trigger.value))
+                .env("RTOP_THRESHOLD", rule.threshold().to_string())
+                .env("RTOP_PID", trigger.pid.map_or(String::new(), |pid| pid.to_string()))
+                .env("RTOP_PROCESS", trigger.process.unwrap_or_default())
+                .env("RTOP_COUNT", trigger.count.to_string())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+
+            match spawned {
+                // Attende in un thread a parte per non lasciare processi zombie
+                Ok(mut child) => {
+                    thread::spawn(move || child.wait());
+                }
+                Err(err) => self
+                    .failures
+                    .push(format!("Cannot run the command of \"{}\": {}", rule.name, err)),
+            }
+        }
+    }
+}
+
+impl App {
+    // Chiamata a ogni refresh sincrono; con il collector gli allarmi vengono
+    // valutati nel suo thread
+    pub fn evaluate_alerts(&mut self) {
+        self.alerts.lock().unwrap().run(&self.snapshot);
+        self.show_alert_failures();
+    }
+
+    pub fn show_alert_failures(&mut self) {
+        let failures = std::mem::take(&mut self.alerts.lock().unwrap().failures);
+        if !failures.is_empty() {
+            self.errors.extend(failures.into_iter().map(|message| ("Alert".to_string(), message)));
+            self.input_mode = InputMode::Error;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rtop::snapshot::Snapshot;
+
+    use super::{AlertMetric, AlertRule, Alerts};
+    use crate::helpers::fake;
+
+    fn rule(metric: AlertMetric, above: Option<f64>, below: Option<f64>) -> AlertRule {
+        AlertRule {
+            name: "test".to_string(),
+            metric,
+            process: None,
+            above,
+            below,
+            for_secs: 0,
+            hysteresis: 5.0,
+            bell: false,
+            command: None,
+        }
+    }
+
+    // Tutti i core allo stesso carico, un secondo dopo l'altro
+    fn cpu_at(second: i64, usage: f32) -> Snapshot {
+        let mut snapshot = fake::machine();
+        snapshot.timestamp += second * 1000;
+        snapshot.cpu_usage = vec![usage; 4];
+        snapshot
+    }
+
+    fn single(rule: AlertRule) -> Alerts {
+        let (alerts, errors) = Alerts::from_config(&[rule]);
+        assert!(errors.is_empty());
+        alerts
+    }
+
+    #[test]
+    fn above_and_below_thresholds() {
+        // La macchina finta usa il 25% della memoria
+        let mut used = single(rule(AlertMetric::MemoryUsed, Some(20.0), None));
+        assert_eq!(used.evaluate(&fake::machine()).len(), 1);
+        let mut used = single(rule(AlertMetric::MemoryUsed, Some(30.0), None));
+        assert!(used.evaluate(&fake::machine()).is_empty());
+
+        let mut available = single(rule(AlertMetric::MemoryAvailable, None, Some(80.0)));
+        let triggers = available.evaluate(&fake::machine());
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].value, 75.0);
+        assert!(triggers[0].pid.is_none());
+        assert_eq!(available.active().count(), 1);
+
+        let (_, errors) = Alerts::from_config(&[
+            rule(AlertMetric::Cpu, Some(1.0), Some(2.0)),
+            rule(AlertMetric::Cpu, None, None),
+        ]);
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn hysteresis_clears_only_past_the_margin() {
+        let mut alerts = single(rule(AlertMetric::Cpu, Some(50.0), None));
+        assert_eq!(alerts.evaluate(&cpu_at(0, 60.0)).len(), 1);
+        // Ancora attivo: non scatta di nuovo e non rientra sotto la soglia
+        assert!(alerts.evaluate(&cpu_at(1, 70.0)).is_empty());
+        assert!(alerts.evaluate(&cpu_at(2, 48.0)).is_empty());
+        assert_eq!(alerts.active().count(), 1);
+
+        assert!(alerts.evaluate(&cpu_at(3, 45.0)).is_empty());
+        assert_eq!(alerts.active().count(), 0);
+        assert_eq!(alerts.evaluate(&cpu_at(4, 60.0)).len(), 1);
+    }
+
+    #[test]
+    fn for_secs_holds_before_triggering() {
+        let mut hold = rule(AlertMetric::Cpu, Some(50.0), None);
+        hold.for_secs = 2;
+        let mut alerts = single(hold);
+
+        assert!(alerts.evaluate(&cpu_at(0, 60.0)).is_empty());
+        assert!(alerts.evaluate(&cpu_at(1, 60.0)).is_empty());
+        // Un campione sotto la soglia fa ripartire l'attesa
+        assert!(alerts.evaluate(&cpu_at(2, 40.0)).is_empty());
+        assert!(alerts.evaluate(&cpu_at(3, 60.0)).is_empty());
+        assert!(alerts.evaluate(&cpu_at(4, 60.0)).is_empty());
+        assert_eq!(alerts.evaluate(&cpu_at(5, 60.0)).len(), 1);
+    }
+
+    #[test]
+    fn process_rules_match_names_and_fire_once() {
+        let mut firefox = rule(AlertMetric::ProcessCpu, Some(10.0), None);
+        firefox.process = Some("FIRE".to_string());
+        let mut alerts = single(firefox);
+        let triggers = alerts.evaluate(&fake::machine());
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].pid, Some(400));
+        assert_eq!(triggers[0].process.as_deref(), Some("firefox"));
+        assert!(alerts.is_flagged(400));
+        assert!(!alerts.is_flagged(300));
+
+        // Senza `process` valgono tutti: cargo e firefox scattano insieme, ma
+        // il comando parte una volta con il valore peggiore
+        let mut any = single(rule(AlertMetric::ProcessCpu, Some(10.0), None));
+        let triggers = any.evaluate(&fake::machine());
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].pid, Some(300));
+        assert_eq!(triggers[0].count, 2);
+        assert!(any.is_flagged(300) && any.is_flagged(400));
+        // Solo i processi sopra la soglia hanno uno stato
+        assert_eq!(any.states[0].len(), 2);
+
+        // Un processo terminato non resta attivo
+        let mut snapshot = fake::machine();
+        snapshot.timestamp += 1000;
+        snapshot.processes.retain(|process| process.pid != 300);
+        assert!(any.evaluate(&snapshot).is_empty());
+        assert!(!any.is_flagged(300));
+        assert_eq!(any.active().count(), 1);
+    }
+}
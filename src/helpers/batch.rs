@@ -0,0 +1,351 @@
+use std::{
+    io::{self, Write},
+    thread,
+};
+
+use anyhow::Result;
+use chrono::Local;
+use clap::ValueEnum;
+use serde_json::{Map, Value, json};
+use sysinfo::MINIMUM_CPU_UPDATE_INTERVAL;
+
+use crate::{
+    App, Column, ProcessNode,
+    helpers::{memory::calculate_memory, utils::calculate_avg_cpu},
+};
+
+#[derive(Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum BatchFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+// Larghezza massima della colonna Name nell'output testuale
+const TEXT_NAME_WIDTH: usize = 32;
+
+impl App {
+    // Modalità non interattiva: stampa `iterations` campioni (0 = senza fine)
+    pub fn run_batch(&mut self, iterations: u64, format: BatchFormat) -> Result<()> {
+        let mut stdout = io::stdout().lock();
+        let mut iteration = 0;
+
+        if format == BatchFormat::Csv {
+            self.write_csv_header(&mut stdout)?;
+        }
+
+        loop {
+            // Al primo giro basta l'intervallo minimo per avere valori di CPU validi
+            if iteration > 0 {
+                thread::sleep(self.update_interval);
+            } else {
+                thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+            }
+            self.force_refresh();
+
+            let rows = self.batch_rows();
+            let result = match format {
+                BatchFormat::Text => self.write_text(&mut stdout, &rows),
+                BatchFormat::Json => self.write_json(&mut stdout, iteration, &rows),
+                BatchFormat::Csv => self.write_csv(&mut stdout, iteration, &rows),
+            };
+
+            // Pipe chiusa (es. `rtop -b | head`): esco senza errori
+            if let Err(err) = result.and_then(|_| stdout.flush()) {
+                if err.kind() == io::ErrorKind::BrokenPipe {
+                    return Ok(());
+                }
+                return Err(err.into());
+            }
+
+            iteration += 1;
+            if iterations != 0 && iteration >= iterations {
+                return Ok(());
+            }
+        }
+    }
+
+    // Tutti i processi che passano i filtri, in ordine e ignorando lo stato espanso
+    fn batch_rows(&self) -> Vec<(usize, &ProcessNode)> {
+        fn collect<'a>(app: &'a App, slots: &[usize], depth: usize, rows: &mut Vec<(usize, &'a ProcessNode)>) {
+            for node in slots.iter().filter_map(|&slot| app.processes.get(slot)) {
+                if app.filter.matches(&node.info) {
+                    rows.push((depth, node));
+                }
+                collect(app, &node.children, depth + 1, rows);
+            }
+        }
+
+        let mut rows = Vec::new();
+        collect(self, self.processes.roots(), 0, &mut rows);
+        rows
+    }
+
+    fn write_text(&self, out: &mut impl Write, rows: &[(usize, &ProcessNode)]) -> io::Result<()> {
+        let (used_mem, total_mem, percent_used) = calculate_memory(self);
+        let [one, five, fifteen] = self.snapshot.load_average;
+
+        writeln!(
+            out,
+            "rtop - {}  up {}  load average: {:.2}, {:.2}, {:.2}",
+            Local::now().format("%H:%M:%S"),
+            format_uptime(self.snapshot.uptime),
+            one,
+            five,
+            fifteen,
+        )?;
+        writeln!(
+            out,
+            "Tasks: {} total, {} shown  CPU: {:.1}%  Mem: {:.2}/{:.2} GB ({}%)",
+            self.snapshot.processes.len(),
+            rows.len(),
+            calculate_avg_cpu(self),
+            used_mem,
+            total_mem,
+            percent_used,
+        )?;
+        writeln!(out)?;
+
+        let columns = &self.preferences.processes.columns;
+        let header: Vec<String> = columns
+            .iter()
+            .map(|column| pad(column.title(), text_width(*column), *column))
+            .collect();
+        writeln!(out, "{}", header.join(" ").trim_end())?;
+
+        for (depth, node) in rows {
+            let cells: Vec<String> = columns
+                .iter()
+                .map(|column| {
+                    let value = match column {
+                        Column::Name if self.tree_mode => {
+                            format!("{}{}", "  ".repeat(*depth), node.info.name)
+                        }
+                        _ => text_value(*column, node),
+                    };
+                    pad(&value, text_width(*column), *column)
+                })
+                .collect();
+            writeln!(out, "{}", cells.join(" ").trim_end())?;
+        }
+
+        writeln!(out)
+    }
+
+    fn write_json(&self, out: &mut impl Write, iteration: u64, rows: &[(usize, &ProcessNode)]) -> io::Result<()> {
+        let processes: Vec<Value> = rows
+            .iter()
+            .map(|(depth, node)| {
+                let mut process = Map::new();
+                for column in &self.preferences.processes.columns {
+                    process.insert(column_key(*column).to_string(), json_value(*column, node));
+                }
+                if self.tree_mode {
+                    process.insert("depth".to_string(), json!(depth));
+                }
+                Value::Object(process)
+            })
+            .collect();
+
+        let sample = json!({
+            "timestamp": Local::now().to_rfc3339(),
+            "iteration": iteration,
+            "uptime": self.snapshot.uptime,
+            "load_average": self.snapshot.load_average,
+            "cpu": {
+                "total": calculate_avg_cpu(self),
+                "cores": self.snapshot.cpu_usage,
+            },
+            "memory": {
+                "used": self.snapshot.memory_used,
+                "total": self.snapshot.memory_total,
+                "swap_used": self.snapshot.swap_used,
+                "swap_total": self.snapshot.swap_total,
+            },
+            "tasks": self.snapshot.processes.len(),
+            "processes": processes,
+        });
+
+        writeln!(out, "{}", sample)
+    }
+
+    // Le statistiche di sistema sono ripetute su ogni riga, così il CSV resta piatto
+    fn write_csv_header(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut header = vec!["timestamp", "iteration", "cpu_total", "memory_used", "memory_total"];
+        header.extend(self.preferences.processes.columns.iter().map(|c| column_key(*c)));
+        writeln!(out, "{}", header.join(","))
+    }
+
+    fn write_csv(&self, out: &mut impl Write, iteration: u64, rows: &[(usize, &ProcessNode)]) -> io::Result<()> {
+        let timestamp = Local::now().to_rfc3339();
+        let cpu_total = format!("{:.1}", calculate_avg_cpu(self));
+        let memory_used = self.snapshot.memory_used.to_string();
+        let memory_total = self.snapshot.memory_total.to_string();
+
+        for (_, node) in rows {
+            let mut fields = vec![
+                timestamp.clone(),
+                iteration.to_string(),
+                cpu_total.clone(),
+                memory_used.clone(),
+                memory_total.clone(),
+            ];
+            for column in &self.preferences.processes.columns {
+                let value = match json_value(*column, node) {
+                    Value::String(s) => s,
+                    Value::Null => String::new(),
+                    other => other.to_string(),
+                };
+                fields.push(csv_field(&value));
+            }
+            writeln!(out, "{}", fields.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+fn column_key(column: Column) -> &'static str {
+    match column {
+        Column::Pid => "pid",
+        Column::Name => "name",
+        Column::Cpu => "cpu",
+        Column::Memory => "memory",
+        Column::User => "user",
+        Column::Status => "status",
+    }
+}
+
+// Valori grezzi (byte, percentuali) per JSON e CSV
+fn json_value(column: Column, node: &ProcessNode) -> Value {
+    match column {
+        Column::Pid => json!(node.info.pid.as_u32()),
+        Column::Name => json!(node.info.name),
+        Column::Cpu => json!(node.info.cpu_usage),
+        Column::Memory => json!(node.info.memory),
+        Column::User => node.info.user_id.map_or(Value::Null, |uid| json!(uid)),
+        Column::Status => json!(node.info.status),
+    }
+}
+
+// Stesso formato della tabella nella TUI
+fn text_value(column: Column, node: &ProcessNode) -> String {
+    match column {
+        Column::Pid => node.info.pid.as_u32().to_string(),
+        Column::Name => node.info.name.clone(),
+        Column::Cpu => format!("{:.1}%", node.info.cpu_usage),
+        Column::Memory => format!("{:.2} MB", node.info.memory as f64 / 1024.0 / 1024.0),
+        Column::User => node.info.user_id.map_or("-".to_string(), |uid| uid.to_string()),
+        Column::Status => node.info.status.clone(),
+    }
+}
+
+fn text_width(column: Column) -> usize {
+    column.fixed_width().map_or(TEXT_NAME_WIDTH, |width| width as usize)
+}
+
+// I numeri sono allineati a destra, il testo a sinistra
+fn pad(value: &str, width: usize, column: Column) -> String {
+    let value: String = value.chars().take(width).collect();
+    match column {
+        Column::Pid | Column::Cpu | Column::Memory => format!("{:>width$}", value),
+        _ => format!("{:<width$}", value),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_uptime(secs: u64) -> String {
+    let days = secs / 86400;
+    let hours = (secs % 86400) / 3600;
+    let minutes = (secs % 3600) / 60;
+    if days > 0 {
+        format!("{}d {:02}:{:02}", days, hours, minutes)
+    } else {
+        format!("{:02}:{:02}", hours, minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::{csv_field, format_uptime};
+    use crate::helpers::fake;
+
+    fn app() -> crate::App {
+        let mut machine = fake::machine();
+        machine.load_average = [1.5, 0.75, 0.25];
+        machine.uptime = 90_061;
+        machine.processes[2].name = "bash, \"login\"".to_string();
+        let mut app = fake::app(vec![machine]);
+        app.tree_mode = false;
+        app.force_refresh();
+        app
+    }
+
+    fn output(write: impl FnOnce(&mut Vec<u8>)) -> String {
+        let mut out = Vec::new();
+        write(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn json_uses_the_sampled_machine() {
+        let app = app();
+        let rows = app.batch_rows();
+        let line = output(|out| app.write_json(out, 3, &rows).unwrap());
+        assert_eq!(line.lines().count(), 1);
+
+        let sample: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(sample["iteration"], 3);
+        assert_eq!(sample["uptime"], 90_061);
+        assert_eq!(sample["load_average"], serde_json::json!([1.5, 0.75, 0.25]));
+        assert_eq!(sample["tasks"], 5);
+        let processes = sample["processes"].as_array().unwrap();
+        assert_eq!(processes.len(), 5);
+        assert_eq!(processes[0]["pid"], 300);
+        assert_eq!(processes[0]["name"], "cargo");
+        assert!(processes[0].get("depth").is_none());
+    }
+
+    #[test]
+    fn text_header_uses_the_sampled_machine() {
+        let app = app();
+        let rows = app.batch_rows();
+        let text = output(|out| app.write_text(out, &rows).unwrap());
+        let first = text.lines().next().unwrap();
+        assert!(first.ends_with("up 1d 01:01  load average: 1.50, 0.75, 0.25"), "{}", first);
+    }
+
+    #[test]
+    fn csv_rows_are_escaped() {
+        let app = app();
+        let rows = app.batch_rows();
+        let header = output(|out| app.write_csv_header(out).unwrap());
+        assert!(header.starts_with("timestamp,iteration,cpu_total,memory_used,memory_total,"));
+
+        let csv = output(|out| app.write_csv(out, 0, &rows).unwrap());
+        assert_eq!(csv.lines().count(), 5);
+        let bash = csv.lines().find(|line| line.contains("bash")).unwrap();
+        assert!(bash.contains(",\"bash, \"\"login\"\"\","), "{}", bash);
+        let columns = header.trim_end().split(',').count();
+        assert!(csv.lines().filter(|line| !line.contains("bash")).all(|line| line.split(',').count() == columns));
+    }
+
+    #[test]
+    fn csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("carriage\rreturn"), "\"carriage\rreturn\"");
+        assert_eq!(format_uptime(3660), "01:01");
+    }
+}
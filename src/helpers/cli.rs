@@ -0,0 +1,323 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+use rtop::filter::SearchKind;
+use sysinfo::{Pid, Users};
+
+use crate::{App, Page, SortColumn, helpers::batch::BatchFormat};
+
+// Le opzioni da riga di comando valgono solo per la sessione corrente e non
+// vengono mai scritte nel file di configurazione
+#[derive(Parser, Default)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Update interval in milliseconds (100 - 6000)
+    #[arg(short, long, value_name = "MS")]
+    pub interval: Option<u64>,
+
+    /// Sort column
+    #[arg(short, long, value_enum)]
+    pub sort: Option<SortColumn>,
+
+    /// Sort in descending order
+    #[arg(short, long, conflicts_with = "no_reverse")]
+    pub reverse: bool,
+
+    /// Sort in ascending order, even if the configuration says reverse_sort
+    #[arg(long)]
+    pub no_reverse: bool,
+
+    /// Show processes as a tree
+    #[arg(long, conflicts_with = "flat")]
+    pub tree: bool,
+
+    /// Show processes as a flat list
+    #[arg(long)]
+    pub flat: bool,
+
+    /// Only show processes owned by this user (name or uid)
+    #[arg(short, long)]
+    pub user: Option<String>,
+
+    /// Only monitor the listed PIDs (comma separated or repeated)
+    #[arg(short, long, value_delimiter = ',')]
+    pub pid: Vec<u32>,
+
+    /// Initial filter, e.g. "cpu>5,mem>100,status=run,user=1000,firefox";
+    /// memory is in MB unless followed by K, M or G. fuzzy=TEXT and
+    /// regex=PATTERN change how the search text is matched
+    #[arg(short, long, value_name = "EXPR", value_parser = parse_filter)]
+    pub filter: Option<FilterExpr>,
+
+    /// Page shown at startup
+    #[arg(long, value_enum)]
+    pub page: Option<Page>,
+
+    /// Read the configuration from this file instead of ~/.config/rtop/config.toml
+    #[arg(short, long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Do not capture mouse events
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// Print samples to stdout instead of starting the interface
+    #[arg(short, long)]
+    pub batch: bool,
+
+    /// Number of samples taken in batch mode (default 1) or when recording
+    /// (default 0 = until interrupted)
+    #[arg(short = 'n', long)]
+    pub iterations: Option<u64>,
+
+    /// Output format for batch mode
+    #[arg(short = 'o', long, value_enum, default_value_t = BatchFormat::Text, requires = "batch")]
+    pub format: BatchFormat,
+
+    /// Record samples to FILE without starting the interface
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["batch", "replay"])]
+    pub record: Option<PathBuf>,
+
+    /// Open a recording made with --record instead of live data
+    #[arg(long, value_name = "FILE", conflicts_with = "batch")]
+    pub replay: Option<PathBuf>,
+
+    /// Serve OpenMetrics on ADDR (e.g. 127.0.0.1:9184), overrides [metrics] listen
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["batch", "record"])]
+    pub metrics: Option<String>,
+
+    /// Only run the metrics exporter, without the interface
+    #[arg(long, conflicts_with = "replay")]
+    pub headless: bool,
+
+    /// Monitor another machine through "rtop agent": HOST:PORT, unix:PATH,
+    /// ssh:HOST or exec:COMMAND
+    #[arg(long, value_name = "TARGET", conflicts_with = "replay")]
+    pub remote: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Serve snapshots and process actions to a remote rtop over stdin/stdout
+    Agent {
+        /// Listen on HOST:PORT or unix:PATH instead of stdin/stdout. Addresses other
+        /// than loopback need a shared token in RTOP_AGENT_TOKEN (also set for the
+        /// client) or --insecure
+        #[arg(long, value_name = "ADDR")]
+        listen: Option<String>,
+
+        /// Accept clients on a non-loopback address without a token: anyone who can
+        /// reach the port can signal and renice processes
+        #[arg(long, requires = "listen")]
+        insecure: bool,
+    },
+}
+
+// Stessi filtri disponibili dal menu (w), più un testo di ricerca libero
+#[derive(Clone, Default)]
+pub struct FilterExpr {
+    pub query: Option<String>,
+    pub search_kind: Option<SearchKind>,
+    pub user: Option<String>,
+    pub status: Option<String>,
+    pub cpu: Option<f32>,
+    // In byte
+    pub memory: Option<u64>,
+}
+
+// Dimensione in MB, oppure con unità K, M o G (anche KB, MB, GB)
+fn parse_size(value: &str) -> Option<u64> {
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "K" | "KB" => 1024,
+        "" | "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+fn parse_filter(expr: &str) -> Result<FilterExpr, String> {
+    let mut filter = FilterExpr::default();
+
+    for term in expr.split([',', ' ']).map(str::trim).filter(|t| !t.is_empty()) {
+        // Le soglie sono solo minime: "cpu<5" non deve diventare un testo di ricerca
+        if let Some((key, _)) = term.split_once('<')
+            && matches!(key.trim(), "cpu" | "mem" | "memory")
+        {
+            return Err(format!("unsupported operator in \"{}\", use > or >=", term));
+        }
+        if let Some((key, value)) = term.split_once(">=").or_else(|| term.split_once('>')) {
+            let value = value.trim();
+            match key.trim() {
+                "cpu" => {
+                    let cpu = value
+                        .trim_end_matches('%')
+                        .parse::<f32>()
+                        .map_err(|_| format!("invalid CPU threshold \"{}\"", value))?;
+                    filter.cpu = Some(cpu.clamp(0.0, 100.0));
+                }
+                "mem" | "memory" => {
+                    let bytes = parse_size(value)
+                        .ok_or_else(|| format!("invalid memory threshold \"{}\", use MB or K, M, G", value))?;
+                    filter.memory = Some(bytes);
+                }
+                other => return Err(format!("unknown threshold \"{}\", use cpu or mem", other)),
+            }
+        } else if let Some((key, value)) = term.split_once('=') {
+            let value = value.trim().to_string();
+            match key.trim() {
+                "user" => filter.user = Some(value),
+                "status" => filter.status = Some(value),
+                "name" => filter.query = Some(value),
+                "fuzzy" | "regex" => {
+                    filter.search_kind = Some(if key.trim() == "fuzzy" { SearchKind::Fuzzy } else { SearchKind::Regex });
+                    filter.query = Some(value);
+                }
+                other => {
+                    return Err(format!(
+                        "unknown filter \"{}\", use user, status, name, fuzzy or regex",
+                        other
+                    ))
+                }
+            }
+        } else {
+            filter.query = Some(term.to_string());
+        }
+    }
+
+    Ok(filter)
+}
+
+// Il filtro utente confronta l'uid, quindi i nomi vengono risolti qui
+fn resolve_user(user: &str) -> Result<String, String> {
+    if user.parse::<u32>().is_ok() {
+        return Ok(user.to_string());
+    }
+    Users::new_with_refreshed_list()
+        .iter()
+        .find(|u| u.name() == user)
+        .map(|u| u.id().to_string())
+        .ok_or_else(|| format!("Unknown user \"{}\"", user))
+}
+
+impl App {
+    // Applica le opzioni ai campi di sessione, lasciando invariate le preferenze
+    pub fn apply_cli(&mut self, cli: &Cli) {
+        if let Some(ms) = cli.interval {
+            self.update_interval = std::time::Duration::from_millis(ms.clamp(100, 6000));
+        }
+        if let Some(sort) = cli.sort {
+            self.sort_column = sort;
+        }
+        if cli.reverse || cli.no_reverse {
+            self.reverse_sort = cli.reverse;
+        }
+        if cli.tree || cli.flat {
+            self.tree_mode = cli.tree;
+        }
+        if let Some(page) = cli.page {
+            self.page = page;
+        }
+        if cli.no_mouse {
+            self.mouse = false;
+        }
+        if !cli.pid.is_empty() {
+            let pids: HashSet<Pid> = cli.pid.iter().map(|&pid| Pid::from_u32(pid)).collect();
+            self.filter.pids = Some(pids);
+        }
+
+        if let Some(filter) = &cli.filter {
+            if let Some(query) = &filter.query {
+                self.filter.search = query.clone();
+            }
+            if let Some(search_kind) = filter.search_kind {
+                self.filter.search_kind = search_kind;
+            }
+            self.filter.status = filter.status.clone();
+            self.filter.cpu = filter.cpu;
+            self.filter.memory = filter.memory;
+        }
+
+        let user = cli
+            .user
+            .as_ref()
+            .or(cli.filter.as_ref().and_then(|f| f.user.as_ref()));
+        if let Some(user) = user {
+            match resolve_user(user) {
+                Ok(uid) => self.filter.user = Some(uid),
+                Err(message) => {
+                    self.errors.push(("Invalid argument".to_string(), message));
+                    self.input_mode = crate::InputMode::Error;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use rtop::filter::SearchKind;
+
+    use super::{Cli, parse_filter, parse_size, resolve_user};
+    use crate::helpers::fake;
+
+    #[test]
+    fn valid_filter_expressions() {
+        let filter = parse_filter("cpu>5, mem>=100,status=run user=1000 firefox").unwrap();
+        assert_eq!(filter.cpu, Some(5.0));
+        assert_eq!(filter.memory, Some(100 * 1024 * 1024));
+        assert_eq!(filter.status.as_deref(), Some("run"));
+        assert_eq!(filter.user.as_deref(), Some("1000"));
+        assert_eq!(filter.query.as_deref(), Some("firefox"));
+        assert!(filter.search_kind.is_none());
+
+        let filter = parse_filter("cpu>150%,mem>2G,regex=^(nginx|php)").unwrap();
+        assert_eq!(filter.cpu, Some(100.0));
+        assert_eq!(filter.memory, Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(filter.query.as_deref(), Some("^(nginx|php)"));
+        assert!(filter.search_kind == Some(SearchKind::Regex));
+
+        let filter = parse_filter("fuzzy=ffx").unwrap();
+        assert!(filter.search_kind == Some(SearchKind::Fuzzy));
+        assert_eq!(filter.query.as_deref(), Some("ffx"));
+    }
+
+    #[test]
+    fn bad_operators_and_units_are_rejected() {
+        for expr in ["cpu<5", "mem<100", "cpu=5", "load>1", "size=3", "cpu>five", "mem>5XB", "mem>-1", "cpu>5MB"] {
+            assert!(parse_filter(expr).is_err(), "{} was accepted", expr);
+        }
+        assert_eq!(parse_size("512K"), Some(512 * 1024));
+        assert_eq!(parse_size("64 mb"), Some(64 * 1024 * 1024));
+        assert_eq!(parse_size("GB"), None);
+    }
+
+    #[test]
+    fn users_are_resolved_to_uids() {
+        assert_eq!(resolve_user("1000").unwrap(), "1000");
+        assert_eq!(resolve_user("root").unwrap(), "0");
+        let err = resolve_user("no-such-user-rtop").unwrap_err();
+        assert!(err.contains("no-such-user-rtop"));
+    }
+
+    #[test]
+    fn sort_order_overrides_the_config() {
+        let mut app = fake::app(vec![fake::machine()]);
+        app.reverse_sort = true;
+        app.apply_cli(&Cli::parse_from(["rtop", "--no-reverse"]));
+        assert!(!app.reverse_sort);
+        app.apply_cli(&Cli::parse_from(["rtop", "--reverse"]));
+        assert!(app.reverse_sort);
+        // Senza opzioni resta quello della configurazione
+        app.apply_cli(&Cli::parse_from(["rtop"]));
+        assert!(app.reverse_sort);
+
+        assert!(Cli::try_parse_from(["rtop", "--reverse", "--no-reverse"]).is_err());
+    }
+}
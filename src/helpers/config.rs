@@ -0,0 +1,335 @@
+use std::collections::BTreeMap;
+
+use rtop::filter::SearchKind;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Column, Page, SortColumn,
+    helpers::{alerts::AlertRule, keymap::KeybindingsConfig, views::ViewConfig},
+};
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmPolicy {
+    Always,
+    Critical,
+    Never,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneralConfig {
+    pub default_page: Page,
+    pub mouse: bool,
+    // Ripristina filtri e rami espansi dell'ultima sessione
+    pub restore_session: bool,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self {
+            default_page: Page::Processes,
+            mouse: true,
+            restore_session: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct RefreshConfig {
+    pub update_interval_ms: u64,
+    pub interval_step_ms: u64,
+    pub history_length: usize,
+    pub auto_refresh: bool,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self {
+            update_interval_ms: 1000,
+            interval_step_ms: 100,
+            history_length: 300,
+            auto_refresh: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcessesConfig {
+    pub sort_column: SortColumn,
+    pub reverse_sort: bool,
+    pub tree_mode: bool,
+    pub columns: Vec<Column>,
+    // Percentuale della larghezza occupata dal pannello dettagli
+    pub detail_width: u16,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub column_widths: BTreeMap<Column, u16>,
+    pub search_mode: SearchKind,
+}
+
+impl Default for ProcessesConfig {
+    fn default() -> Self {
+        Self {
+            sort_column: SortColumn::Cpu,
+            reverse_sort: true,
+            tree_mode: true,
+            columns: vec![Column::Pid, Column::Name, Column::Cpu, Column::Memory],
+            detail_width: 30,
+            column_widths: BTreeMap::new(),
+            search_mode: SearchKind::Substring,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfirmConfig {
+    pub kill: ConfirmPolicy,
+}
+
+impl Default for ConfirmConfig {
+    fn default() -> Self {
+        Self {
+            kill: ConfirmPolicy::Critical,
+        }
+    }
+}
+
+// Usato sia per [colors] che per i temi utente in [themes.<nome>]
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    #[serde(alias = "base", skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub muted: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub good: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub critical: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    // Indirizzo dell'exporter, vuoto = disattivato
+    pub listen: String,
+    // Numero massimo di processi esportati singolarmente
+    pub max_processes: usize,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            listen: String::new(),
+            max_processes: 50,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preferences {
+    pub general: GeneralConfig,
+    pub refresh: RefreshConfig,
+    pub processes: ProcessesConfig,
+    pub confirm: ConfirmConfig,
+    pub colors: ThemeConfig,
+    pub themes: BTreeMap<String, ThemeConfig>,
+    pub views: BTreeMap<String, ViewConfig>,
+    pub keybindings: KeybindingsConfig,
+    pub metrics: MetricsConfig,
+    // Le regole non vengono modificate dall'interfaccia, quindi non si riscrivono
+    #[serde(skip_serializing)]
+    pub alerts: Vec<AlertRule>,
+}
+
+impl Preferences {
+    // Riporta i valori numerici entro limiti sensati
+    pub fn normalize(&mut self) {
+        self.refresh.update_interval_ms = self.refresh.update_interval_ms.clamp(100, 6000);
+        self.refresh.interval_step_ms = self.refresh.interval_step_ms.clamp(10, 1000);
+        self.refresh.history_length = self.refresh.history_length.clamp(10, 3600);
+        self.metrics.max_processes = self.metrics.max_processes.min(1000);
+        self.processes.detail_width = self.processes.detail_width.clamp(10, 70);
+        for width in self.processes.column_widths.values_mut() {
+            *width = (*width).clamp(4, 60);
+        }
+        if self.processes.columns.is_empty() {
+            self.processes.columns = ProcessesConfig::default().columns;
+        }
+    }
+}
+
+// Formato di preferences.json usato dalle versioni precedenti
+#[derive(Deserialize)]
+pub struct LegacyPreferences {
+    pub update_interval_ms: u64,
+    pub sort_column: SortColumn,
+    pub reverse_sort: bool,
+    #[serde(default)]
+    pub history_length: Option<usize>,
+}
+
+impl LegacyPreferences {
+    pub fn apply(self, preferences: &mut Preferences) {
+        preferences.refresh.update_interval_ms = self.update_interval_ms;
+        preferences.processes.sort_column = self.sort_column;
+        preferences.processes.reverse_sort = self.reverse_sort;
+        if let Some(history_length) = self.history_length {
+            preferences.refresh.history_length = history_length;
+        }
+    }
+}
+
+pub const DEFAULT_CONFIG: &str = r##"# rtop configuration file
+#
+# Values that you change from inside rtop (sort order, update interval, view
+# mode) are written back here on exit, comments are preserved.
+
+[general]
+# Page shown at startup: "processes", "stats" or "help"
+default_page = "processes"
+# Capture mouse events (click to select, double click to sort/expand, right
+# click for the process menu, drag to resize panels and columns)
+mouse = true
+# Restore the filters, search and expanded tree branches of the last session
+restore_session = false
+
+[refresh]
+# Time between two refreshes, in milliseconds (100 - 6000)
+update_interval_ms = 1000
+# Amount added/removed by the +/- keys, in milliseconds
+interval_step_ms = 100
+# Number of samples kept for the charts on the stats page (10 - 3600)
+history_length = 300
+# Start with automatic refresh enabled (toggle with z)
+auto_refresh = true
+
+[processes]
+# Sort column: "pid", "name", "cpu" or "memory"
+sort_column = "cpu"
+# Sort in descending order
+reverse_sort = true
+# Show processes as a tree (true) or as a flat list (false)
+tree_mode = true
+# Visible columns, in order: "pid", "name", "cpu", "memory", "user", "status"
+columns = ["pid", "name", "cpu", "memory"]
+# Width of the details panel, in percent of the screen (10 - 70). Drag its
+# border with the mouse to change it
+detail_width = 30
+# Widths of the fixed columns, also set by dragging the header borders
+# column_widths = { pid = 8, status = 14 }
+# How the search text is matched against name, PID, command line, executable,
+# user and cgroup: "substring", "fuzzy" (on the name) or "regex". Tab switches
+# it while searching
+search_mode = "substring"
+
+[confirm]
+# Ask for confirmation before killing: "always", "critical" or "never"
+kill = "critical"
+
+[colors]
+# Built-in themes: "dark", "light", "monochrome", "high-contrast", or the name of
+# a theme defined below. Setting the NO_COLOR environment variable always forces
+# monochrome output.
+theme = "dark"
+# Single colors can be overridden on top of the theme. Accepted values are color
+# names ("cyan", "lightblue", ...), indexed colors ("42") or hex ("#00ffaa").
+# Keys: text, background, accent, header, selection, selection_text, muted,
+#       good, warning, critical, info, highlight
+# accent = "cyan"
+
+# User defined themes start from a base theme and override some of its colors.
+# A theme with the name of a built-in one ([themes.dark]) replaces it:
+# [themes.solarized]
+# base = "dark"
+# accent = "#268bd2"
+# header = "#b58900"
+# good = "#859900"
+# critical = "#dc322f"
+
+# Saved views, switched from the view picker (v). Press s in the picker to save
+# the current filters, sort order, columns and mode under a new name, or add
+# them here. Filters missing from a view are cleared when it's applied.
+# heavy = { cpu = 10.0, memory_mb = 500, sort_column = "memory", tree_mode = false }
+# mine = { user = "1000", search = "cargo", columns = ["pid", "name", "cpu", "user"] }
+[views]
+
+[keybindings]
+# Starting keymap: "default", "htop" or "vim". The help page (?) always shows
+# the keys that are currently active.
+preset = "default"
+# Any action can be remapped to one key or a list of keys. Remapping an action
+# replaces the preset keys for it. Keys look like "q", "G", "ctrl+r", "alt+x",
+# "f5", "pagedown", "space", "enter", "esc", "delete", "home", "end".
+# "shift+g" is the same as "G"; shift only combines with letters and tab.
+# Actions: up, down, page_up, page_down, top, bottom, toggle_expand,
+#   expand_all, collapse_all, kill, suspend, resume, open_files, inspect,
+#   refresh, toggle_auto_refresh, sort_pid, sort_name, sort_cpu, sort_memory,
+#   cycle_sort, search, find, find_next, find_prev, filter, clear_filters,
+#   set_interval, faster, slower, toggle_tree, processes, stats, help,
+#   command_palette, views, quit, force_quit, back, replay_step_back,
+#   replay_step_forward, replay_seek_back, replay_seek_forward
+# down = ["down", "ctrl+n"]
+# up = ["up", "ctrl+p"]
+# kill = "f9"
+
+[metrics]
+# Serve system and per-process metrics in OpenMetrics format on
+# http://<listen>/metrics, e.g. "127.0.0.1:9184". Empty disables the exporter.
+listen = ""
+# Only the most active processes (by CPU, then memory) get their own series,
+# so that short lived processes don't flood the time series database (0 - 1000)
+max_processes = 50
+
+# Alert rules, evaluated on every refresh. When a rule triggers rtop highlights
+# the processes involved, shows a banner in the footer, rings the terminal bell
+# and runs the optional command. An alert clears once the value moves back past
+# the threshold by more than `hysteresis`, so that it doesn't flap.
+#
+# Metrics: "cpu", "memory_used", "memory_available", "swap" (percent of total),
+#   "pressure_cpu", "pressure_memory", "pressure_io" (percent, 10s average),
+#   "process_cpu" (percent) and "process_memory" (MB), checked for every
+#   process whose name contains `process`, or all processes if it's missing.
+# The command runs through `sh -c` with RTOP_ALERT, RTOP_METRIC, RTOP_VALUE,
+# RTOP_THRESHOLD, RTOP_PID and RTOP_PROCESS set. Processes that trigger the same
+# rule together ring the bell and run the command once, for the worst value,
+# with RTOP_COUNT set to how many triggered.
+#
+# [[alerts]]
+# name = "Firefox busy"
+# metric = "process_cpu"
+# process = "firefox"
+# above = 80
+# for_secs = 30
+#
+# [[alerts]]
+# name = "Low memory"
+# metric = "memory_available"
+# below = 5
+# hysteresis = 2
+# bell = true
+# command = "notify-send \"$RTOP_ALERT\" \"$RTOP_VALUE% available\""
+"##;
+
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use ratatui::layout::Rect;
+use sysinfo::Pid;
+
+use crate::{App, InputMode, helpers::utils::copy_to_clipboard};
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum MenuItem {
+    Kill,
+    Signal,
+    Suspend,
+    Resume,
+    Renice,
+    OpenFiles,
+    Inspect,
+    CopyPid,
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 8] = [
+        MenuItem::Kill,
+        MenuItem::Signal,
+        MenuItem::Suspend,
+        MenuItem::Resume,
+        MenuItem::Renice,
+        MenuItem::OpenFiles,
+        MenuItem::Inspect,
+        MenuItem::CopyPid,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuItem::Kill => "Kill",
+            MenuItem::Signal => "Send signal...",
+            MenuItem::Suspend => "Suspend",
+            MenuItem::Resume => "Resume",
+            MenuItem::Renice => "Renice...",
+            MenuItem::OpenFiles => "Open files",
+            MenuItem::Inspect => "Inspect",
+            MenuItem::CopyPid => "Copy PID",
+        }
+    }
+}
+
+pub struct ContextMenu {
+    pub pid: Pid,
+    pub selected: usize,
+    pub area: Rect,
+}
+
+// Segnali accettati per nome, con o senza il prefisso SIG
+const SIGNALS: [(&str, i32); 11] = [
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("USR2", libc::SIGUSR2),
+    ("TERM", libc::SIGTERM),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("WINCH", libc::SIGWINCH),
+];
+
+pub fn parse_signal(input: &str) -> Option<i32> {
+    let input = input.trim().to_uppercase();
+    if let Ok(number) = input.parse::<i32>() {
+        return (1..=64).contains(&number).then_some(number);
+    }
+    let name = input.strip_prefix("SIG").unwrap_or(&input);
+    SIGNALS.iter().find(|(signal, _)| *signal == name).map(|&(_, number)| number)
+}
+
+impl App {
+    // Il menu si apre sotto il cursore, spostato se uscirebbe dalla pagina
+    pub fn open_context_menu(&mut self, pid: Pid, x: u16, y: u16) {
+        let bounds = self.table_area.union(self.detail_area);
+        let width = MenuItem::ALL.iter().map(|item| item.label().len() as u16).max().unwrap_or(0) + 4;
+        let height = MenuItem::ALL.len() as u16 + 2;
+        let x = x.min(bounds.right().saturating_sub(width)).max(bounds.x);
+        let y = y.min(bounds.bottom().saturating_sub(height)).max(bounds.y);
+
+        self.context_menu = Some(ContextMenu {
+            pid,
+            selected: 0,
+            area: Rect::new(x, y, width, height).intersection(bounds),
+        });
+        self.input_mode = InputMode::ContextMenu;
+    }
+
+    pub fn close_context_menu(&mut self) {
+        self.context_menu = None;
+        self.input_mode = InputMode::None;
+        self.input_buffer.clear();
+    }
+
+    pub fn move_menu_selection(&mut self, delta: isize) {
+        if let Some(menu) = &mut self.context_menu {
+            menu.selected = menu.selected.saturating_add_signed(delta).min(MenuItem::ALL.len() - 1);
+        }
+    }
+
+    pub fn run_menu_item(&mut self, item: MenuItem) -> Result<()> {
+        let Some(pid) = self.context_menu.as_ref().map(|menu| menu.pid) else {
+            return Ok(());
+        };
+        self.input_mode = InputMode::None;
+
+        // Le azioni lavorano sulla riga selezionata, che nel frattempo
+        // potrebbe essersi spostata
+        if !self.select_pid(pid) {
+            self.close_context_menu();
+            self.errors.push((
+                "Process not found".to_string(),
+                format!("PID {} no longer exists", pid),
+            ));
+            self.input_mode = InputMode::Error;
+            return Ok(());
+        }
+
+        match item {
+            MenuItem::Kill => self.initiate_kill()?,
+            MenuItem::Suspend => self.suspend_process()?,
+            MenuItem::Resume => self.resume_process()?,
+            MenuItem::OpenFiles => self.process_open_files(),
+            MenuItem::Inspect => self.open_inspector(),
+            MenuItem::CopyPid => copy_to_clipboard(&pid.to_string()),
+            MenuItem::Signal => {
+                self.input_mode = InputMode::SendSignal;
+                self.input_buffer.clear();
+                return Ok(());
+            }
+            MenuItem::Renice => {
+                self.input_mode = InputMode::Renice;
+                self.input_buffer.clear();
+                return Ok(());
+            }
+        }
+        self.context_menu = None;
+        Ok(())
+    }
+
+    pub fn select_pid(&mut self, pid: Pid) -> bool {
+        let Some(slot) = self.processes.slot_of(pid) else {
+            return false;
+        };
+        let Some(index) = self.flatten_processes().iter().position(|&(_, s)| s == slot) else {
+            return false;
+        };
+        self.table_state.select(Some(index));
+        self.ensure_visible(index);
+        true
+    }
+
+    pub fn send_signal_to_menu_process(&mut self) {
+        let Some(pid) = self.context_menu.take().map(|menu| menu.pid) else {
+            return;
+        };
+        let input = std::mem::take(&mut self.input_buffer);
+        self.input_mode = InputMode::None;
+        self.send_signal(pid, &input);
+    }
+
+    pub fn renice_menu_process(&mut self) {
+        let Some(pid) = self.context_menu.take().map(|menu| menu.pid) else {
+            return;
+        };
+        let input = std::mem::take(&mut self.input_buffer);
+        self.input_mode = InputMode::None;
+        self.renice(pid, &input);
+    }
+
+    // Usate anche dalla command palette; gli errori finiscono nell'overlay
+    pub fn send_signal(&mut self, pid: Pid, input: &str) {
+        let Some(signal) = parse_signal(input) else {
+            self.errors.push(("Send signal".to_string(), format!("Unknown signal '{}'", input.trim())));
+            self.input_mode = InputMode::Error;
+            return;
+        };
+        match self.control.signal(pid.as_u32(), signal) {
+            Ok(()) => self.force_refresh(),
+            Err(err) => {
+                self.errors.push(("Failed to send signal".to_string(), format!("PID {}: {}", pid, err)));
+                self.input_mode = InputMode::Error;
+            }
+        }
+    }
+
+    pub fn renice(&mut self, pid: Pid, input: &str) {
+        let Ok(nice) = input.trim().parse::<i32>() else {
+            self.errors.push(("Renice".to_string(), format!("'{}' is not a number", input.trim())));
+            self.input_mode = InputMode::Error;
+            return;
+        };
+        if let Err(err) = self.control.renice(pid.as_u32(), nice.clamp(-20, 19)) {
+            self.errors.push(("Failed to renice process".to_string(), format!("PID {}: {}", pid, err)));
+            self.input_mode = InputMode::Error;
+        }
+    }
+}
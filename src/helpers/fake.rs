@@ -0,0 +1,207 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use ratatui::{Frame, Terminal, backend::TestBackend};
+
+use rtop::{
+    cpu_freq::CpuFreqInfo,
+    pressure::SystemPressure,
+    snapshot::{NetworkSample, ProcessSample, Snapshot},
+    source::{MetricsSource, ProcessControl},
+};
+
+use crate::{App, helpers::cli::Cli};
+
+// Restituisce i campioni nell'ordine dato, poi ripete l'ultimo
+pub struct ScriptedSource {
+    snapshots: VecDeque<Snapshot>,
+    last: Snapshot,
+    killed: Arc<Mutex<HashSet<u32>>>,
+}
+
+// I processi uccisi spariscono dai campioni successivi della sorgente
+pub struct FakeControl {
+    killed: Arc<Mutex<HashSet<u32>>>,
+    pub niced: Arc<Mutex<HashMap<u32, i32>>>,
+    pub signals: Arc<Mutex<Vec<(u32, i32)>>>,
+}
+
+pub fn scripted(snapshots: Vec<Snapshot>) -> (ScriptedSource, FakeControl) {
+    let killed = Arc::<Mutex<HashSet<u32>>>::default();
+    let source = ScriptedSource {
+        snapshots: snapshots.into(),
+        last: Snapshot::default(),
+        killed: Arc::clone(&killed),
+    };
+    (source, FakeControl {
+        killed,
+        niced: Arc::default(),
+        signals: Arc::default(),
+    })
+}
+
+impl MetricsSource for ScriptedSource {
+    fn sample(&mut self) -> Snapshot {
+        if let Some(next) = self.snapshots.pop_front() {
+            self.last = next;
+        }
+        let killed = self.killed.lock().unwrap();
+        self.last.processes.retain(|p| !killed.contains(&p.pid));
+        self.last.clone()
+    }
+
+    // Solo /user.slice ha dati di pressure
+    fn cgroup_pressure(&mut self, cgroup: &str) -> Option<SystemPressure> {
+        let mut pressure = SystemPressure::default();
+        pressure.memory.some.avg10 = 12.5;
+        (cgroup == "/user.slice").then_some(pressure)
+    }
+}
+
+impl ProcessControl for FakeControl {
+    fn signal(&self, pid: u32, signal: i32) -> io::Result<()> {
+        // Come per un processo vero, dopo SIGKILL ogni segnale fallisce
+        let mut killed = self.killed.lock().unwrap();
+        if killed.contains(&pid) {
+            return Err(io::Error::from_raw_os_error(libc::ESRCH));
+        }
+        if signal == libc::SIGKILL {
+            killed.insert(pid);
+        }
+        self.signals.lock().unwrap().push((pid, signal));
+        Ok(())
+    }
+
+    fn renice(&self, pid: u32, nice: i32) -> io::Result<()> {
+        self.niced.lock().unwrap().insert(pid, nice);
+        Ok(())
+    }
+}
+
+pub fn process(pid: u32, parent: Option<u32>, name: &str, cpu_usage: f32, memory_mb: u64) -> ProcessSample {
+    ProcessSample {
+        pid,
+        parent,
+        name: name.to_string(),
+        cpu_usage,
+        memory: memory_mb * 1024 * 1024,
+        virtual_memory: memory_mb * 2 * 1024 * 1024,
+        user_id: Some(1000),
+        status: "Sleeping".to_string(),
+        start_time: 1_700_000_000,
+        cmd: vec![format!("/usr/bin/{}", name)],
+        exe: format!("/usr/bin/{}", name),
+        user: "luca".to_string(),
+        cgroup: "/user.slice".to_string(),
+        read_bytes: 0,
+        written_bytes: 0,
+    }
+}
+
+// Macchina finta con 4 core e un piccolo albero di processi
+pub fn machine() -> Snapshot {
+    Snapshot {
+        timestamp: 1_700_000_060_000,
+        cpu_usage: vec![10.0, 20.0, 30.0, 40.0],
+        cpu_freq: vec![
+            CpuFreqInfo {
+                current_mhz: 2400,
+                min_mhz: Some(800),
+                max_mhz: Some(3600),
+                ..Default::default()
+            };
+            4
+        ],
+        memory_used: 4 * 1024 * 1024 * 1024,
+        memory_total: 16 * 1024 * 1024 * 1024,
+        swap_used: 0,
+        swap_total: 2 * 1024 * 1024 * 1024,
+        processes: vec![
+            process(1, None, "init", 0.1, 10),
+            process(100, Some(1), "sshd", 0.0, 8),
+            process(200, Some(100), "bash", 1.5, 5),
+            process(300, Some(200), "cargo", 55.0, 900),
+            process(400, Some(1), "firefox", 12.5, 2048),
+        ],
+        networks: vec![NetworkSample {
+            name: "eth0".to_string(),
+            received: 2048,
+            transmitted: 512,
+            total_received: 1024 * 1024,
+            total_transmitted: 256 * 1024,
+        }],
+        ..Default::default()
+    }
+}
+
+// Campioni a un secondo di distanza con il carico della CPU che sale
+pub fn timeline(count: usize) -> Vec<Snapshot> {
+    (0..count)
+        .map(|i| {
+            let mut snapshot = machine();
+            snapshot.timestamp += i as i64 * 1000;
+            for usage in &mut snapshot.cpu_usage {
+                *usage = (*usage + i as f32 * 10.0).min(100.0);
+            }
+            snapshot
+        })
+        .collect()
+}
+
+// App con preferenze predefinite, senza leggere né scrivere il config dell'utente
+pub fn app(snapshots: Vec<Snapshot>) -> App {
+    app_with_log(snapshots).0
+}
+
+// Segnali mandati e priorità impostate dall'app, in quest'ordine
+pub type ControlLog = (Arc<Mutex<Vec<(u32, i32)>>>, Arc<Mutex<HashMap<u32, i32>>>);
+
+pub fn app_with_log(snapshots: Vec<Snapshot>) -> (App, ControlLog) {
+    let cli = Cli {
+        config: Some("/dev/null".into()),
+        ..Default::default()
+    };
+    let (source, control) = scripted(snapshots);
+    let log = (Arc::clone(&control.signals), Arc::clone(&control.niced));
+    (App::with_source(&cli, Box::new(source), Box::new(control)), log)
+}
+
+// Disegna su un TestBackend e restituisce il testo riga per riga
+pub fn render(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(draw).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    (0..height)
+        .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+        .collect()
+}
+
+// Confronta con src/gui/snapshots/<nome>.txt; con RTOP_UPDATE_SNAPSHOTS=1
+// il file viene riscritto
+pub fn assert_snapshot(name: &str, lines: &[String]) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/gui/snapshots")
+        .join(format!("{}.txt", name));
+    let actual = lines.join("\n") + "\n";
+
+    if std::env::var_os("RTOP_UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Missing snapshot {}, run with RTOP_UPDATE_SNAPSHOTS=1", path.display()));
+    assert!(
+        expected == actual,
+        "Snapshot {} changed:\n--- expected\n{}--- actual\n{}",
+        name,
+        expected,
+        actual
+    );
+}
@@ -0,0 +1,115 @@
+use rtop::filter::{ProcessFilter, SearchKind};
+
+use crate::App;
+
+// Ricerca che sposta la selezione invece di filtrare, come / e n in less
+#[derive(Default)]
+pub struct FindState {
+    // Solo search e search_kind sono usati
+    pub query: ProcessFilter,
+    // true mentre si scrive il testo
+    pub typing: bool,
+    // (testo cercato, generazione della lista, righe che corrispondono): il
+    // footer chiede il contatore a ogni disegno
+    matches: Option<(ProcessFilter, u64, Vec<usize>)>,
+}
+
+impl App {
+    pub fn start_find(&mut self) {
+        self.find.query.search.clear();
+        self.find.query.search_kind = self.filter.search_kind;
+        self.find.typing = true;
+    }
+
+    pub fn stop_find(&mut self) {
+        self.find.query.search.clear();
+        self.find.typing = false;
+    }
+
+    pub fn set_find_query(&mut self, query: String) {
+        self.find.query.search = query;
+        if self.find.query.search.is_empty() {
+            return;
+        }
+
+        // Come per la ricerca, i match nei rami chiusi vengono resi visibili
+        self.processes.expand_matches(&self.find.query, &mut self.expanded_pids);
+        self.cached_flat_processes = None;
+
+        // Incrementale: resta sulla riga corrente se corrisponde ancora
+        let selected = self.table_state.selected().unwrap_or(0);
+        if !self.find_matches().contains(&selected) {
+            self.find_next(true);
+        }
+    }
+
+    pub fn set_search_kind(&mut self, kind: SearchKind) {
+        self.filter.search_kind = kind;
+        self.find.query.search_kind = kind;
+        self.preferences.processes.search_mode = kind;
+        self.cached_flat_processes = None;
+    }
+
+    // Righe visibili che corrispondono al testo cercato, ricalcolate solo
+    // quando cambiano il testo o la lista
+    pub fn find_matches(&mut self) -> &[usize] {
+        if self.find.query.search.is_empty() {
+            return &[];
+        }
+        self.flatten_processes();
+        let generation = self.flatten_generation;
+        let stale = self
+            .find
+            .matches
+            .as_ref()
+            .is_none_or(|(query, cached, _)| *cached != generation || *query != self.find.query);
+        if stale {
+            let rows = self.cached_flat_processes.as_deref().unwrap_or_default();
+            let matches = rows
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, slot))| {
+                    self.processes
+                        .get(*slot)
+                        .is_some_and(|node| self.find.query.search_score(&node.info).is_some())
+                })
+                .map(|(index, _)| index)
+                .collect();
+            self.find.matches = Some((self.find.query.clone(), generation, matches));
+        }
+        self.find.matches.as_ref().map_or(&[], |(_, _, matches)| matches)
+    }
+
+    // Salta al match successivo (o precedente), ricominciando dall'altro capo
+    pub fn find_next(&mut self, forward: bool) {
+        let selected = self.table_state.selected();
+        let matches = self.find_matches();
+        let next = if forward {
+            matches
+                .iter()
+                .find(|&&index| selected.is_none_or(|selected| index > selected))
+                .or(matches.first())
+                .copied()
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|&&index| selected.is_none_or(|selected| index < selected))
+                .or(matches.last())
+                .copied()
+        };
+
+        if let Some(index) = next {
+            self.table_state.select(Some(index));
+            self.ensure_visible(index);
+        }
+    }
+
+    // (posizione del match selezionato, numero di match) per il footer
+    pub fn find_counter(&mut self) -> (Option<usize>, usize) {
+        let selected = self.table_state.selected();
+        let matches = self.find_matches();
+        let current = selected.and_then(|selected| matches.iter().position(|&index| index == selected));
+        (current.map(|position| position + 1), matches.len())
+    }
+}
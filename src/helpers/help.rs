@@ -0,0 +1,285 @@
+use crate::{App, InputMode, Page, helpers::keymap::Action};
+
+#[derive(Default)]
+pub struct HelpState {
+    pub scroll: usize,
+    // Righe visibili nell'ultimo disegno, per PgUp/PgDn
+    pub height: usize,
+    pub query: String,
+    pub searching: bool,
+    // Da dove è stato aperto l'aiuto, Esc ci riporta lì
+    pub return_page: Option<Page>,
+    pub overlay: Option<InputMode>,
+}
+
+pub struct HelpSection {
+    pub title: &'static str,
+    pub entries: Vec<(String, String)>,
+}
+
+impl HelpSection {
+    fn fixed(title: &'static str, entries: &[(&str, &str)]) -> Self {
+        Self {
+            title,
+            entries: entries.iter().map(|(keys, description)| (keys.to_string(), description.to_string())).collect(),
+        }
+    }
+
+    // Le azioni del gruppo con i tasti della keymap attiva
+    fn actions(app: &App, title: &'static str) -> Self {
+        let entries = Action::ALL
+            .iter()
+            .filter(|action| action.group() == title)
+            .filter_map(|&action| {
+                let keys = app.keymap.keys_for(action);
+                (!keys.is_empty()).then(|| (keys.join(", "), action.description().to_string()))
+            })
+            .collect();
+        Self { title, entries }
+    }
+}
+
+fn overlay_section(mode: InputMode) -> Option<HelpSection> {
+    let section = match mode {
+        InputMode::None => return None,
+        InputMode::SelectFilter => HelpSection::fixed(
+            "Filter menu",
+            &[
+                ("0", "Reset all filters"),
+                ("1", "Filter by user ID"),
+                ("2", "Filter by status"),
+                ("3", "Minimum CPU %"),
+                ("4", "Minimum memory (MB)"),
+                ("Enter", "Open the chosen filter"),
+                ("Esc", "Close the menu"),
+            ],
+        ),
+        InputMode::UpdateInterval => HelpSection::fixed(
+            "Update interval",
+            &[
+                ("0-9", "Interval in milliseconds (100 - 6000)"),
+                ("Backspace", "Delete a digit"),
+                ("Enter", "Apply and save"),
+                ("Esc", "Cancel"),
+            ],
+        ),
+        InputMode::ConfirmKill => HelpSection::fixed(
+            "Kill confirmation",
+            &[("y, Y", "Kill the process"), ("n, N, Esc", "Cancel")],
+        ),
+        InputMode::UserFilter
+        | InputMode::StatusFilter
+        | InputMode::CpuThreshold
+        | InputMode::MemoryThreshold => HelpSection::fixed(
+            "Filter prompts",
+            &[
+                ("Text", "User ID, status, CPU % or memory in MB"),
+                ("Backspace", "Delete a character"),
+                ("Enter", "Apply, an empty value clears the filter"),
+                ("Esc", "Cancel"),
+            ],
+        ),
+        InputMode::SelectView => HelpSection::fixed(
+            "Saved views",
+            &[
+                ("↑, ↓", "Choose a view"),
+                ("Enter", "Apply the view"),
+                ("s", "Save the current view as..."),
+                ("d", "Delete the view"),
+                ("Esc", "Close"),
+            ],
+        ),
+        InputMode::SaveView => HelpSection::fixed(
+            "Save view",
+            &[
+                ("Text", "View name (letters, digits, - and _)"),
+                ("Backspace", "Delete a character"),
+                ("Enter", "Save, replacing a view with the same name"),
+                ("Esc", "Back to the views"),
+            ],
+        ),
+        InputMode::ContextMenu => HelpSection::fixed(
+            "Process menu",
+            &[
+                ("Right click", "Open the menu on a process row"),
+                ("↑, ↓", "Choose an entry"),
+                ("Enter, click", "Run the entry"),
+                ("Esc", "Close the menu"),
+            ],
+        ),
+        InputMode::SendSignal | InputMode::Renice => HelpSection::fixed(
+            "Signal and renice prompts",
+            &[
+                ("Text", "Signal name or number (TERM, 9), or a nice value (-20 - 19)"),
+                ("Backspace", "Delete a character"),
+                ("Enter", "Apply"),
+                ("Esc", "Cancel"),
+            ],
+        ),
+        InputMode::CommandPalette => HelpSection::fixed(
+            "Command palette",
+            &[
+                ("Text", "Fuzzy search the commands, then arguments (signal HUP, expand 2)"),
+                ("↑, ↓", "Choose a command, recent ones come first"),
+                ("Tab", "Complete the chosen command"),
+                ("Enter", "Run the command"),
+                ("Esc", "Close"),
+            ],
+        ),
+        InputMode::Error => HelpSection::fixed("Errors", &[("Enter, Esc", "Dismiss")]),
+    };
+    Some(section)
+}
+
+impl App {
+    // Con `?` dentro un overlay l'aiuto mostra solo i tasti di quell'overlay
+    pub fn open_help(&mut self) {
+        let overlay = (self.input_mode != InputMode::None).then_some(self.input_mode);
+        let return_page = if self.page == Page::Help { self.help.return_page } else { Some(self.page) };
+        self.help = HelpState {
+            return_page,
+            overlay,
+            ..Default::default()
+        };
+        self.input_mode = InputMode::None;
+        self.page = Page::Help;
+    }
+
+    pub fn close_help(&mut self) {
+        self.page = self.help.return_page.take().unwrap_or(Page::Processes);
+        if let Some(mode) = self.help.overlay.take() {
+            self.input_mode = mode;
+        }
+        self.help.query.clear();
+        self.help.searching = false;
+    }
+
+    // Il valore viene limitato al disegno, quando si conosce l'altezza
+    pub fn scroll_help(&mut self, delta: isize) {
+        self.help.scroll = self.help.scroll.saturating_add_signed(delta);
+    }
+
+    pub fn help_page_size(&self) -> isize {
+        self.help.height.max(1) as isize
+    }
+
+    pub fn set_help_query(&mut self, query: String) {
+        self.help.query = query;
+        self.help.scroll = 0;
+    }
+
+    fn all_help_sections(&self) -> Vec<HelpSection> {
+        let general = HelpSection::actions(self, "General");
+        let up_down = [Action::SelectUp, Action::SelectDown, Action::PageUp, Action::PageDown]
+            .map(|action| self.keymap.keys_for(action).join(", "))
+            .join(", ");
+        let processes = vec![
+            HelpSection::actions(self, "Navigation"),
+            HelpSection::actions(self, "Process actions"),
+            HelpSection::actions(self, "Sorting & tree"),
+            HelpSection::actions(self, "Search & Filter"),
+            HelpSection::fixed(
+                "Search",
+                &[
+                    ("Text", "Search name, PID, command line, executable, user and cgroup"),
+                    ("Tab", "Switch between substring, fuzzy and regex matching"),
+                    ("Backspace", "Delete a character"),
+                    ("↑, ↓", "Move selection"),
+                    ("Enter", "Keep the results"),
+                    ("Esc", "Clear the search"),
+                ],
+            ),
+            HelpSection::fixed(
+                "Find",
+                &[
+                    ("Text", "Jump to the first matching process, the list stays whole"),
+                    ("Tab", "Switch between substring, fuzzy and regex matching"),
+                    ("↑, ↓", "Previous or next match"),
+                    ("Enter", "Keep the text to jump between matches"),
+                    ("Esc", "Stop finding"),
+                ],
+            ),
+            HelpSection {
+                title: "Inspector",
+                entries: vec![
+                    ("Tab, →".to_string(), "Next tab".to_string()),
+                    ("Shift+Tab, ←".to_string(), "Previous tab".to_string()),
+                    (up_down.clone(), "Scroll the tab".to_string()),
+                    (self.keymap.keys_for(Action::Back).join(", "), "Back to the process list".to_string()),
+                ],
+            },
+        ];
+        let mut stats = vec![HelpSection::actions(self, "Refresh")];
+        if self.replay.is_some() {
+            stats.push(HelpSection::actions(self, "Replay"));
+        }
+
+        let help = HelpSection {
+            title: "Help",
+            entries: vec![
+                (up_down, "Scroll the help".to_string()),
+                (self.keymap.keys_for(Action::Search).join(", "), "Search the keybindings".to_string()),
+                (self.keymap.keys_for(Action::Back).join(", "), "Clear the search, otherwise go back".to_string()),
+                ("? in a menu or dialog".to_string(), "Show the keys of that overlay".to_string()),
+            ],
+        };
+
+        // Prima le sezioni della pagina da cui si arriva
+        let mut sections = vec![general];
+        if self.help.return_page == Some(Page::SystemStats) {
+            sections.extend(stats);
+            sections.extend(processes);
+        } else {
+            sections.extend(processes);
+            sections.extend(stats);
+        }
+        sections.push(help);
+        sections.push(HelpSection::fixed(
+            "Mouse",
+            &[
+                ("Click", "Select a process or run a footer entry"),
+                ("Double click", "Expand a process, sort on a header"),
+                ("Right click", "Process menu (kill, signal, renice...)"),
+                ("Drag", "Resize the details panel and the columns"),
+                ("Wheel", "Scroll the list, details, help, inspector and menus"),
+            ],
+        ));
+        sections.extend(
+            [
+                InputMode::SelectFilter,
+                InputMode::UserFilter,
+                InputMode::UpdateInterval,
+                InputMode::ConfirmKill,
+                InputMode::CommandPalette,
+                InputMode::ContextMenu,
+                InputMode::SendSignal,
+                InputMode::SelectView,
+                InputMode::SaveView,
+            ]
+            .into_iter()
+            .filter_map(overlay_section),
+        );
+        sections
+    }
+
+    // Sezioni da mostrare, ristrette all'overlay di partenza e alla ricerca
+    pub fn help_sections(&self) -> Vec<HelpSection> {
+        let sections = match self.help.overlay {
+            Some(mode) => overlay_section(mode).into_iter().collect(),
+            None => self.all_help_sections(),
+        };
+
+        let query = self.help.query.to_lowercase();
+        sections
+            .into_iter()
+            .filter_map(|mut section| {
+                if !section.title.to_lowercase().contains(&query) {
+                    section.entries.retain(|(keys, description)| {
+                        keys.to_lowercase().contains(&query) || description.to_lowercase().contains(&query)
+                    });
+                }
+                (!section.entries.is_empty()).then_some(section)
+            })
+            .collect()
+    }
+}
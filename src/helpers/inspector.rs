@@ -0,0 +1,670 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use chrono::{TimeZone, Utc};
+use ratatui::layout::Rect;
+use rtop::snapshot::ProcessSample;
+use sysinfo::Pid;
+
+use crate::{App, Page, gui::charts::format_span};
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum InspectorTab {
+    Overview,
+    Threads,
+    OpenFiles,
+    Sockets,
+    MemoryMaps,
+    Environment,
+    Limits,
+    Namespaces,
+}
+
+impl InspectorTab {
+    pub const ALL: [InspectorTab; 8] = [
+        InspectorTab::Overview,
+        InspectorTab::Threads,
+        InspectorTab::OpenFiles,
+        InspectorTab::Sockets,
+        InspectorTab::MemoryMaps,
+        InspectorTab::Environment,
+        InspectorTab::Limits,
+        InspectorTab::Namespaces,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            InspectorTab::Overview => "Overview",
+            InspectorTab::Threads => "Threads",
+            InspectorTab::OpenFiles => "Open Files",
+            InspectorTab::Sockets => "Sockets",
+            InspectorTab::MemoryMaps => "Memory Maps",
+            InspectorTab::Environment => "Environment",
+            InspectorTab::Limits => "Limits",
+            InspectorTab::Namespaces => "cgroup/Namespaces",
+        }
+    }
+
+    // Per i terminali stretti, dove i titoli completi non entrano
+    pub fn short_title(&self) -> &'static str {
+        match self {
+            InspectorTab::OpenFiles => "Files",
+            InspectorTab::MemoryMaps => "Maps",
+            InspectorTab::Environment => "Env",
+            InspectorTab::Namespaces => "cgroup/NS",
+            _ => self.title(),
+        }
+    }
+}
+
+pub enum InspectorLine {
+    Title(String),
+    // Etichetta e valore, i valori lunghi vanno a capo allineati
+    Field(String, String),
+    Text(String),
+    // Avvisi ed errori di lettura
+    Note(String),
+}
+
+#[derive(Default)]
+pub struct TabContent {
+    // Intestazione delle colonne, resta ferma mentre si scorre
+    pub header: Option<String>,
+    pub lines: Vec<InspectorLine>,
+}
+
+#[derive(Default)]
+pub struct InspectorState {
+    pub pid: Option<Pid>,
+    pub name: String,
+    // Distingue il processo da un altro che riusa lo stesso PID
+    pub start_time: u64,
+    pub tab: usize,
+    pub scroll: usize,
+    // Righe visibili nell'ultimo disegno, per PgUp/PgDn
+    pub height: usize,
+    pub content: TabContent,
+    // Il processo non c'è più, restano gli ultimi dati letti
+    pub exited: bool,
+    pub return_page: Option<Page>,
+    // Posizione dei titoli delle schede, per il click
+    pub tab_targets: Vec<(Rect, usize)>,
+    // Lettura di /proc in corso in un thread: (PID, scheda, risultato)
+    pub loading: Option<(u32, usize, Receiver<io::Result<TabContent>>)>,
+}
+
+impl App {
+    pub fn open_inspector(&mut self) {
+        let Some(node) = self
+            .table_state
+            .selected()
+            .and_then(|selected| self.get_process_at_flat_index(selected))
+        else {
+            return;
+        };
+        let pid = node.info.pid;
+        let name = node.info.name.clone();
+        let start_time = self.snapshot.process(pid.as_u32()).map_or(0, |process| process.start_time);
+
+        // La scheda resta quella usata l'ultima volta
+        let return_page = if self.page == Page::Inspector { self.inspector.return_page } else { Some(self.page) };
+        self.inspector = InspectorState {
+            pid: Some(pid),
+            name,
+            start_time,
+            tab: self.inspector.tab,
+            return_page,
+            ..Default::default()
+        };
+        self.page = Page::Inspector;
+        self.reload_inspector();
+    }
+
+    pub fn close_inspector(&mut self) {
+        self.page = self.inspector.return_page.take().unwrap_or(Page::Processes);
+        self.inspector.pid = None;
+        self.inspector.content = TabContent::default();
+    }
+
+    pub fn switch_inspector_tab(&mut self, delta: isize) {
+        let count = InspectorTab::ALL.len() as isize;
+        self.select_inspector_tab((self.inspector.tab as isize + delta).rem_euclid(count) as usize);
+    }
+
+    pub fn select_inspector_tab(&mut self, tab: usize) {
+        self.inspector.tab = tab.min(InspectorTab::ALL.len() - 1);
+        self.inspector.scroll = 0;
+        self.reload_inspector();
+    }
+
+    // Il valore viene limitato al disegno, quando si conosce l'altezza
+    pub fn scroll_inspector(&mut self, delta: isize) {
+        self.inspector.scroll = self.inspector.scroll.saturating_add_signed(delta);
+    }
+
+    pub fn inspector_page_size(&self) -> isize {
+        self.inspector.height.max(1) as isize
+    }
+
+    // Rilegge subito la scheda aperta, quando l'utente la cambia
+    pub fn reload_inspector(&mut self) {
+        self.load_inspector(false);
+    }
+
+    // Chiamata a ogni campione mentre l'ispettore è visibile: con il collector
+    // /proc viene letto in un thread e il risultato arriva con poll_inspector
+    pub fn refresh_inspector(&mut self) {
+        self.load_inspector(self.collector.is_some());
+    }
+
+    fn load_inspector(&mut self, background: bool) {
+        let Some(pid) = self.inspector.pid else {
+            return;
+        };
+        let Some(process) = self
+            .snapshot
+            .process(pid.as_u32())
+            .filter(|process| process.start_time == self.inspector.start_time)
+        else {
+            self.inspector.exited = true;
+            return;
+        };
+        self.inspector.exited = false;
+
+        // /proc è quello della macchina locale, e nelle registrazioni non c'è
+        let pid = pid.as_u32();
+        let tab = InspectorTab::ALL[self.inspector.tab];
+        let result = match tab {
+            InspectorTab::Overview => Ok(self.inspector_overview(process)),
+            _ if self.remote.is_some() => Ok(note("Not available when monitoring a remote host")),
+            _ if self.replay.is_some() => Ok(note("Not recorded in replays, only the overview is available")),
+            _ if background => {
+                // Una lettura alla volta: finché la precedente non torna si salta il giro
+                if self.inspector.loading.is_none() {
+                    let (sender, receiver) = mpsc::channel();
+                    thread::spawn(move || sender.send(read_tab(tab, pid)).ok());
+                    self.inspector.loading = Some((pid, self.inspector.tab, receiver));
+                }
+                return;
+            }
+            _ => read_tab(tab, pid),
+        };
+        self.inspector.content = result.unwrap_or_else(|err| note(&format!("Cannot read /proc/{}: {}", pid, err)));
+    }
+
+    pub fn poll_inspector(&mut self) {
+        let Some((pid, tab, receiver)) = &self.inspector.loading else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.inspector.loading = None;
+                return;
+            }
+        };
+        let (pid, tab) = (*pid, *tab);
+        self.inspector.loading = None;
+
+        // Nel frattempo l'utente può aver cambiato processo o scheda
+        if self.inspector.pid == Some(Pid::from_u32(pid)) && self.inspector.tab == tab {
+            self.inspector.content = result.unwrap_or_else(|err| note(&format!("Cannot read /proc/{}: {}", pid, err)));
+        }
+    }
+
+    fn inspector_overview(&self, process: &ProcessSample) -> TabContent {
+        let field = |label: &str, value: String| InspectorLine::Field(label.to_string(), value);
+        let or_na = |value: &str| if value.is_empty() { "N/A".to_string() } else { value.to_string() };
+        let megabytes = |bytes: u64| format!("{:.2} MB", bytes as f64 / 1024.0 / 1024.0);
+
+        let parent = match process.parent {
+            Some(parent) => match self.snapshot.process(parent) {
+                Some(parent_process) => format!("{} ({})", parent, parent_process.name),
+                None => parent.to_string(),
+            },
+            None => "None".to_string(),
+        };
+        let user = match process.user_id {
+            Some(uid) if !process.user.is_empty() => format!("{} ({})", process.user, uid),
+            Some(uid) => uid.to_string(),
+            None => or_na(&process.user),
+        };
+        let children = self
+            .snapshot
+            .processes
+            .iter()
+            .filter(|child| child.parent == Some(process.pid))
+            .count();
+        let started = Utc
+            .timestamp_opt(process.start_time as i64, 0)
+            .single()
+            .map_or("-".to_string(), |time| time.to_string());
+        let run_time = (self.snapshot.timestamp / 1000).saturating_sub(process.start_time as i64).max(0);
+
+        TabContent {
+            header: None,
+            lines: vec![
+                field("PID", process.pid.to_string()),
+                field("Name", process.name.clone()),
+                field("Parent", parent),
+                field("User", user),
+                field("Status", process.status.clone()),
+                field("Children", children.to_string()),
+                InspectorLine::Text(String::new()),
+                field("CPU", format!("{:.2}%", process.cpu_usage)),
+                field("Memory", megabytes(process.memory)),
+                field("Virtual memory", megabytes(process.virtual_memory)),
+                field("Disk read", megabytes(process.read_bytes)),
+                field("Disk written", megabytes(process.written_bytes)),
+                InspectorLine::Text(String::new()),
+                field("Started", started),
+                field("Run time", format_span(run_time as f64)),
+                InspectorLine::Text(String::new()),
+                field("Executable", or_na(&process.exe)),
+                field("cgroup", or_na(&process.cgroup)),
+                field("Command", or_na(&process.cmd.join(" "))),
+            ],
+        }
+    }
+}
+
+// Le schede lette da /proc; la panoramica si costruisce dal campione
+fn read_tab(tab: InspectorTab, pid: u32) -> io::Result<TabContent> {
+    match tab {
+        InspectorTab::Overview => unreachable!("the overview comes from the snapshot"),
+        InspectorTab::Threads => threads(pid),
+        InspectorTab::OpenFiles => open_files(pid),
+        InspectorTab::Sockets => sockets(pid),
+        InspectorTab::MemoryMaps => memory_maps(pid),
+        InspectorTab::Environment => environment(pid),
+        InspectorTab::Limits => limits(pid),
+        InspectorTab::Namespaces => Ok(namespaces(pid)),
+    }
+}
+
+fn note(text: &str) -> TabContent {
+    TabContent {
+        header: None,
+        lines: vec![InspectorLine::Note(text.to_string())],
+    }
+}
+
+struct ThreadStat {
+    name: String,
+    state: char,
+    cpu_ticks: u64,
+    nice: i64,
+    processor: i64,
+}
+
+// Il nome è tra parentesi e può contenerne altre, i campi seguono l'ultima
+fn parse_stat(stat: &str) -> Option<ThreadStat> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    // fields[0] è il terzo campo di proc(5), lo stato
+    let fields: Vec<&str> = stat.get(close + 1..)?.split_whitespace().collect();
+    let number = |index: usize| fields.get(index).and_then(|field| field.parse::<i64>().ok());
+    Some(ThreadStat {
+        name: stat.get(open + 1..close)?.to_string(),
+        state: fields.first()?.chars().next()?,
+        cpu_ticks: (number(11)? + number(12)?) as u64,
+        nice: number(16)?,
+        processor: number(36)?,
+    })
+}
+
+fn threads(pid: u32) -> io::Result<TabContent> {
+    // Tick del kernel al secondo per utime e stime
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+
+    let mut threads = Vec::new();
+    for entry in fs::read_dir(format!("/proc/{}/task", pid))? {
+        let Ok(tid) = entry?.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        // Il thread può terminare tra la lettura della cartella e quella del file
+        let Ok(stat) = fs::read_to_string(format!("/proc/{}/task/{}/stat", pid, tid)) else {
+            continue;
+        };
+        if let Some(stat) = parse_stat(&stat) {
+            threads.push((tid, stat));
+        }
+    }
+    threads.sort_by_key(|(tid, _)| *tid);
+
+    let lines = threads
+        .into_iter()
+        .map(|(tid, stat)| {
+            let seconds = stat.cpu_ticks as f64 / ticks;
+            let cpu_time = if seconds < 60.0 { format!("{:.2}s", seconds) } else { format_span(seconds) };
+            InspectorLine::Text(format!(
+                "{:>8} {:<5} {:>9} {:>4} {:>4}  {}",
+                tid, stat.state, cpu_time, stat.nice, stat.processor, stat.name
+            ))
+        })
+        .collect();
+    Ok(TabContent {
+        header: Some(format!("{:>8} {:<5} {:>9} {:>4} {:>4}  {}", "TID", "State", "CPU time", "Nice", "CPU", "Name")),
+        lines,
+    })
+}
+
+fn fd_targets(pid: u32) -> io::Result<Vec<(u32, String)>> {
+    let mut fds = Vec::new();
+    for entry in fs::read_dir(format!("/proc/{}/fd", pid))? {
+        let entry = entry?;
+        let Ok(fd) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        // Il descrittore può essere chiuso nel frattempo
+        if let Ok(target) = fs::read_link(entry.path()) {
+            fds.push((fd, target.to_string_lossy().into_owned()));
+        }
+    }
+    fds.sort_by_key(|(fd, _)| *fd);
+    Ok(fds)
+}
+
+fn open_files(pid: u32) -> io::Result<TabContent> {
+    let lines = fd_targets(pid)?
+        .into_iter()
+        .map(|(fd, target)| InspectorLine::Text(format!("{:>6}  {}", fd, target)))
+        .collect();
+    Ok(TabContent {
+        header: Some(format!("{:>6}  {}", "FD", "Target")),
+        lines,
+    })
+}
+
+struct Socket {
+    protocol: &'static str,
+    local: String,
+    remote: String,
+    state: &'static str,
+}
+
+// "0100007F:0035" è 127.0.0.1:53: il kernel stampa le parole a 32 bit
+// nell'ordine dei byte della macchina
+fn parse_inet_address(text: &str) -> Option<SocketAddr> {
+    let (address, port) = text.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let word = |index: usize| {
+        address
+            .get(index * 8..index * 8 + 8)
+            .and_then(|word| u32::from_str_radix(word, 16).ok())
+            .map(u32::to_ne_bytes)
+    };
+    let ip = match address.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(word(0)?)),
+        32 => {
+            let mut bytes = [0; 16];
+            for index in 0..4 {
+                bytes[index * 4..index * 4 + 4].copy_from_slice(&word(index)?);
+            }
+            IpAddr::V6(Ipv6Addr::from(bytes))
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+fn tcp_state(code: &str, udp: bool) -> &'static str {
+    match code {
+        "01" => "ESTABLISHED",
+        "02" => "SYN_SENT",
+        "03" => "SYN_RECV",
+        "04" => "FIN_WAIT1",
+        "05" => "FIN_WAIT2",
+        "06" => "TIME_WAIT",
+        "07" if udp => "UNCONN",
+        "07" => "CLOSE",
+        "08" => "CLOSE_WAIT",
+        "09" => "LAST_ACK",
+        "0A" => "LISTEN",
+        "0B" => "CLOSING",
+        _ => "",
+    }
+}
+
+// Righe di /proc/<pid>/net/{tcp,udp}[6], indicizzate per inode
+fn parse_inet_sockets(text: &str, protocol: &'static str, sockets: &mut HashMap<u64, Socket>) {
+    for line in text.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (Some(local), Some(remote), Some(state), Some(inode)) =
+            (fields.get(1), fields.get(2), fields.get(3), fields.get(9).and_then(|inode| inode.parse().ok()))
+        else {
+            continue;
+        };
+        let address = |text: &str| parse_inet_address(text).map_or(text.to_string(), |address| address.to_string());
+        sockets.insert(inode, Socket {
+            protocol,
+            local: address(local),
+            remote: address(remote),
+            state: tcp_state(state, protocol.starts_with("UDP")),
+        });
+    }
+}
+
+fn parse_unix_sockets(text: &str, sockets: &mut HashMap<u64, Socket>) {
+    for line in text.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (Some(flags), Some(state), Some(inode)) = (
+            fields.get(3).and_then(|flags| u32::from_str_radix(flags, 16).ok()),
+            fields.get(5),
+            fields.get(6).and_then(|inode| inode.parse().ok()),
+        ) else {
+            continue;
+        };
+        // __SO_ACCEPTCON: il socket accetta connessioni
+        let state = if flags & 0x10000 != 0 {
+            "LISTEN"
+        } else if *state == "03" {
+            "CONNECTED"
+        } else {
+            "UNCONN"
+        };
+        sockets.insert(inode, Socket {
+            protocol: "UNIX",
+            local: fields.get(7).map_or(String::new(), |path| path.to_string()),
+            remote: String::new(),
+            state,
+        });
+    }
+}
+
+fn sockets(pid: u32) -> io::Result<TabContent> {
+    let fds = fd_targets(pid)?;
+
+    // Le tabelle del namespace di rete del processo, non di rtop
+    let mut table = HashMap::new();
+    for (file, protocol) in [("tcp", "TCP"), ("tcp6", "TCP6"), ("udp", "UDP"), ("udp6", "UDP6")] {
+        if let Ok(text) = fs::read_to_string(format!("/proc/{}/net/{}", pid, file)) {
+            parse_inet_sockets(&text, protocol, &mut table);
+        }
+    }
+    if let Ok(text) = fs::read_to_string(format!("/proc/{}/net/unix", pid)) {
+        parse_unix_sockets(&text, &mut table);
+    }
+
+    let rows: Vec<(u32, Option<&Socket>)> = fds
+        .iter()
+        .filter_map(|(fd, target)| {
+            let inode = target.strip_prefix("socket:[")?.strip_suffix(']')?.parse::<u64>().ok()?;
+            Some((*fd, table.get(&inode)))
+        })
+        .collect();
+    if rows.is_empty() {
+        return Ok(note("No open sockets"));
+    }
+
+    let local_width = rows.iter().filter_map(|(_, socket)| socket.map(|s| s.local.len())).max().unwrap_or(0).max(5);
+    let remote_width = rows.iter().filter_map(|(_, socket)| socket.map(|s| s.remote.len())).max().unwrap_or(0).max(6);
+    let row = |fd: &str, protocol: &str, local: &str, remote: &str, state: &str| {
+        format!("{:>6}  {:<5} {:<local_width$} {:<remote_width$} {}", fd, protocol, local, remote, state)
+    };
+
+    let lines = rows
+        .into_iter()
+        .map(|(fd, socket)| {
+            InspectorLine::Text(match socket {
+                Some(socket) => row(&fd.to_string(), socket.protocol, &socket.local, &socket.remote, socket.state),
+                // Netlink, packet e altre famiglie senza tabella in /proc/net
+                None => row(&fd.to_string(), "?", "", "", ""),
+            })
+        })
+        .collect();
+    Ok(TabContent {
+        header: Some(row("FD", "Proto", "Local", "Remote", "State")),
+        lines,
+    })
+}
+
+fn format_kilobytes(kb: u64) -> String {
+    if kb >= 1024 * 1024 {
+        format!("{:.1} GB", kb as f64 / 1024.0 / 1024.0)
+    } else if kb >= 1024 {
+        format!("{:.1} MB", kb as f64 / 1024.0)
+    } else {
+        format!("{} KB", kb)
+    }
+}
+
+fn memory_maps(pid: u32) -> io::Result<TabContent> {
+    let text = fs::read_to_string(format!("/proc/{}/maps", pid))?;
+    let lines = text
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let range = fields.next()?;
+            let permissions = fields.next()?;
+            // Offset, dispositivo e inode non servono
+            let path = fields.skip(3).collect::<Vec<_>>().join(" ");
+            let (start, end) = range.split_once('-')?;
+            let size = u64::from_str_radix(end, 16).ok()? - u64::from_str_radix(start, 16).ok()?;
+            Some(InspectorLine::Text(format!(
+                "{:<25} {:>9} {:<5} {}",
+                range,
+                format_kilobytes(size / 1024),
+                permissions,
+                path
+            )))
+        })
+        .collect();
+    Ok(TabContent {
+        header: Some(format!("{:<25} {:>9} {:<5} {}", "Address", "Size", "Perm", "Path")),
+        lines,
+    })
+}
+
+fn environment(pid: u32) -> io::Result<TabContent> {
+    let environ = fs::read(format!("/proc/{}/environ", pid))?;
+    let mut variables: Vec<String> = environ
+        .split(|&byte| byte == 0)
+        .filter(|variable| !variable.is_empty())
+        .map(|variable| String::from_utf8_lossy(variable).into_owned())
+        .collect();
+    if variables.is_empty() {
+        return Ok(note("Empty environment"));
+    }
+    variables.sort();
+    Ok(TabContent {
+        header: None,
+        lines: variables.into_iter().map(InspectorLine::Text).collect(),
+    })
+}
+
+fn limits(pid: u32) -> io::Result<TabContent> {
+    let text = fs::read_to_string(format!("/proc/{}/limits", pid))?;
+    let mut lines = text.lines().map(|line| line.trim_end().to_string());
+    Ok(TabContent {
+        header: lines.next(),
+        lines: lines.map(InspectorLine::Text).collect(),
+    })
+}
+
+fn namespaces(pid: u32) -> TabContent {
+    let mut lines = vec![InspectorLine::Title("cgroup".to_string())];
+    match fs::read_to_string(format!("/proc/{}/cgroup", pid)) {
+        Ok(text) => lines.extend(text.lines().map(|line| InspectorLine::Text(line.to_string()))),
+        Err(err) => lines.push(InspectorLine::Note(format!("Cannot read the cgroups: {}", err))),
+    }
+
+    lines.push(InspectorLine::Text(String::new()));
+    lines.push(InspectorLine::Title("Namespaces".to_string()));
+    let entries = fs::read_dir(format!("/proc/{}/ns", pid)).and_then(|entries| entries.collect::<io::Result<Vec<_>>>());
+    match entries {
+        Ok(mut entries) => {
+            entries.sort_by_key(|entry| entry.file_name());
+            for entry in entries {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let target = match fs::read_link(entry.path()) {
+                    Ok(target) => target,
+                    Err(err) => {
+                        lines.push(InspectorLine::Field(name, err.to_string()));
+                        continue;
+                    }
+                };
+                // Segnala i namespace diversi da quelli di rtop, cioè i container
+                let own = fs::read_link(format!("/proc/self/ns/{}", name)).ok();
+                let mut value = target.to_string_lossy().into_owned();
+                if own.is_some_and(|own| own != target) {
+                    value.push_str("  (not shared with rtop)");
+                }
+                lines.push(InspectorLine::Field(name, value));
+            }
+        }
+        Err(err) => lines.push(InspectorLine::Note(format!("Cannot read the namespaces: {}", err))),
+    }
+    TabContent { header: None, lines }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{parse_inet_sockets, parse_stat, parse_unix_sockets};
+
+    #[test]
+    fn parses_proc_files() {
+        let stat = parse_stat("4242 (tokio (worker)) S 1 2 3 0 -1 4194368 10 0 0 0 150 50 0 0 20 5 8 0 \
+                               100 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 3 0 0 0 0 0")
+            .unwrap();
+        assert_eq!(stat.name, "tokio (worker)");
+        assert_eq!(stat.state, 'S');
+        assert_eq!(stat.cpu_ticks, 200);
+        assert_eq!(stat.nice, 5);
+        assert_eq!(stat.processor, 3);
+
+        let mut sockets = HashMap::new();
+        parse_inet_sockets(
+            "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+             0: 0100007F:0035 00000000:0000 0A 00000000:00000000 00:00000000 00000000 0 0 1234 1\n",
+            "TCP",
+            &mut sockets,
+        );
+        parse_inet_sockets(
+            "  sl  local_address                         remote_address                        st\n\
+             0: 00000000000000000000000001000000:1F90 00000000000000000000000000000000:0000 07 0 0 0 0 0 5678\n",
+            "UDP6",
+            &mut sockets,
+        );
+        parse_unix_sockets(
+            "Num       RefCount Protocol Flags    Type St Inode Path\n\
+             0000000000000000: 00000002 00000000 00010000 0001 01 91011 /run/rtop.sock\n",
+            &mut sockets,
+        );
+        let socket = |inode| {
+            let socket = &sockets[&inode];
+            (socket.protocol, socket.local.as_str(), socket.state)
+        };
+        assert_eq!(socket(1234), ("TCP", "127.0.0.1:53", "LISTEN"));
+        assert_eq!(socket(5678), ("UDP6", "[::1]:8080", "UNCONN"));
+        assert_eq!(socket(91011), ("UNIX", "/run/rtop.sock", "LISTEN"));
+    }
+}
@@ -1,9 +1,28 @@
-use crate::{App, InputMode};
+use crate::{
+    App, InputMode, Page, SortColumn,
+    helpers::{context_menu::MenuItem, keymap::Action},
+};
 use anyhow::{Ok, Result};
 use crossterm::event::{KeyCode, KeyModifiers};
 use std::time::Duration;
 
 pub fn handle_key_event(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
+    // `?` dentro un overlay mostra i tasti di quell'overlay, tranne in quelli
+    // in cui si scrive testo
+    if code == KeyCode::Char('?')
+        && matches!(
+            app.input_mode,
+            InputMode::ContextMenu
+                | InputMode::ConfirmKill
+                | InputMode::SelectFilter
+                | InputMode::SelectView
+                | InputMode::Error
+        )
+    {
+        app.open_help();
+        return Ok(false);
+    }
+
     // Gestisco le input modes
     match app.input_mode {
         InputMode::SelectFilter => {
@@ -27,34 +46,60 @@ pub fn handle_key_event(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -
         InputMode::MemoryThreshold => {
             return handle_memory_threshold_input(app, code)
         }
+        InputMode::SelectView => {
+            return handle_select_view_input(app, code)
+        }
+        InputMode::SaveView => {
+            return handle_save_view_input(app, code)
+        }
+        InputMode::ContextMenu => {
+            return handle_context_menu_input(app, code)
+        }
+        InputMode::SendSignal | InputMode::Renice => {
+            return handle_menu_prompt_input(app, code)
+        }
+        InputMode::CommandPalette => {
+            return handle_palette_input(app, code)
+        }
         InputMode::Error => {
             return handle_error_overlay_input(app, code)
         }
         InputMode::None => {}
     }
 
+    if app.page == Page::Help && app.help.searching {
+        return handle_help_search_input(app, code);
+    }
+
+    if app.find.typing {
+        return handle_find_input(app, code);
+    }
+
     if app.search_mode {
         match code {
             KeyCode::Esc => {
                 app.search_mode = false;
-                app.search_query.clear();
-                app.cached_flat_processes = None;
-                if app.refresh {app.force_refresh()}
+                app.filter.search.clear();
+                app.reapply_filters();
             }
             KeyCode::Enter => {
                 app.search_mode = false;
                 app.select_first_matching();
             }
             KeyCode::Char(c) => {
-                app.search_query.push(c);
-                app.cached_flat_processes = None;
-                if app.refresh {app.force_refresh()}
+                app.filter.search.push(c);
+                app.reapply_filters();
                 app.select_first_matching();
             }
             KeyCode::Backspace => {
-                app.search_query.pop();
-                app.cached_flat_processes = None;
-                if app.refresh {app.force_refresh()}
+                app.filter.search.pop();
+                app.reapply_filters();
+                app.select_first_matching();
+            }
+            // Sottostringa, fuzzy o regex; la scelta viene salvata
+            KeyCode::Tab => {
+                app.set_search_kind(app.filter.search_kind.next());
+                app.reapply_filters();
                 app.select_first_matching();
             }
             KeyCode::Down => {
@@ -65,131 +110,176 @@ pub fn handle_key_event(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -
             }
             _ => {}
         }
-    } else {
-        match code {
-            KeyCode::Up => {
-                app.select_prev();
-            }
-            KeyCode::Down => {
-                app.select_next();
-            }
-            KeyCode::Char('c') | KeyCode::Char('C') if modifiers.contains(KeyModifiers::CONTROL) => {
+    } else if app.page == Page::Inspector && matches!(code, KeyCode::Tab | KeyCode::BackTab | KeyCode::Left | KeyCode::Right) {
+        // Le schede dell'ispettore hanno la precedenza sui tasti della keymap
+        app.switch_inspector_tab(if matches!(code, KeyCode::Tab | KeyCode::Right) { 1 } else { -1 });
+    } else if let Some(action) = app.keymap.action_for(code, modifiers) {
+        return perform_action(app, action);
+    }
+    Ok(false)
+}
+
+// Esegue un'azione indipendentemente dal tasto che l'ha generata
+pub fn perform_action(app: &mut App, action: Action) -> Result<bool> {
+    match action {
+        Action::Kill | Action::Suspend | Action::Resume | Action::OpenFiles if app.replay.is_some() => {
+            app.refuse_in_replay();
+        }
+        Action::Quit | Action::ForceQuit => {
+            app.save_preferences().ok();
+            app.save_session().ok();
+            return Ok(true);
+        }
+        Action::Back if app.page == Page::Inspector => app.close_inspector(),
+        // Prima chiude la ricerca con salto, poi esce
+        Action::Back if app.page != Page::Help && !app.find.query.search.is_empty() => app.stop_find(),
+        Action::Back => {
+            if app.page != Page::Help {
                 app.save_preferences().ok();
+                app.save_session().ok();
                 return Ok(true);
             }
-            KeyCode::Char('q') | KeyCode::Char('Q') => {
-                app.save_preferences().ok();
-                return Ok(true)
-            },
-            KeyCode::Esc => {
-                if app.page != crate::Page::Help {
-                    app.save_preferences().ok();
-                    return Ok(true);
-                } else {
-                    app.page = crate::Page::Processes;
-                }
-            }
-            KeyCode::F(1) | KeyCode::Char('1') => {
-                app.page = crate::Page::Processes;
-            }
-            KeyCode::F(2) | KeyCode::Char('2') => {
-                app.page = crate::Page::SystemStats;
-            }
-            KeyCode::F(3) | KeyCode::Char('3') | KeyCode::Char('h') | KeyCode::Char('H') | KeyCode::Char('?') => {
-                app.page = crate::Page::Help
-            }
-            KeyCode::Char('f') | KeyCode::Char('F') if modifiers.contains(KeyModifiers::CONTROL) => {
-                app.search_mode = true;
-            }
-            KeyCode::Char('/') => {
-                app.search_mode = true;
-            }
-            KeyCode::Char('k') | KeyCode::Char('K') | KeyCode::Delete => {
-                app.initiate_kill()?;
-            }
-            KeyCode::Char('r') | KeyCode::Char('R') if modifiers.contains(KeyModifiers::CONTROL) => {
-                app.force_refresh();
-            }
-            KeyCode::Char('s') | KeyCode::Char('S') => {
-                app.suspend_process()?;
-            }
-            KeyCode::Char('r') | KeyCode::Char('R') => {
-                app.resume_process()?;
-            }
-            KeyCode::Char('i') | KeyCode::Char('I') => {
-                app.input_mode = InputMode::UpdateInterval;
-                app.input_buffer = app.update_interval.as_millis().to_string();
-            }
-            KeyCode::Char('l') | KeyCode::Char('L') => {
-                app.clear_filters();
-            }
-            KeyCode::Char('w') | KeyCode::Char('W') => {
-                app.input_mode = InputMode::SelectFilter;
-                app.input_buffer.clear();
-            }
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                app.toggle_expand();
-            }
-            KeyCode::Char('t') | KeyCode::Char('T') => {
-                app.go_to_top();
-            }
-            KeyCode::Char('b') | KeyCode::Char('B') => {
-                app.go_to_bottom();
-            }
-            KeyCode::Char('p') => {
-                app.sort_column = crate::SortColumn::Pid;
-                app.reverse_sort = !app.reverse_sort;
-                app.preferences.sort_column = app.sort_column;
-                app.preferences.reverse_sort = app.reverse_sort;
-                if app.refresh {app.force_refresh()}
-            }
-            KeyCode::Char('n') => {
-                app.sort_column = crate::SortColumn::Name;
-                app.reverse_sort = !app.reverse_sort;
-                app.preferences.sort_column = app.sort_column;
-                app.preferences.reverse_sort = app.reverse_sort;
-                if app.refresh {app.force_refresh()}
-            }
-            KeyCode::Char('c') => {
-                app.sort_column = crate::SortColumn::Cpu;
-                app.reverse_sort = !app.reverse_sort;
-                app.preferences.sort_column = app.sort_column;
-                app.preferences.reverse_sort = app.reverse_sort;
-                if app.refresh {app.force_refresh()}
-            }
-            KeyCode::Char('m') => {
-                app.sort_column = crate::SortColumn::Memory;
-                app.reverse_sort = !app.reverse_sort;
-                app.preferences.sort_column = app.sort_column;
-                app.preferences.reverse_sort = app.reverse_sort;
-                if app.refresh {app.force_refresh()}
-            }
-            KeyCode::Char('+') | KeyCode::Char('=') => {
-                let new_interval = app.update_interval.saturating_sub(Duration::from_millis(100));
-                app.update_interval = new_interval.max(Duration::from_millis(100));
-                app.preferences.update_interval_ms = app.update_interval.as_millis() as u64;
-            }
-            KeyCode::Char('-') | KeyCode::Char('_') => {
-                let new_interval = app.update_interval + Duration::from_millis(100);
-                app.update_interval = new_interval.min(Duration::from_millis(6000));
-                app.preferences.update_interval_ms = app.update_interval.as_millis() as u64;
-            }
-            KeyCode::PageDown => {
-                app.page_down();
-            }
-            KeyCode::PageUp => {
-                app.page_up();
-            }
-            KeyCode::Char('z') | KeyCode::Char('Z') => {
-                app.refresh = !app.refresh;
-                app.force_refresh();
-            }
-            KeyCode::Char('x') => {
-                app.process_open_files();
-                
+            if app.help.query.is_empty() {
+                app.close_help();
+            } else {
+                app.set_help_query(String::new());
+            }
+        }
+        // Nell'aiuto la navigazione scorre la pagina
+        Action::SelectUp if app.page == Page::Help => app.scroll_help(-1),
+        Action::SelectDown if app.page == Page::Help => app.scroll_help(1),
+        Action::PageUp if app.page == Page::Help => app.scroll_help(-app.help_page_size()),
+        Action::PageDown if app.page == Page::Help => app.scroll_help(app.help_page_size()),
+        Action::GoTop if app.page == Page::Help => app.help.scroll = 0,
+        Action::GoBottom if app.page == Page::Help => app.help.scroll = usize::MAX,
+        Action::Search if app.page == Page::Help => app.help.searching = true,
+        // Nell'ispettore scorre la scheda aperta
+        Action::SelectUp if app.page == Page::Inspector => app.scroll_inspector(-1),
+        Action::SelectDown if app.page == Page::Inspector => app.scroll_inspector(1),
+        Action::PageUp if app.page == Page::Inspector => app.scroll_inspector(-app.inspector_page_size()),
+        Action::PageDown if app.page == Page::Inspector => app.scroll_inspector(app.inspector_page_size()),
+        Action::GoTop if app.page == Page::Inspector => app.inspector.scroll = 0,
+        Action::GoBottom if app.page == Page::Inspector => app.inspector.scroll = usize::MAX,
+        Action::SelectUp => app.select_prev(),
+        Action::SelectDown => app.select_next(),
+        Action::PageUp => app.page_up(),
+        Action::PageDown => app.page_down(),
+        Action::GoTop => app.go_to_top(),
+        Action::GoBottom => app.go_to_bottom(),
+        Action::ToggleExpand => app.toggle_expand(),
+        Action::ExpandAll => app.expand_to_depth(None),
+        Action::CollapseAll => app.expand_to_depth(Some(0)),
+        Action::Kill => app.initiate_kill()?,
+        Action::Suspend => app.suspend_process()?,
+        Action::Resume => app.resume_process()?,
+        Action::OpenFiles => app.process_open_files(),
+        Action::Inspect => {
+            if app.page == Page::Inspector {
+                app.close_inspector();
+            } else {
+                app.open_inspector();
+            }
+        }
+        Action::ForceRefresh => app.force_refresh(),
+        Action::ToggleAutoRefresh => {
+            app.refresh = !app.refresh;
+            app.force_refresh();
+        }
+        Action::SortPid => set_sort(app, SortColumn::Pid),
+        Action::SortName => set_sort(app, SortColumn::Name),
+        Action::SortCpu => set_sort(app, SortColumn::Cpu),
+        Action::SortMemory => set_sort(app, SortColumn::Memory),
+        Action::CycleSort => {
+            app.sort_column = match app.sort_column {
+                SortColumn::Pid => SortColumn::Name,
+                SortColumn::Name => SortColumn::Cpu,
+                SortColumn::Cpu => SortColumn::Memory,
+                SortColumn::Memory => SortColumn::Pid,
+            };
+            app.preferences.processes.sort_column = app.sort_column;
+            app.reapply_filters();
+        }
+        Action::Search => app.search_mode = true,
+        Action::Find => app.start_find(),
+        Action::FindNext => app.find_next(true),
+        Action::FindPrev => app.find_next(false),
+        Action::SelectFilter => {
+            app.input_mode = InputMode::SelectFilter;
+            app.input_buffer.clear();
+        }
+        Action::ClearFilters => app.clear_filters(),
+        Action::SelectView => {
+            app.input_mode = InputMode::SelectView;
+            app.view_selected = 0;
+        }
+        Action::SetInterval => {
+            app.input_mode = InputMode::UpdateInterval;
+            app.input_buffer = app.update_interval.as_millis().to_string();
+        }
+        Action::FasterRefresh => {
+            let step = Duration::from_millis(app.preferences.refresh.interval_step_ms);
+            let new_interval = app.update_interval.saturating_sub(step);
+            app.update_interval = new_interval.max(Duration::from_millis(100));
+            app.preferences.refresh.update_interval_ms = app.update_interval.as_millis() as u64;
+        }
+        Action::SlowerRefresh => {
+            let step = Duration::from_millis(app.preferences.refresh.interval_step_ms);
+            let new_interval = app.update_interval + step;
+            app.update_interval = new_interval.min(Duration::from_millis(6000));
+            app.preferences.refresh.update_interval_ms = app.update_interval.as_millis() as u64;
+        }
+        Action::ToggleTree => {
+            app.tree_mode = !app.tree_mode;
+            app.preferences.processes.tree_mode = app.tree_mode;
+            app.force_refresh();
+        }
+        Action::ShowProcesses => app.page = Page::Processes,
+        Action::ShowStats => app.page = Page::SystemStats,
+        Action::CommandPalette => app.open_palette(),
+        Action::ShowHelp => {
+            if app.page == Page::Help {
+                app.close_help();
+            } else {
+                app.open_help();
             }
-            _ => {}
         }
+        Action::ReplayStepBack => app.replay_step(-1),
+        Action::ReplayStepForward => app.replay_step(1),
+        Action::ReplaySeekBack => app.replay_jump(-0.1),
+        Action::ReplaySeekForward => app.replay_jump(0.1),
+    }
+    Ok(false)
+}
+
+// Premere di nuovo lo stesso ordinamento inverte la direzione
+fn set_sort(app: &mut App, column: SortColumn) {
+    app.sort_column = column;
+    app.reverse_sort = !app.reverse_sort;
+    app.preferences.processes.sort_column = app.sort_column;
+    app.preferences.processes.reverse_sort = app.reverse_sort;
+    app.reapply_filters();
+}
+
+fn handle_help_search_input(app: &mut App, code: KeyCode) -> Result<bool> {
+    match code {
+        KeyCode::Esc => {
+            app.help.searching = false;
+            app.set_help_query(String::new());
+        }
+        KeyCode::Enter => app.help.searching = false,
+        KeyCode::Char(c) => {
+            let mut query = std::mem::take(&mut app.help.query);
+            query.push(c);
+            app.set_help_query(query);
+        }
+        KeyCode::Backspace => {
+            let mut query = std::mem::take(&mut app.help.query);
+            query.pop();
+            app.set_help_query(query);
+        }
+        KeyCode::Up => app.scroll_help(-1),
+        KeyCode::Down => app.scroll_help(1),
+        _ => {}
     }
     Ok(false)
 }
@@ -197,27 +287,27 @@ pub fn handle_key_event(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -
 fn handle_select_filter_input(app: &mut App, code: KeyCode) -> Result<bool> {
     match code {
         KeyCode::Enter => {
-            if let std::result::Result::Ok(number) = app.input_buffer.parse::<i8>() {
-                if (0..=5).contains(&number) {
-                    match number {
-                        0 => {
-                            app.clear_filters();
-                            app.input_mode = InputMode::None;
-                        }
-                        1 => {
-                            app.input_mode = InputMode::UserFilter;
-                        },
-                        2 => {
-                            app.input_mode = InputMode::StatusFilter;
-                        },
-                        3 => {
-                            app.input_mode = InputMode::CpuThreshold;
-                        },
-                        4 => {
-                            app.input_mode = InputMode::MemoryThreshold;
-                        }
-                        _ => {}
+            if let std::result::Result::Ok(number) = app.input_buffer.parse::<i8>()
+                && (0..=5).contains(&number)
+            {
+                match number {
+                    0 => {
+                        app.clear_filters();
+                        app.input_mode = InputMode::None;
+                    }
+                    1 => {
+                        app.input_mode = InputMode::UserFilter;
+                    },
+                    2 => {
+                        app.input_mode = InputMode::StatusFilter;
+                    },
+                    3 => {
+                        app.input_mode = InputMode::CpuThreshold;
+                    },
+                    4 => {
+                        app.input_mode = InputMode::MemoryThreshold;
                     }
+                    _ => {}
                 }
             }
             app.input_buffer.clear();
@@ -234,7 +324,73 @@ fn handle_select_filter_input(app: &mut App, code: KeyCode) -> Result<bool> {
         }
         _ => {}
     }
-    if app.refresh {app.force_refresh()}
+    Ok(false)
+}
+
+fn handle_select_view_input(app: &mut App, code: KeyCode) -> Result<bool> {
+    let names = app.view_names();
+    match code {
+        KeyCode::Up => app.view_selected = app.view_selected.saturating_sub(1),
+        KeyCode::Down => {
+            app.view_selected = (app.view_selected + 1).min(names.len().saturating_sub(1));
+        }
+        KeyCode::Enter => {
+            if let Some(view) = names.get(app.view_selected).and_then(|name| app.preferences.views.get(name)) {
+                let view = view.clone();
+                app.apply_view(&view);
+            }
+            app.input_mode = InputMode::None;
+        }
+        KeyCode::Char('s') => {
+            app.input_mode = InputMode::SaveView;
+            app.input_buffer.clear();
+        }
+        KeyCode::Char('d') => {
+            if let Some(name) = names.get(app.view_selected) {
+                if let Err(err) = app.delete_view(name) {
+                    app.errors.push(("Views".to_string(), format!("{:#}", err)));
+                    app.input_mode = InputMode::Error;
+                }
+                app.view_selected = app.view_selected.min(names.len().saturating_sub(2));
+            }
+        }
+        KeyCode::Esc => app.input_mode = InputMode::None,
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn handle_save_view_input(app: &mut App, code: KeyCode) -> Result<bool> {
+    match code {
+        KeyCode::Enter => {
+            let name = app.input_buffer.trim().to_string();
+            app.input_mode = InputMode::SelectView;
+            if !name.is_empty() {
+                match app.save_view(&name) {
+                    std::result::Result::Ok(()) => {
+                        app.view_selected = app.view_names().iter().position(|n| *n == name).unwrap_or(0);
+                    }
+                    Err(err) => {
+                        app.errors.push(("Views".to_string(), format!("{:#}", err)));
+                        app.input_mode = InputMode::Error;
+                    }
+                }
+            }
+            app.input_buffer.clear();
+        }
+        KeyCode::Esc => {
+            app.input_mode = InputMode::SelectView;
+            app.input_buffer.clear();
+        }
+        // Il nome diventa una chiave TOML, meglio evitare caratteri da quotare
+        KeyCode::Char(c) if c.is_alphanumeric() || c == '-' || c == '_' => {
+            app.input_buffer.push(c);
+        }
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        }
+        _ => {}
+    }
     Ok(false)
 }
 
@@ -244,7 +400,7 @@ fn handle_update_interval_input(app: &mut App, code: KeyCode) -> Result<bool> {
             if let std::result::Result::Ok(ms) = app.input_buffer.parse::<u64>() {
                 let ms = ms.clamp(100, 6000);
                 app.update_interval = Duration::from_millis(ms);
-                app.preferences.update_interval_ms = ms;
+                app.preferences.refresh.update_interval_ms = ms;
                 app.save_preferences().ok();
             }
             app.input_mode = InputMode::None;
@@ -262,7 +418,6 @@ fn handle_update_interval_input(app: &mut App, code: KeyCode) -> Result<bool> {
         }
         _ => {}
     }
-    if app.refresh {app.force_refresh()}
     Ok(false)
 }
 
@@ -270,9 +425,7 @@ fn handle_confirm_kill(app: &mut App, code: KeyCode) -> Result<bool> {
     match code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
             if let Some(pid) = app.pending_kill_pid {
-                if let Some(process) = app.system.process(pid) {
-                    process.kill();
-                }
+                app.control.kill(pid.as_u32());
                 app.force_refresh();
             }
             app.input_mode = InputMode::None;
@@ -291,14 +444,13 @@ fn handle_user_filter_input(app: &mut App, code: KeyCode) -> Result<bool> {
     match code {
         KeyCode::Enter => {
             if app.input_buffer.is_empty() {
-                app.user_filter = None;
+                app.filter.user = None;
             } else {
-                app.user_filter = Some(app.input_buffer.clone());
+                app.filter.user = Some(app.input_buffer.clone());
             }
             app.input_mode = InputMode::None;
             app.input_buffer.clear();
-            app.cached_flat_processes = None;
-            if app.refresh {app.force_refresh()}
+            app.reapply_filters();
         }
         KeyCode::Esc => {
             app.input_mode = InputMode::None;
@@ -319,14 +471,13 @@ fn handle_status_filter_input(app: &mut App, code: KeyCode) -> Result<bool> {
     match code {
         KeyCode::Enter => {
             if app.input_buffer.is_empty() {
-                app.status_filter = None;
+                app.filter.status = None;
             } else {
-                app.status_filter = Some(app.input_buffer.clone());
+                app.filter.status = Some(app.input_buffer.clone());
             }
             app.input_mode = InputMode::None;
             app.input_buffer.clear();
-            app.cached_flat_processes = None;
-            if app.refresh {app.force_refresh()}
+            app.reapply_filters();
         }
         KeyCode::Esc => {
             app.input_mode = InputMode::None;
@@ -347,16 +498,15 @@ fn handle_cpu_threshold_input(app: &mut App, code: KeyCode) -> Result<bool> {
     match code {
         KeyCode::Enter => {
             if app.input_buffer.is_empty() {
-                app.cpu_threshold = None;
+                app.filter.cpu = None;
             } else {
                 if let std::result::Result::Ok(threshold) = app.input_buffer.parse::<f32>() {
-                    app.cpu_threshold = Some(threshold.clamp(0.0, 100.0));
+                    app.filter.cpu = Some(threshold.clamp(0.0, 100.0));
                 }
             }
             app.input_mode = InputMode::None;
             app.input_buffer.clear();
-            app.cached_flat_processes = None;
-            if app.refresh {app.force_refresh()}
+            app.reapply_filters();
         }
         KeyCode::Esc => {
             app.input_mode = InputMode::None;
@@ -377,17 +527,16 @@ fn handle_memory_threshold_input(app: &mut App, code: KeyCode) -> Result<bool> {
     match code {
         KeyCode::Enter => {
             if app.input_buffer.is_empty() {
-                app.memory_threshold = None;
+                app.filter.memory = None;
             } else {
                 if let std::result::Result::Ok(mb) = app.input_buffer.parse::<u64>() {
                     // Convert MB to bytes
-                    app.memory_threshold = Some(mb * 1024 * 1024);
+                    app.filter.memory = Some(mb * 1024 * 1024);
                 }
             }
             app.input_mode = InputMode::None;
             app.input_buffer.clear();
-            app.cached_flat_processes = None;
-            if app.refresh {app.force_refresh()}
+            app.reapply_filters();
         }
         KeyCode::Esc => {
             app.input_mode = InputMode::None;
@@ -404,6 +553,88 @@ fn handle_memory_threshold_input(app: &mut App, code: KeyCode) -> Result<bool> {
     Ok(false)
 }
 
+fn handle_context_menu_input(app: &mut App, code: KeyCode) -> Result<bool> {
+    match code {
+        KeyCode::Up => app.move_menu_selection(-1),
+        KeyCode::Down => app.move_menu_selection(1),
+        KeyCode::Enter => {
+            if let Some(menu) = &app.context_menu {
+                app.run_menu_item(MenuItem::ALL[menu.selected])?;
+            }
+        }
+        KeyCode::Esc => app.close_context_menu(),
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn handle_menu_prompt_input(app: &mut App, code: KeyCode) -> Result<bool> {
+    match code {
+        KeyCode::Enter => {
+            if app.input_mode == InputMode::SendSignal {
+                app.send_signal_to_menu_process();
+            } else {
+                app.renice_menu_process();
+            }
+        }
+        KeyCode::Esc => app.close_context_menu(),
+        KeyCode::Char(c) if c.is_ascii_alphanumeric() || c == '-' => {
+            app.input_buffer.push(c);
+        }
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn handle_find_input(app: &mut App, code: KeyCode) -> Result<bool> {
+    match code {
+        KeyCode::Esc => app.stop_find(),
+        // Il testo resta per saltare tra i match
+        KeyCode::Enter => app.find.typing = false,
+        KeyCode::Char(c) => {
+            let query = format!("{}{}", app.find.query.search, c);
+            app.set_find_query(query);
+        }
+        KeyCode::Backspace => {
+            let mut query = app.find.query.search.clone();
+            query.pop();
+            app.set_find_query(query);
+        }
+        KeyCode::Tab => {
+            app.set_search_kind(app.find.query.search_kind.next());
+            let query = app.find.query.search.clone();
+            app.set_find_query(query);
+        }
+        KeyCode::Down => app.find_next(true),
+        KeyCode::Up => app.find_next(false),
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn handle_palette_input(app: &mut App, code: KeyCode) -> Result<bool> {
+    match code {
+        KeyCode::Enter => return app.run_palette(),
+        KeyCode::Esc => app.close_palette(),
+        KeyCode::Tab => app.complete_palette(),
+        KeyCode::Up => app.move_palette_selection(-1),
+        KeyCode::Down => app.move_palette_selection(1),
+        KeyCode::Char(c) => {
+            app.input_buffer.push(c);
+            app.palette.selected = 0;
+        }
+        KeyCode::Backspace => {
+            app.input_buffer.pop();
+            app.palette.selected = 0;
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
 fn handle_error_overlay_input(app: &mut App, code: KeyCode) -> Result<bool> {
     match code{
         KeyCode::Enter => {
@@ -417,4 +648,374 @@ fn handle_error_overlay_input(app: &mut App, code: KeyCode) -> Result<bool> {
         _ => {}
     }
     Ok(false)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use rtop::filter::SearchKind;
+
+    use super::handle_key_event;
+    use crate::{
+        App, InputMode, Page, SortColumn,
+        helpers::{
+            fake,
+            keymap::{KeybindingsConfig, Keymap, KeymapPreset},
+        },
+    };
+
+    fn press(app: &mut App, code: KeyCode) -> bool {
+        // Come dopo un disegno, che aggiorna le righe visibili
+        app.flatten_processes();
+        handle_key_event(app, code, KeyModifiers::NONE).unwrap()
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    fn selected_pid(app: &mut App) -> Option<u32> {
+        app.flatten_processes();
+        let selected = app.table_state.selected()?;
+        app.get_process_at_flat_index(selected).map(|node| node.info.pid.as_u32())
+    }
+
+    fn visible_rows(app: &mut App) -> usize {
+        app.flatten_processes().len()
+    }
+
+    // Ordinati per CPU: cargo, firefox, bash, init, sshd
+    fn flat_app() -> App {
+        let mut app = fake::app(vec![fake::machine()]);
+        app.tree_mode = false;
+        app.force_refresh();
+        app
+    }
+
+    #[test]
+    fn navigation() {
+        let mut app = flat_app();
+        assert_eq!(selected_pid(&mut app), Some(300));
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(selected_pid(&mut app), Some(200));
+
+        press(&mut app, KeyCode::Up);
+        assert_eq!(selected_pid(&mut app), Some(400));
+
+        press(&mut app, KeyCode::Char('b'));
+        assert_eq!(selected_pid(&mut app), Some(100));
+
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(selected_pid(&mut app), Some(300));
+    }
+
+    #[test]
+    fn sort_keys_toggle_direction() {
+        let mut app = flat_app();
+
+        press(&mut app, KeyCode::Char('n'));
+        assert!(app.sort_column == SortColumn::Name);
+        app.table_state.select(Some(0));
+        assert_eq!(selected_pid(&mut app), Some(200));
+
+        press(&mut app, KeyCode::Char('n'));
+        app.table_state.select(Some(0));
+        assert_eq!(selected_pid(&mut app), Some(100));
+    }
+
+    #[test]
+    fn search() {
+        let mut app = flat_app();
+
+        press(&mut app, KeyCode::Char('/'));
+        assert!(app.search_mode);
+        type_text(&mut app, "fire");
+        assert_eq!(app.filter.search, "fire");
+        assert_eq!(selected_pid(&mut app), Some(400));
+
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.search_mode);
+        assert_eq!(app.filter.search, "fire");
+
+        press(&mut app, KeyCode::Char('/'));
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.search_mode);
+        assert!(app.filter.search.is_empty());
+    }
+
+    #[test]
+    fn typing_filters_the_current_sample() {
+        let mut app = fake::app(fake::timeline(10));
+        app.tree_mode = false;
+        app.force_refresh();
+        let timestamp = app.snapshot.timestamp;
+
+        // Ricerca, ordinamento e soglie non chiedono nuovi campioni
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "fire");
+        assert_eq!(visible_rows(&mut app), 1);
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('m'));
+        assert!(app.sort_column == SortColumn::Memory);
+        app.filter.cpu = Some(10.0);
+        app.clear_filters();
+        assert_eq!(visible_rows(&mut app), 5);
+        assert_eq!(app.snapshot.timestamp, timestamp);
+
+        app.force_refresh();
+        assert!(app.snapshot.timestamp > timestamp);
+    }
+
+    #[test]
+    fn search_modes_pick_the_best_match() {
+        let mut app = flat_app();
+
+        // Il nome che inizia con "s" batte i percorsi /usr/bin degli altri
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "s");
+        assert_eq!(visible_rows(&mut app), 5);
+        assert_eq!(selected_pid(&mut app), Some(100));
+
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Tab);
+        type_text(&mut app, "fx");
+        assert_eq!(visible_rows(&mut app), 1);
+        assert_eq!(selected_pid(&mut app), Some(400));
+
+        press(&mut app, KeyCode::Tab);
+        assert!(app.preferences.processes.search_mode == SearchKind::Regex);
+        assert_eq!(visible_rows(&mut app), 0);
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Backspace);
+        type_text(&mut app, "^(ba|ss)");
+        assert_eq!(visible_rows(&mut app), 2);
+        type_text(&mut app, "(");
+        assert!(app.filter.invalid_regex());
+        assert_eq!(visible_rows(&mut app), 0);
+    }
+
+    #[test]
+    fn find_jumps_without_filtering() {
+        let mut app = flat_app();
+
+        press(&mut app, KeyCode::Char('f'));
+        type_text(&mut app, "sh");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(visible_rows(&mut app), 5);
+        assert_eq!(selected_pid(&mut app), Some(200));
+
+        press(&mut app, KeyCode::Char(';'));
+        assert_eq!(selected_pid(&mut app), Some(100));
+        assert_eq!(app.find_counter(), (Some(2), 2));
+        press(&mut app, KeyCode::Char(';'));
+        assert_eq!(selected_pid(&mut app), Some(200));
+        press(&mut app, KeyCode::Char(','));
+        assert_eq!(selected_pid(&mut app), Some(100));
+
+        // Esc chiude la ricerca invece di uscire
+        assert!(!press(&mut app, KeyCode::Esc));
+        assert_eq!(app.find_counter(), (None, 0));
+
+        // Nel preset vim sono /, n e N come in less
+        app.keymap = Keymap::from_config(&KeybindingsConfig {
+            preset: KeymapPreset::Vim,
+            ..Default::default()
+        })
+        .0;
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "sh");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(selected_pid(&mut app), Some(100));
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(selected_pid(&mut app), Some(200));
+        press(&mut app, KeyCode::Char('N'));
+        assert_eq!(selected_pid(&mut app), Some(100));
+        assert_eq!(visible_rows(&mut app), 5);
+    }
+
+    #[test]
+    fn inspector_tabs_follow_the_process() {
+        let mut app = flat_app();
+        press(&mut app, KeyCode::Char('d'));
+        assert!(app.page == Page::Inspector);
+        assert_eq!(app.inspector.pid.map(|pid| pid.as_u32()), Some(300));
+
+        // Le frecce cambiano scheda anche in senso inverso, su/giù scorrono
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Left);
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.inspector.tab, 7);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.inspector.scroll, 1);
+        assert_eq!(selected_pid(&mut app), Some(300));
+
+        // Dopo la terminazione restano gli ultimi dati letti
+        app.control.signal(300, libc::SIGKILL).unwrap();
+        app.force_refresh();
+        assert!(app.inspector.exited);
+        assert!(!app.inspector.content.lines.is_empty());
+
+        assert!(!press(&mut app, KeyCode::Esc));
+        assert!(app.page == Page::Processes);
+        assert!(app.inspector.pid.is_none());
+    }
+
+    #[test]
+    fn tree_expansion_and_search() {
+        // Albero: sshd > bash > cargo e firefox, tutti chiusi
+        let mut app = fake::app(vec![fake::machine(), fake::machine()]);
+        assert_eq!(visible_rows(&mut app), 2);
+
+        press(&mut app, KeyCode::Char('*'));
+        assert_eq!(visible_rows(&mut app), 4);
+        press(&mut app, KeyCode::Char('E'));
+        assert_eq!(visible_rows(&mut app), 2);
+
+        // Il match in profondità apre i suoi antenati
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "cargo");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(visible_rows(&mut app), 3);
+        assert_eq!(selected_pid(&mut app), Some(300));
+
+        // Chiuso a mano resta chiuso anche dopo un refresh
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Enter);
+        app.refresh();
+        assert_eq!(visible_rows(&mut app), 2);
+    }
+
+    #[test]
+    fn cpu_threshold_filter() {
+        let mut app = flat_app();
+
+        press(&mut app, KeyCode::Char('w'));
+        assert!(matches!(app.input_mode, InputMode::SelectFilter));
+        press(&mut app, KeyCode::Char('3'));
+        press(&mut app, KeyCode::Enter);
+        assert!(matches!(app.input_mode, InputMode::CpuThreshold));
+
+        type_text(&mut app, "10");
+        press(&mut app, KeyCode::Enter);
+        assert!(matches!(app.input_mode, InputMode::None));
+        assert_eq!(app.filter.cpu, Some(10.0));
+        assert_eq!(visible_rows(&mut app), 2);
+
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(app.filter.cpu, None);
+        assert_eq!(visible_rows(&mut app), 5);
+    }
+
+    #[test]
+    fn kill_without_confirmation() {
+        let mut app = flat_app();
+
+        press(&mut app, KeyCode::Char('k'));
+        assert!(matches!(app.input_mode, InputMode::None));
+        assert!(app.snapshot.process(300).is_none());
+        assert_eq!(visible_rows(&mut app), 4);
+    }
+
+    #[test]
+    fn kill_critical_process_asks_first() {
+        let mut app = flat_app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(selected_pid(&mut app), Some(1));
+
+        press(&mut app, KeyCode::Char('k'));
+        assert!(matches!(app.input_mode, InputMode::ConfirmKill));
+        press(&mut app, KeyCode::Char('n'));
+        assert!(matches!(app.input_mode, InputMode::None));
+        assert!(app.snapshot.process(1).is_some());
+
+        press(&mut app, KeyCode::Char('k'));
+        press(&mut app, KeyCode::Char('y'));
+        assert!(app.snapshot.process(1).is_none());
+    }
+
+    #[test]
+    fn quit() {
+        let mut app = flat_app();
+        assert!(!press(&mut app, KeyCode::Char('?')));
+        // Esc dall'help torna ai processi invece di uscire
+        assert!(!press(&mut app, KeyCode::Esc));
+        assert!(press(&mut app, KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn help_for_the_open_overlay() {
+        let mut app = flat_app();
+        app.page = Page::SystemStats;
+
+        press(&mut app, KeyCode::Char('w'));
+        press(&mut app, KeyCode::Char('?'));
+        assert!(app.page == Page::Help);
+        let sections = app.help_sections();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].title, "Filter menu");
+
+        // Esc torna all'overlay e alla pagina di partenza
+        press(&mut app, KeyCode::Esc);
+        assert!(matches!(app.input_mode, InputMode::SelectFilter));
+        assert!(app.page == Page::SystemStats);
+    }
+
+    #[test]
+    fn help_search() {
+        let mut app = flat_app();
+        press(&mut app, KeyCode::Char('?'));
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "sort by");
+
+        let sections = app.help_sections();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].entries.len(), 4);
+        assert!(sections[0].entries.iter().any(|(keys, description)| keys == "c" && description == "Sort by CPU usage"));
+
+        // Il primo Esc chiude la ricerca, poi si torna ai processi
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Esc);
+        assert!(app.help.query.is_empty());
+        assert!(app.page == Page::Help);
+        press(&mut app, KeyCode::Esc);
+        assert!(app.page == Page::Processes);
+    }
+
+    #[test]
+    fn question_mark_is_text_in_prompts() {
+        let mut app = flat_app();
+        press(&mut app, KeyCode::Char(':'));
+        assert!(app.input_mode == InputMode::CommandPalette);
+        type_text(&mut app, "he?");
+        assert_eq!(app.input_buffer, "he?");
+        assert!(app.page == Page::Processes);
+
+        press(&mut app, KeyCode::Esc);
+        app.input_mode = InputMode::SaveView;
+        type_text(&mut app, "why?");
+        assert!(app.input_mode == InputMode::SaveView);
+        assert!(app.page == Page::Processes);
+
+        press(&mut app, KeyCode::Esc);
+        app.input_mode = InputMode::StatusFilter;
+        type_text(&mut app, "s?");
+        assert_eq!(app.input_buffer, "s?");
+        assert!(app.page == Page::Processes);
+
+        // Dove non si scrive, `?` apre l'aiuto dell'overlay
+        app.input_mode = InputMode::None;
+        app.input_buffer.clear();
+        press(&mut app, KeyCode::Char('w'));
+        assert!(app.input_mode == InputMode::SelectFilter);
+        press(&mut app, KeyCode::Char('?'));
+        assert!(app.page == Page::Help);
+        assert!(app.help.overlay == Some(InputMode::SelectFilter));
+    }
+}
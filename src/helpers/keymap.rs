@@ -0,0 +1,709 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Action {
+    Quit,
+    ForceQuit,
+    Back,
+    SelectUp,
+    SelectDown,
+    PageUp,
+    PageDown,
+    GoTop,
+    GoBottom,
+    ToggleExpand,
+    ExpandAll,
+    CollapseAll,
+    Kill,
+    Suspend,
+    Resume,
+    OpenFiles,
+    Inspect,
+    ForceRefresh,
+    ToggleAutoRefresh,
+    SortPid,
+    SortName,
+    SortCpu,
+    SortMemory,
+    CycleSort,
+    Search,
+    Find,
+    FindNext,
+    FindPrev,
+    SelectFilter,
+    ClearFilters,
+    SelectView,
+    SetInterval,
+    FasterRefresh,
+    SlowerRefresh,
+    ToggleTree,
+    ShowProcesses,
+    ShowStats,
+    ShowHelp,
+    CommandPalette,
+    ReplayStepBack,
+    ReplayStepForward,
+    ReplaySeekBack,
+    ReplaySeekForward,
+}
+
+impl Action {
+    pub const ALL: [Action; 43] = [
+        Action::SelectUp,
+        Action::SelectDown,
+        Action::PageUp,
+        Action::PageDown,
+        Action::GoTop,
+        Action::GoBottom,
+        Action::ToggleExpand,
+        Action::Kill,
+        Action::Suspend,
+        Action::Resume,
+        Action::OpenFiles,
+        Action::Inspect,
+        Action::ForceRefresh,
+        Action::SortPid,
+        Action::SortName,
+        Action::SortCpu,
+        Action::SortMemory,
+        Action::CycleSort,
+        Action::Search,
+        Action::Find,
+        Action::FindNext,
+        Action::FindPrev,
+        Action::SelectFilter,
+        Action::ClearFilters,
+        Action::SelectView,
+        Action::ShowProcesses,
+        Action::ShowStats,
+        Action::ShowHelp,
+        Action::CommandPalette,
+        Action::ToggleTree,
+        Action::ExpandAll,
+        Action::CollapseAll,
+        Action::SetInterval,
+        Action::FasterRefresh,
+        Action::SlowerRefresh,
+        Action::ToggleAutoRefresh,
+        Action::ReplayStepBack,
+        Action::ReplayStepForward,
+        Action::ReplaySeekBack,
+        Action::ReplaySeekForward,
+        Action::Quit,
+        Action::ForceQuit,
+        Action::Back,
+    ];
+
+    // Nome usato nella sezione [keybindings] del file di configurazione
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::ForceQuit => "force_quit",
+            Action::Back => "back",
+            Action::SelectUp => "up",
+            Action::SelectDown => "down",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::GoTop => "top",
+            Action::GoBottom => "bottom",
+            Action::ToggleExpand => "toggle_expand",
+            Action::ExpandAll => "expand_all",
+            Action::CollapseAll => "collapse_all",
+            Action::Kill => "kill",
+            Action::Suspend => "suspend",
+            Action::Resume => "resume",
+            Action::OpenFiles => "open_files",
+            Action::Inspect => "inspect",
+            Action::ForceRefresh => "refresh",
+            Action::ToggleAutoRefresh => "toggle_auto_refresh",
+            Action::SortPid => "sort_pid",
+            Action::SortName => "sort_name",
+            Action::SortCpu => "sort_cpu",
+            Action::SortMemory => "sort_memory",
+            Action::CycleSort => "cycle_sort",
+            Action::Search => "search",
+            Action::Find => "find",
+            Action::FindNext => "find_next",
+            Action::FindPrev => "find_prev",
+            Action::SelectFilter => "filter",
+            Action::ClearFilters => "clear_filters",
+            Action::SelectView => "views",
+            Action::SetInterval => "set_interval",
+            Action::FasterRefresh => "faster",
+            Action::SlowerRefresh => "slower",
+            Action::ToggleTree => "toggle_tree",
+            Action::ShowProcesses => "processes",
+            Action::ShowStats => "stats",
+            Action::ShowHelp => "help",
+            Action::CommandPalette => "command_palette",
+            Action::ReplayStepBack => "replay_step_back",
+            Action::ReplayStepForward => "replay_step_forward",
+            Action::ReplaySeekBack => "replay_seek_back",
+            Action::ReplaySeekForward => "replay_seek_forward",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|action| action.name() == name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit (saves preferences)",
+            Action::ForceQuit => "Force quit (saves preferences)",
+            Action::Back => "Close help/inspector, otherwise quit",
+            Action::SelectUp => "Move selection up",
+            Action::SelectDown => "Move selection down",
+            Action::PageUp => "Navigate one page up",
+            Action::PageDown => "Navigate one page down",
+            Action::GoTop => "Jump to top",
+            Action::GoBottom => "Jump to bottom",
+            Action::ToggleExpand => "Expand/collapse process tree",
+            Action::ExpandAll => "Expand every process",
+            Action::CollapseAll => "Collapse every process",
+            Action::Kill => "Kill process",
+            Action::Suspend => "Suspend process (SIGSTOP)",
+            Action::Resume => "Resume process (SIGCONT)",
+            Action::OpenFiles => "View a process's open files",
+            Action::Inspect => "Inspect a process full screen",
+            Action::ForceRefresh => "Force refresh",
+            Action::ToggleAutoRefresh => "Toggle auto refresh / pause replay",
+            Action::SortPid => "Sort by PID",
+            Action::SortName => "Sort by name",
+            Action::SortCpu => "Sort by CPU usage",
+            Action::SortMemory => "Sort by memory usage",
+            Action::CycleSort => "Cycle sort column",
+            Action::Search => "Search processes",
+            Action::Find => "Find processes without filtering",
+            Action::FindNext => "Jump to the next found process",
+            Action::FindPrev => "Jump to the previous found process",
+            Action::SelectFilter => "Select the filtering mode",
+            Action::ClearFilters => "Clear all filters",
+            Action::SelectView => "Saved views",
+            Action::SetInterval => "Set custom update interval",
+            Action::FasterRefresh => "Increase update speed",
+            Action::SlowerRefresh => "Decrease update speed",
+            Action::ToggleTree => "Toggle tree/flat view",
+            Action::ShowProcesses => "Process view",
+            Action::ShowStats => "System stats view",
+            Action::ShowHelp => "Help screen",
+            Action::CommandPalette => "Command palette",
+            Action::ReplayStepBack => "Replay: previous sample",
+            Action::ReplayStepForward => "Replay: next sample",
+            Action::ReplaySeekBack => "Replay: jump back 10%",
+            Action::ReplaySeekForward => "Replay: jump forward 10%",
+        }
+    }
+
+    pub fn group(&self) -> &'static str {
+        match self {
+            Action::SelectUp
+            | Action::SelectDown
+            | Action::PageUp
+            | Action::PageDown
+            | Action::GoTop
+            | Action::GoBottom => "Navigation",
+            Action::ToggleExpand
+            | Action::Kill
+            | Action::Suspend
+            | Action::Resume
+            | Action::OpenFiles
+            | Action::Inspect => "Process actions",
+            Action::SortPid
+            | Action::SortName
+            | Action::SortCpu
+            | Action::SortMemory
+            | Action::CycleSort
+            | Action::ToggleTree
+            | Action::ExpandAll
+            | Action::CollapseAll => "Sorting & tree",
+            Action::Search
+            | Action::Find
+            | Action::FindNext
+            | Action::FindPrev
+            | Action::SelectFilter
+            | Action::ClearFilters
+            | Action::SelectView => "Search & Filter",
+            Action::ForceRefresh
+            | Action::SetInterval
+            | Action::FasterRefresh
+            | Action::SlowerRefresh
+            | Action::ToggleAutoRefresh => "Refresh",
+            Action::ReplayStepBack
+            | Action::ReplayStepForward
+            | Action::ReplaySeekBack
+            | Action::ReplaySeekForward => "Replay",
+            Action::ShowProcesses
+            | Action::ShowStats
+            | Action::ShowHelp
+            | Action::CommandPalette
+            | Action::Quit
+            | Action::ForceQuit
+            | Action::Back => "General",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KeymapPreset {
+    #[default]
+    Default,
+    Htop,
+    Vim,
+}
+
+impl KeymapPreset {
+    fn bindings(&self) -> Vec<(Action, &'static [&'static str])> {
+        match self {
+            KeymapPreset::Default => vec![
+                (Action::SelectUp, &["up"]),
+                (Action::SelectDown, &["down"]),
+                (Action::PageUp, &["pageup"]),
+                (Action::PageDown, &["pagedown"]),
+                (Action::GoTop, &["t", "T"]),
+                (Action::GoBottom, &["b", "B"]),
+                (Action::ToggleExpand, &["enter", "space"]),
+                (Action::Kill, &["k", "K", "delete"]),
+                (Action::Suspend, &["s", "S"]),
+                // r e Ctrl+R, come c e Ctrl+C, restano separati: i tasti si
+                // confrontano con i modificatori, non solo con il carattere
+                (Action::Resume, &["r", "R"]),
+                (Action::OpenFiles, &["x"]),
+                (Action::Inspect, &["d", "D"]),
+                (Action::ForceRefresh, &["ctrl+r"]),
+                (Action::SortPid, &["p"]),
+                (Action::SortName, &["n"]),
+                (Action::SortCpu, &["c"]),
+                (Action::SortMemory, &["m"]),
+                (Action::Search, &["/", "ctrl+f"]),
+                (Action::Find, &["f"]),
+                (Action::FindNext, &[";"]),
+                (Action::FindPrev, &[","]),
+                (Action::SelectFilter, &["w", "W"]),
+                (Action::ClearFilters, &["l", "L"]),
+                (Action::SelectView, &["v", "V"]),
+                (Action::ShowProcesses, &["1", "f1"]),
+                (Action::ShowStats, &["2", "f2"]),
+                (Action::ShowHelp, &["?", "h", "H", "3", "f3"]),
+                (Action::CommandPalette, &[":"]),
+                (Action::ToggleTree, &["f5"]),
+                (Action::ExpandAll, &["*", "e"]),
+                (Action::CollapseAll, &["E"]),
+                (Action::SetInterval, &["i", "I"]),
+                (Action::FasterRefresh, &["+", "="]),
+                (Action::SlowerRefresh, &["-", "_"]),
+                (Action::ToggleAutoRefresh, &["z", "Z"]),
+                (Action::ReplayStepBack, &["left"]),
+                (Action::ReplayStepForward, &["right"]),
+                (Action::ReplaySeekBack, &["["]),
+                (Action::ReplaySeekForward, &["]"]),
+                (Action::Quit, &["q", "Q"]),
+                (Action::ForceQuit, &["ctrl+c"]),
+                (Action::Back, &["esc"]),
+            ],
+            KeymapPreset::Htop => vec![
+                (Action::SelectUp, &["up"]),
+                (Action::SelectDown, &["down"]),
+                (Action::PageUp, &["pageup"]),
+                (Action::PageDown, &["pagedown"]),
+                (Action::GoTop, &["home"]),
+                (Action::GoBottom, &["end"]),
+                (Action::ToggleExpand, &["enter", "space"]),
+                (Action::Kill, &["f9", "k"]),
+                (Action::Suspend, &["s"]),
+                (Action::Resume, &["r"]),
+                (Action::OpenFiles, &["l"]),
+                (Action::Inspect, &["e"]),
+                (Action::ForceRefresh, &["ctrl+l"]),
+                (Action::SortPid, &["N"]),
+                (Action::SortName, &["A"]),
+                (Action::SortCpu, &["P"]),
+                (Action::SortMemory, &["M"]),
+                (Action::CycleSort, &["f6", ">"]),
+                (Action::Search, &["f3", "/"]),
+                (Action::Find, &["F"]),
+                (Action::FindNext, &[";"]),
+                (Action::FindPrev, &[","]),
+                (Action::SelectFilter, &["f4", "\\"]),
+                (Action::ClearFilters, &["ctrl+u"]),
+                (Action::SelectView, &["v"]),
+                (Action::ShowProcesses, &["1"]),
+                (Action::ShowStats, &["2", "f2"]),
+                (Action::ShowHelp, &["f1", "h", "?"]),
+                (Action::CommandPalette, &[":"]),
+                (Action::ToggleTree, &["f5", "t"]),
+                (Action::ExpandAll, &["*"]),
+                (Action::CollapseAll, &["E"]),
+                (Action::SetInterval, &["i"]),
+                (Action::FasterRefresh, &["+"]),
+                (Action::SlowerRefresh, &["-"]),
+                (Action::ToggleAutoRefresh, &["z"]),
+                (Action::ReplayStepBack, &["left"]),
+                (Action::ReplayStepForward, &["right"]),
+                (Action::ReplaySeekBack, &["["]),
+                (Action::ReplaySeekForward, &["]"]),
+                (Action::Quit, &["f10", "q"]),
+                (Action::ForceQuit, &["ctrl+c"]),
+                (Action::Back, &["esc"]),
+            ],
+            KeymapPreset::Vim => vec![
+                (Action::SelectUp, &["k", "up"]),
+                (Action::SelectDown, &["j", "down"]),
+                (Action::PageUp, &["ctrl+u", "ctrl+b", "pageup"]),
+                (Action::PageDown, &["ctrl+d", "ctrl+f", "pagedown"]),
+                (Action::GoTop, &["g", "home"]),
+                (Action::GoBottom, &["G", "end"]),
+                (Action::ToggleExpand, &["o", "enter", "space"]),
+                (Action::Kill, &["D", "delete"]),
+                (Action::Suspend, &["s"]),
+                (Action::Resume, &["r"]),
+                (Action::OpenFiles, &["x"]),
+                (Action::Inspect, &["K"]),
+                (Action::ForceRefresh, &["ctrl+r"]),
+                (Action::SortPid, &["p"]),
+                (Action::SortName, &["a"]),
+                (Action::SortCpu, &["c"]),
+                (Action::SortMemory, &["m"]),
+                (Action::CycleSort, &["S"]),
+                (Action::Search, &["\\"]),
+                (Action::Find, &["/"]),
+                (Action::FindNext, &["n"]),
+                (Action::FindPrev, &["N"]),
+                (Action::SelectFilter, &["f"]),
+                (Action::ClearFilters, &["F"]),
+                (Action::SelectView, &["v"]),
+                (Action::ShowProcesses, &["1"]),
+                (Action::ShowStats, &["2"]),
+                (Action::ShowHelp, &["3", "?"]),
+                (Action::CommandPalette, &[":"]),
+                (Action::ToggleTree, &["T"]),
+                (Action::ExpandAll, &["O"]),
+                (Action::CollapseAll, &["C"]),
+                (Action::SetInterval, &["i"]),
+                (Action::FasterRefresh, &["+"]),
+                (Action::SlowerRefresh, &["-"]),
+                (Action::ToggleAutoRefresh, &["z"]),
+                (Action::ReplayStepBack, &["left"]),
+                (Action::ReplayStepForward, &["right"]),
+                (Action::ReplaySeekBack, &["["]),
+                (Action::ReplaySeekForward, &["]"]),
+                (Action::Quit, &["q", "Z"]),
+                (Action::ForceQuit, &["ctrl+c"]),
+                (Action::Back, &["esc"]),
+            ],
+        }
+    }
+}
+
+// Nel file di configurazione un'azione accetta un tasto singolo o una lista
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            KeyList::One(key) => vec![key.as_str()],
+            KeyList::Many(keys) => keys.iter().map(|k| k.as_str()).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct KeybindingsConfig {
+    pub preset: KeymapPreset,
+    #[serde(flatten)]
+    pub bindings: BTreeMap<String, KeyList>,
+}
+
+type KeyCombo = (KeyCode, KeyModifiers);
+
+pub struct Keymap {
+    bindings: HashMap<KeyCombo, Action>,
+    // Ordine di inserimento dei tasti per ogni azione, usato da help e footer
+    keys: HashMap<Action, Vec<KeyCombo>>,
+}
+
+impl Keymap {
+    pub fn from_config(config: &KeybindingsConfig) -> (Self, Vec<String>) {
+        let mut keymap = Keymap {
+            bindings: HashMap::new(),
+            keys: HashMap::new(),
+        };
+        let mut errors = Vec::new();
+
+        for (action, keys) in config.preset.bindings() {
+            for key in keys {
+                if let Some(combo) = parse_key_spec(key) {
+                    keymap.bind(combo, action);
+                }
+            }
+        }
+
+        // Un'azione ridefinita nel config sostituisce i tasti del preset
+        for (name, keys) in &config.bindings {
+            let Some(action) = Action::from_name(name) else {
+                errors.push(format!("Unknown action \"{}\" in [keybindings]", name));
+                continue;
+            };
+
+            if let Some(previous) = keymap.keys.remove(&action) {
+                for combo in previous {
+                    keymap.bindings.remove(&combo);
+                }
+            }
+
+            for key in keys.keys() {
+                match parse_key_spec(key) {
+                    Some(combo) => keymap.bind(combo, action),
+                    None => errors.push(format!("Invalid key \"{}\" for action {}", key, name)),
+                }
+            }
+        }
+
+        (keymap, errors)
+    }
+
+    fn bind(&mut self, combo: KeyCombo, action: Action) {
+        // Lo stesso tasto non può attivare due azioni: vince l'ultima definizione
+        if let Some(previous) = self.bindings.insert(combo, action)
+            && let Some(keys) = self.keys.get_mut(&previous)
+        {
+            keys.retain(|k| *k != combo);
+        }
+        self.keys.entry(action).or_default().push(combo);
+    }
+
+    pub fn action_for(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        self.bindings.get(&normalize(code, modifiers)).copied()
+    }
+
+    pub fn keys_for(&self, action: Action) -> Vec<String> {
+        self.keys
+            .get(&action)
+            .map(|keys| keys.iter().map(|&(code, mods)| format_key(code, mods)).collect())
+            .unwrap_or_default()
+    }
+
+    // Primo tasto associato all'azione, per i suggerimenti nel footer
+    pub fn hint(&self, action: Action) -> String {
+        self.keys_for(action)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+// Per i caratteri il maiuscolo è già nel carattere stesso, SHIFT viene ignorato;
+// con Ctrl la lettera è sempre minuscola
+fn normalize(code: KeyCode, modifiers: KeyModifiers) -> KeyCombo {
+    match code {
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+            (KeyCode::Char(c.to_ascii_lowercase()), modifiers - KeyModifiers::SHIFT)
+        }
+        KeyCode::Char(_) => (code, modifiers - KeyModifiers::SHIFT),
+        _ => (code, modifiers),
+    }
+}
+
+// Formato: "q", "ctrl+r", "shift+tab", "shift+k" (come "K"), "pagedown", "f5", ...
+pub fn parse_key_spec(spec: &str) -> Option<KeyCombo> {
+    let spec = spec.trim();
+    let mut modifiers = KeyModifiers::NONE;
+    let mut key = spec;
+
+    // Un singolo carattere può essere anche "+" o "-"
+    while key.chars().count() > 1 {
+        let Some((prefix, rest)) = key.split_once('+') else {
+            break;
+        };
+        match prefix.to_lowercase().as_str() {
+            "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+            "alt" => modifiers |= KeyModifiers::ALT,
+            "shift" => modifiers |= KeyModifiers::SHIFT,
+            _ => return None,
+        }
+        key = rest;
+    }
+
+    let code = match key.to_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
+        "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        lower => {
+            if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                KeyCode::F(n)
+            } else {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    // Shift con una lettera è la maiuscola; per gli altri caratteri
+                    // il risultato dipende dal layout della tastiera
+                    (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                        if !c.is_ascii_alphabetic() {
+                            return None;
+                        }
+                        KeyCode::Char(c.to_ascii_uppercase())
+                    }
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        }
+    };
+
+    Some(normalize(code, modifiers))
+}
+
+pub fn format_key(code: KeyCode, modifiers: KeyModifiers) -> String {
+    let key = match code {
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "Shift+Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::Insert => "Ins".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => c.to_ascii_uppercase().to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        other => format!("{:?}", other),
+    };
+
+    let mut prefix = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("Ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("Alt+");
+    }
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        prefix.push_str("Shift+");
+    }
+    format!("{}{}", prefix, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use super::{Action, KeyList, KeybindingsConfig, Keymap, KeymapPreset, parse_key_spec};
+
+    fn config(preset: KeymapPreset, bindings: &[(&str, &[&str])]) -> KeybindingsConfig {
+        KeybindingsConfig {
+            preset,
+            bindings: bindings
+                .iter()
+                .map(|(name, keys)| (name.to_string(), KeyList::Many(keys.iter().map(|k| k.to_string()).collect())))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn key_specs() {
+        let char = |c| Some((KeyCode::Char(c), KeyModifiers::NONE));
+        assert_eq!(parse_key_spec("q"), char('q'));
+        assert_eq!(parse_key_spec("K"), char('K'));
+        assert_eq!(parse_key_spec("shift+k"), char('K'));
+        assert_eq!(parse_key_spec("Shift+K"), char('K'));
+        assert_eq!(parse_key_spec("+"), char('+'));
+        assert_eq!(parse_key_spec("space"), char(' '));
+        assert_eq!(parse_key_spec("ctrl+R"), Some((KeyCode::Char('r'), KeyModifiers::CONTROL)));
+        assert_eq!(parse_key_spec("alt+x"), Some((KeyCode::Char('x'), KeyModifiers::ALT)));
+        assert_eq!(parse_key_spec("shift+tab"), Some((KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert_eq!(parse_key_spec("F5"), Some((KeyCode::F(5), KeyModifiers::NONE)));
+        assert_eq!(parse_key_spec(" pagedown "), Some((KeyCode::PageDown, KeyModifiers::NONE)));
+
+        for invalid in ["", "shift+1", "shift+/", "hyper+k", "ctrl+", "abc", "ctrl+abc"] {
+            assert_eq!(parse_key_spec(invalid), None, "{:?} was accepted", invalid);
+        }
+    }
+
+    #[test]
+    fn presets_parse_without_overlaps() {
+        for preset in [KeymapPreset::Default, KeymapPreset::Htop, KeymapPreset::Vim] {
+            let mut seen = HashMap::new();
+            for (action, keys) in preset.bindings() {
+                for key in keys {
+                    let combo = parse_key_spec(key).unwrap_or_else(|| panic!("invalid key {:?}", key));
+                    if let Some(other) = seen.insert(combo, action) {
+                        panic!("{:?} is bound to both {:?} and {:?}", key, other, action);
+                    }
+                }
+            }
+
+            let (keymap, errors) = Keymap::from_config(&config(preset, &[]));
+            assert!(errors.is_empty());
+            for action in [Action::Quit, Action::ForceQuit, Action::ShowHelp, Action::Search, Action::Kill] {
+                assert_ne!(keymap.hint(action), "-");
+            }
+        }
+
+        // Stesso carattere, modificatori diversi: azioni diverse
+        let (keymap, _) = Keymap::from_config(&KeybindingsConfig::default());
+        assert_eq!(keymap.action_for(KeyCode::Char('c'), KeyModifiers::NONE), Some(Action::SortCpu));
+        assert_eq!(keymap.action_for(KeyCode::Char('c'), KeyModifiers::CONTROL), Some(Action::ForceQuit));
+        assert_eq!(keymap.action_for(KeyCode::Char('r'), KeyModifiers::NONE), Some(Action::Resume));
+        assert_eq!(keymap.action_for(KeyCode::Char('r'), KeyModifiers::CONTROL), Some(Action::ForceRefresh));
+        // Il terminale manda la maiuscola con SHIFT
+        assert_eq!(keymap.action_for(KeyCode::Char('K'), KeyModifiers::SHIFT), Some(Action::Kill));
+    }
+
+    #[test]
+    fn config_bindings_replace_and_steal_keys() {
+        let (keymap, errors) = Keymap::from_config(&config(
+            KeymapPreset::Default,
+            &[("sort_memory", &["shift+c", "c"]), ("kill", &["ctrl+k"])],
+        ));
+        assert!(errors.is_empty());
+
+        // "c" passa a sort_memory e sparisce da sort_cpu, "m" non è più legato
+        assert_eq!(keymap.action_for(KeyCode::Char('c'), KeyModifiers::NONE), Some(Action::SortMemory));
+        assert_eq!(keymap.action_for(KeyCode::Char('C'), KeyModifiers::SHIFT), Some(Action::SortMemory));
+        assert_eq!(keymap.action_for(KeyCode::Char('m'), KeyModifiers::NONE), None);
+        assert!(keymap.keys_for(Action::SortCpu).is_empty());
+        assert_eq!(keymap.keys_for(Action::SortMemory), ["C", "c"]);
+        assert_eq!(keymap.keys_for(Action::Kill), ["Ctrl+K"]);
+
+        let (_, errors) = Keymap::from_config(&config(
+            KeymapPreset::Vim,
+            &[("explode", &["x"]), ("kill", &["shift+2", "ctrl+k"])],
+        ));
+        assert_eq!(errors, [
+            "Unknown action \"explode\" in [keybindings]",
+            "Invalid key \"shift+2\" for action kill",
+        ]);
+    }
+}
@@ -1,82 +1,128 @@
-use crate::App;
+use crate::{App, helpers::config::ConfirmPolicy};
 use anyhow::Result;
+use sysinfo::Pid;
+
+// Controllo se è un processo critico di sistema (PID < 10)
+pub fn is_critical_process(pid: Pid, name: &str) -> bool {
+    let name = name.to_lowercase();
+    pid.as_u32() < 10 || name.contains("systemd") || name.contains("init") || name.contains("kernel")
+}
 
 impl App {
+    // In replay i PID appartengono alla registrazione, non a questa macchina
+    pub fn refuse_in_replay(&mut self) -> bool {
+        if self.replay.is_none() {
+            return false;
+        }
+        self.errors.push((
+            "Replay".to_string(),
+            "Processes can't be managed while replaying a recording".to_string(),
+        ));
+        self.input_mode = crate::InputMode::Error;
+        true
+    }
+
     pub fn initiate_kill(&mut self) -> Result<()> {
-        if let Some(selected) = self.table_state.selected() {
-            if let Some(node) = self.get_process_at_flat_index(selected) {
-                let pid = node.info.pid;
-                
-                // Controllo se è un processo critico di sistema (PID < 10)
-                let is_critical = pid.as_u32() < 10
-                    || node.info.name.to_lowercase().contains("systemd")
-                    || node.info.name.to_lowercase().contains("init")
-                    || node.info.name.to_lowercase().contains("kernel");
-                
-                if is_critical {
-                    self.pending_kill_pid = Some(pid);
-                    self.input_mode = crate::InputMode::ConfirmKill;
-                } else {
-                    if let Some(process) = self.system.process(pid) {
-                        if !process.kill() {
-                            self.errors.push((
-                                "Failed to kill process".to_string(),
-                                format!("PID {} ({})", pid, node.info.name),
-                            ));
-                            self.input_mode = crate::InputMode::Error
-                        }
-                    } else {
-                        self.errors.push((
-                            "Process not found".to_string(),
-                            format!("PID {} no longer exists", pid),
-                        ));
-                        self.input_mode = crate::InputMode::Error
-                    }
-                    self.force_refresh();
+        if let Some(selected) = self.table_state.selected()
+            && let Some(node) = self.get_process_at_flat_index(selected)
+        {
+            let pid = node.info.pid;
+
+            let is_critical = is_critical_process(pid, &node.info.name);
+
+            let needs_confirm = match self.preferences.confirm.kill {
+                ConfirmPolicy::Always => true,
+                ConfirmPolicy::Critical => is_critical,
+                ConfirmPolicy::Never => false,
+            };
+
+            if needs_confirm {
+                self.pending_kill_pid = Some(pid);
+                self.input_mode = crate::InputMode::ConfirmKill;
+            } else {
+                let exists = self.snapshot.process(pid.as_u32()).is_some();
+                let name = node.info.name.clone();
+                if !exists {
+                    self.errors.push((
+                        "Process not found".to_string(),
+                        format!("PID {} no longer exists", pid),
+                    ));
+                    self.input_mode = crate::InputMode::Error
+                } else if !self.control.kill(pid.as_u32()) {
+                    self.errors.push((
+                        "Failed to kill process".to_string(),
+                        format!("PID {} ({})", pid, name),
+                    ));
+                    self.input_mode = crate::InputMode::Error
                 }
+                self.force_refresh();
             }
         }
         Ok(())
     }
 
     pub fn suspend_process(&mut self) -> Result<()> {
-        if let Some(selected) = self.table_state.selected() {
-            if let Some(node) = self.get_process_at_flat_index(selected) {
-                let pid = node.info.pid.as_u32() as i32;
-                let result = unsafe { libc::kill(pid, libc::SIGSTOP) };
-                
-                if result == -1 {
-                    let errno = unsafe { *libc::__errno_location() };
-                    self.errors.push((
-                        "Failed to suspend process".to_string(),
-                        format!("PID {}: errno {}", pid, errno),
-                    ));
-                } else {
-                    self.force_refresh();
-                }
+        if let Some(selected) = self.table_state.selected()
+            && let Some(node) = self.get_process_at_flat_index(selected)
+        {
+            let pid = node.info.pid.as_u32();
+            let result = self.control.signal(pid, libc::SIGSTOP);
+
+            if let Err(err) = result {
+                self.errors.push((
+                    "Failed to suspend process".to_string(),
+                    format!("PID {}: {}", pid, err),
+                ));
+                self.input_mode = crate::InputMode::Error
+            } else {
+                self.force_refresh();
             }
-        }        
+        }
         Ok(())
     }
 
     pub fn resume_process(&mut self) -> Result<()> {
-        if let Some(selected) = self.table_state.selected() {
-            if let Some(node) = self.get_process_at_flat_index(selected) {
-                let pid = node.info.pid.as_u32() as i32;
-                let result = unsafe { libc::kill(pid, libc::SIGCONT) };
-                
-                if result == -1 {
-                    let errno = unsafe { *libc::__errno_location() };
-                    self.errors.push((
-                        "Failed to resume process".to_string(),
-                        format!("PID {}: errno {}", pid, errno),
-                    ));
-                    self.input_mode = crate::InputMode::Error
-                } else {
-                    self.force_refresh();
-                }
+        if let Some(selected) = self.table_state.selected()
+            && let Some(node) = self.get_process_at_flat_index(selected)
+        {
+            let pid = node.info.pid.as_u32();
+            let result = self.control.signal(pid, libc::SIGCONT);
+
+            if let Err(err) = result {
+                self.errors.push((
+                    "Failed to resume process".to_string(),
+                    format!("PID {}: {}", pid, err),
+                ));
+                self.input_mode = crate::InputMode::Error
+            } else {
+                self.force_refresh();
             }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sysinfo::Pid;
+
+    use crate::{InputMode, helpers::fake};
+
+    #[test]
+    fn failed_signals_are_shown() {
+        let mut app = fake::app(vec![fake::machine()]);
+        assert!(app.select_pid(Pid::from_u32(400)));
+
+        // Il processo termina prima del prossimo campione
+        app.control.kill(400);
+        app.suspend_process().unwrap();
+        assert!(app.input_mode == InputMode::Error);
+        assert_eq!(app.errors.last().unwrap().0, "Failed to suspend process");
+
+        app.errors.clear();
+        app.input_mode = InputMode::None;
+        app.resume_process().unwrap();
+        assert!(app.input_mode == InputMode::Error);
+        assert_eq!(app.errors.last().unwrap().0, "Failed to resume process");
+    }
+}
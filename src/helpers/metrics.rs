@@ -0,0 +1,232 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Result, anyhow};
+use tiny_http::{Header, Response, Server};
+
+use rtop::snapshot::ProcessSample;
+
+use crate::App;
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+// Etichette: \, " e a capo vanno escapati
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn family(out: &mut String, name: &str, kind: &str, unit: Option<&str>, help: &str) {
+    writeln!(out, "# TYPE {} {}", name, kind).ok();
+    if let Some(unit) = unit {
+        writeln!(out, "# UNIT {} {}", name, unit).ok();
+    }
+    writeln!(out, "# HELP {} {}", name, help).ok();
+}
+
+// I processi esportati uno per uno sono limitati ai più attivi, per non
+// generare una serie nuova per ogni processo di breve durata
+fn exported_processes(processes: &[ProcessSample], limit: usize) -> Vec<&ProcessSample> {
+    let mut top: Vec<&ProcessSample> = processes.iter().collect();
+    top.sort_by(|a, b| {
+        b.cpu_usage
+            .partial_cmp(&a.cpu_usage)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.memory.cmp(&a.memory))
+    });
+    top.truncate(limit);
+    top
+}
+
+impl App {
+    // Metriche dell'ultimo campione in formato OpenMetrics
+    pub fn render_metrics(&self) -> String {
+        let snapshot = &self.snapshot;
+        let mut out = String::new();
+
+        family(&mut out, "rtop_cpu_usage_ratio", "gauge", Some("ratio"), "CPU usage per core.");
+        for (core, usage) in snapshot.cpu_usage.iter().enumerate() {
+            writeln!(out, "rtop_cpu_usage_ratio{{cpu=\"{}\"}} {}", core, usage / 100.0).ok();
+        }
+
+        family(&mut out, "rtop_cpu_frequency_hertz", "gauge", Some("hertz"), "Current CPU frequency per core.");
+        for (core, freq) in snapshot.cpu_freq.iter().enumerate() {
+            writeln!(out, "rtop_cpu_frequency_hertz{{cpu=\"{}\"}} {}", core, freq.current_mhz * 1_000_000).ok();
+        }
+
+        family(&mut out, "rtop_memory_used_bytes", "gauge", Some("bytes"), "Used memory.");
+        writeln!(out, "rtop_memory_used_bytes {}", snapshot.memory_used).ok();
+        family(&mut out, "rtop_memory_total_bytes", "gauge", Some("bytes"), "Total memory.");
+        writeln!(out, "rtop_memory_total_bytes {}", snapshot.memory_total).ok();
+        family(&mut out, "rtop_swap_used_bytes", "gauge", Some("bytes"), "Used swap.");
+        writeln!(out, "rtop_swap_used_bytes {}", snapshot.swap_used).ok();
+        family(&mut out, "rtop_swap_total_bytes", "gauge", Some("bytes"), "Total swap.");
+        writeln!(out, "rtop_swap_total_bytes {}", snapshot.swap_total).ok();
+
+        family(&mut out, "rtop_network_received_bytes", "counter", Some("bytes"), "Bytes received per interface.");
+        for net in &snapshot.networks {
+            writeln!(
+                out,
+                "rtop_network_received_bytes_total{{interface=\"{}\"}} {}",
+                escape_label(&net.name),
+                net.total_received
+            )
+            .ok();
+        }
+        family(&mut out, "rtop_network_transmitted_bytes", "counter", Some("bytes"), "Bytes transmitted per interface.");
+        for net in &snapshot.networks {
+            writeln!(
+                out,
+                "rtop_network_transmitted_bytes_total{{interface=\"{}\"}} {}",
+                escape_label(&net.name),
+                net.total_transmitted
+            )
+            .ok();
+        }
+
+        if let Some(pressure) = snapshot.pressure {
+            family(
+                &mut out,
+                "rtop_pressure_some_avg10_ratio",
+                "gauge",
+                Some("ratio"),
+                "Share of the last 10 seconds in which some tasks were stalled.",
+            );
+            for (resource, stats) in [("cpu", pressure.cpu), ("memory", pressure.memory), ("io", pressure.io)] {
+                writeln!(
+                    out,
+                    "rtop_pressure_some_avg10_ratio{{resource=\"{}\"}} {}",
+                    resource,
+                    stats.some.avg10 / 100.0
+                )
+                .ok();
+            }
+        }
+
+        let mut states: BTreeMap<&str, usize> = BTreeMap::new();
+        for process in &snapshot.processes {
+            *states.entry(process.status.as_str()).or_default() += 1;
+        }
+        family(&mut out, "rtop_processes", "gauge", None, "Number of processes by state.");
+        for (state, count) in states {
+            writeln!(out, "rtop_processes{{state=\"{}\"}} {}", escape_label(state), count).ok();
+        }
+
+        let top = exported_processes(&snapshot.processes, self.preferences.metrics.max_processes);
+        let labels: Vec<String> = top
+            .iter()
+            .map(|p| format!("pid=\"{}\",name=\"{}\"", p.pid, escape_label(&p.name)))
+            .collect();
+
+        family(&mut out, "rtop_process_cpu_usage_ratio", "gauge", Some("ratio"), "CPU usage of the most active processes.");
+        for (process, labels) in top.iter().zip(&labels) {
+            writeln!(out, "rtop_process_cpu_usage_ratio{{{}}} {}", labels, process.cpu_usage / 100.0).ok();
+        }
+        family(&mut out, "rtop_process_memory_bytes", "gauge", Some("bytes"), "Resident memory of the most active processes.");
+        for (process, labels) in top.iter().zip(&labels) {
+            writeln!(out, "rtop_process_memory_bytes{{{}}} {}", labels, process.memory).ok();
+        }
+        family(&mut out, "rtop_process_disk_read_bytes", "counter", Some("bytes"), "Bytes read from disk by the most active processes.");
+        for (process, labels) in top.iter().zip(&labels) {
+            writeln!(out, "rtop_process_disk_read_bytes_total{{{}}} {}", labels, process.read_bytes).ok();
+        }
+        family(&mut out, "rtop_process_disk_written_bytes", "counter", Some("bytes"), "Bytes written to disk by the most active processes.");
+        for (process, labels) in top.iter().zip(&labels) {
+            writeln!(out, "rtop_process_disk_written_bytes_total{{{}}} {}", labels, process.written_bytes).ok();
+        }
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+// Serve /metrics leggendo l'ultimo campione raccolto dal thread di refresh
+pub fn spawn_metrics_server(addr: &str, app: Arc<Mutex<App>>) -> Result<()> {
+    let server = Server::http(addr).map_err(|err| anyhow!("Cannot listen on {}: {}", addr, err))?;
+    let content_type = Header::from_bytes("Content-Type", CONTENT_TYPE).expect("valid header");
+
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = match request.url() {
+                "/metrics" | "/" => {
+                    let body = app.lock().unwrap().render_metrics();
+                    Response::from_string(body).with_header(content_type.clone())
+                }
+                _ => Response::from_string("Not found\n").with_status_code(404),
+            };
+            request.respond(response).ok();
+        }
+    });
+    Ok(())
+}
+
+// Solo exporter, senza interfaccia: i campioni arrivano dal collector, così
+// le richieste HTTP non aspettano la scansione dei processi
+pub fn run_headless(app: Arc<Mutex<App>>) -> Result<()> {
+    {
+        let mut app = app.lock().unwrap();
+        // auto_refresh = false nel config non deve fermare l'exporter
+        app.refresh = true;
+        app.start_collector();
+    }
+    loop {
+        app.lock().unwrap().tick();
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helpers::fake;
+
+    #[test]
+    fn openmetrics_output() {
+        let mut machine = fake::machine();
+        machine.networks[0].name = "we\"ird\\eth\n0".to_string();
+        machine.processes[4].name = "fire\"fox".to_string();
+        let mut app = fake::app(vec![machine]);
+        app.preferences.metrics.max_processes = 2;
+        let out = app.render_metrics();
+        let lines: Vec<&str> = out.lines().collect();
+
+        // Ogni famiglia ha TYPE, UNIT se serve e HELP prima dei valori
+        assert!(lines.contains(&"# TYPE rtop_memory_used_bytes gauge"));
+        assert!(lines.contains(&"# UNIT rtop_memory_used_bytes bytes"));
+        assert!(lines.contains(&"# TYPE rtop_processes gauge"));
+        assert!(!out.contains("# UNIT rtop_processes "));
+        assert!(lines.contains(&"rtop_cpu_usage_ratio{cpu=\"3\"} 0.4"));
+        assert!(lines.contains(&"rtop_memory_total_bytes 17179869184"));
+
+        // I counter dichiarano il nome base, i campioni hanno il suffisso _total
+        assert!(lines.contains(&"# TYPE rtop_network_received_bytes counter"));
+        assert!(lines.contains(&"rtop_network_received_bytes_total{interface=\"we\\\"ird\\\\eth\\n0\"} 1048576"));
+        assert!(lines.contains(&"# TYPE rtop_process_disk_read_bytes counter"));
+        let counters = lines
+            .iter()
+            .filter(|line| line.starts_with("rtop_network_") || line.starts_with("rtop_process_disk_"));
+        for line in counters {
+            assert!(line.split('{').next().unwrap().ends_with("_total"), "{}", line);
+        }
+
+        // Solo i processi più attivi hanno le proprie serie
+        let memory: Vec<&&str> = lines
+            .iter()
+            .filter(|line| line.starts_with("rtop_process_memory_bytes{"))
+            .collect();
+        assert_eq!(memory.len(), 2);
+        assert!(memory[0].starts_with("rtop_process_memory_bytes{pid=\"300\",name=\"cargo\"}"));
+        assert!(memory[1].starts_with("rtop_process_memory_bytes{pid=\"400\",name=\"fire\\\"fox\"}"));
+        assert!(!out.contains("name=\"bash\""));
+
+        assert_eq!(lines.last(), Some(&"# EOF"));
+        assert_eq!(out.matches("# EOF").count(), 1);
+        assert!(out.ends_with("# EOF\n"));
+    }
+}
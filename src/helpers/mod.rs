@@ -1,13 +1,29 @@
 pub mod memory;
 pub mod network;
 pub mod process_tree;
-pub mod sorting;
 pub mod mouse;
 pub mod manage_process;
 pub mod keyboard;
-pub mod build_node;
 pub mod utils;
 pub mod ui;
 pub mod saving;
 pub mod refresh;
-pub mod process_files;
\ No newline at end of file
+pub mod process_files;
+pub mod pressure;
+pub mod config;
+pub mod keymap;
+pub mod cli;
+pub mod batch;
+pub mod snapshot;
+pub mod record;
+pub mod metrics;
+pub mod alerts;
+pub mod views;
+pub mod remote;
+pub mod help;
+pub mod context_menu;
+pub mod palette;
+pub mod find;
+pub mod inspector;
+#[cfg(test)]
+pub mod fake;
\ No newline at end of file
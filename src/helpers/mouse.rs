@@ -1,24 +1,46 @@
-use crate::{App, Page, SortColumn};
-use crossterm::event::MouseEventKind;
+use crate::{
+    App, Column, InputMode, Page, SortColumn,
+    gui::processes::column_widths,
+    helpers::{context_menu::MenuItem, keyboard::perform_action},
+};
+use anyhow::Result;
+use crossterm::event::{MouseButton, MouseEventKind};
 use std::time::{Duration, Instant};
 
-pub fn handle_mouse(app: &mut App, kind: MouseEventKind, x: u16, y: u16) {
+// Bordo trascinato con il mouse
+#[derive(PartialEq, Clone, Copy)]
+pub enum Drag {
+    Split,
+    Column(usize),
+}
+
+pub fn handle_mouse(app: &mut App, kind: MouseEventKind, x: u16, y: u16) -> Result<bool> {
     match kind {
+        MouseEventKind::Down(MouseButton::Right) => {
+            app.handle_right_click(x, y);
+        }
         MouseEventKind::Down(_) => {
-            app.handle_mouse_click(x, y);
+            return app.handle_mouse_click(x, y);
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            app.handle_drag(x);
+        }
+        MouseEventKind::Up(_) => {
+            app.drag = None;
         }
         MouseEventKind::ScrollDown => {
-            app.select_next();
+            app.handle_scroll(x, y, 1);
         }
         MouseEventKind::ScrollUp => {
-            app.select_prev();
+            app.handle_scroll(x, y, -1);
         }
         _ => {}
     }
+    Ok(false)
 }
 
 impl App {
-    fn handle_mouse_click(&mut self, x: u16, y: u16) -> bool {
+    fn handle_mouse_click(&mut self, x: u16, y: u16) -> Result<bool> {
         let now = Instant::now();
         let is_double_click = if let Some((last_time, last_x, last_y)) = self.last_click {
             now.duration_since(last_time) < Duration::from_millis(500)
@@ -30,101 +52,352 @@ impl App {
 
         self.last_click = Some((now, x, y));
 
+        // Con il menu aperto un click sceglie una voce, fuori lo chiude
+        if self.input_mode == InputMode::ContextMenu {
+            if let Some(index) = self.menu_item_at(x, y) {
+                if let Some(menu) = &mut self.context_menu {
+                    menu.selected = index;
+                }
+                self.run_menu_item(MenuItem::ALL[index])?;
+            } else {
+                self.close_context_menu();
+            }
+            return Ok(false);
+        }
+        if self.input_mode != InputMode::None {
+            return Ok(false);
+        }
+
+        // Le voci del footer eseguono la stessa azione del loro tasto
+        if self.footer_area.contains((x, y).into()) {
+            let target = self
+                .footer_targets
+                .iter()
+                .find(|&&(start, end, _)| (start..end).contains(&x))
+                .map(|&(_, _, action)| action);
+            if let Some(action) = target {
+                return perform_action(self, action);
+            }
+            return Ok(false);
+        }
+
+        if self.page == Page::Inspector {
+            let tab = self
+                .inspector
+                .tab_targets
+                .iter()
+                .find(|(area, _)| area.contains((x, y).into()))
+                .map(|&(_, tab)| tab);
+            if let Some(tab) = tab {
+                self.select_inspector_tab(tab);
+            }
+            return Ok(false);
+        }
+
+        if self.page != Page::Processes {
+            return Ok(false);
+        }
+
+        // Il bordo tra tabella e dettagli
+        if self.detail_area.width > 0
+            && (self.detail_area.x.saturating_sub(1)..=self.detail_area.x).contains(&x)
+            && (self.detail_area.y..self.detail_area.bottom()).contains(&y)
+        {
+            self.drag = Some(Drag::Split);
+            return Ok(false);
+        }
+
         // Controllo click sull header
-        if self.page == Page::Processes && self.header_area.contains((x, y).into()) {
+        if self.header_area.contains((x, y).into()) {
             let header_y = self.header_area.y + 1;
             if y == header_y {
-                let _table_width = self.header_area.width.saturating_sub(2);
-                let relative_x = x.saturating_sub(self.header_area.x + 1);
-
-                // Calculate dynamic column widths (same logic as in draw_processes)
-                let available_width = self.table_area.width.saturating_sub(4);
-                
-                let max_line_num = self.flatten_processes().len();
-                let line_num_width = max_line_num.to_string().len().max(3) as u16;
-                
-                let pid_width = 10u16;
-                let cpu_width = 12u16;
-                let mem_width = 15u16;
-                let fixed_total = line_num_width + 1 + pid_width + cpu_width + mem_width;
-                
-                let name_width = if available_width > fixed_total {
-                    available_width.saturating_sub(fixed_total).max(10)
-                } else {
-                    10
-                };
+                let bounds = self.column_bounds();
 
-                let mut col_edges = Vec::new();
-                let mut start = 0u16;
-                
-                // Numero riga
-                let line_col_width = line_num_width + 1;
-                col_edges.push((start, start + line_col_width));
-                start += line_col_width;
-                
-                // PID
-                col_edges.push((start, start + pid_width));
-                start += pid_width;
-                
-                // Name
-                col_edges.push((start, start + name_width));
-                start += name_width;
-                
-                // CPU
-                col_edges.push((start, start + cpu_width));
-                start += cpu_width;
-                
-                // Memory
-                col_edges.push((start, start + mem_width));
-
-                let new_column = if relative_x < col_edges[0].1 {
-                    None
-                } else if relative_x < col_edges[1].1 {
-                    Some(SortColumn::Pid)
-                } else if relative_x < col_edges[2].1 {
-                    Some(SortColumn::Name)
-                } else if relative_x < col_edges[3].1 {
-                    Some(SortColumn::Cpu)
-                } else if relative_x < col_edges[4].1 {
-                    Some(SortColumn::Memory)
-                } else {
-                    None
-                };
+                // Lo spazio dopo una colonna è il suo bordo destro
+                if let Some(index) = bounds.iter().position(|&(_, _, end)| x == end) {
+                    self.drag = Some(Drag::Column(index));
+                    return Ok(false);
+                }
 
-                if let Some(col) = new_column {
-                    if is_double_click {
-                        if self.sort_column == col {
-                            self.reverse_sort = !self.reverse_sort;
-                        } else {
-                            self.sort_column = col;
-                            self.reverse_sort = matches!(col, SortColumn::Cpu | SortColumn::Memory);
-                        }
-                        if self.refresh {self.force_refresh()}
+                let new_column = bounds
+                    .iter()
+                    .find(|&&(_, start, end)| (start..end).contains(&x))
+                    .and_then(|(column, _, _)| column.sort_column());
+
+                if let Some(col) = new_column
+                    && is_double_click
+                {
+                    if self.sort_column == col {
+                        self.reverse_sort = !self.reverse_sort;
+                    } else {
+                        self.sort_column = col;
+                        self.reverse_sort = matches!(col, SortColumn::Cpu | SortColumn::Memory);
                     }
+                    self.reapply_filters();
                 }
 
-                return true;
+                return Ok(false);
             }
         }
 
-        if self.page == Page::Processes && self.table_area.contains((x, y).into()) {
-            let row_offset = 3;
-            if y > self.table_area.y + row_offset {
-                let clicked_row = (y - self.table_area.y - row_offset + 1) as usize;
-                let actual_index = self.viewport_offset + clicked_row;
-                let flat = self.flatten_processes();
+        if let Some(index) = self.row_at(x, y) {
+            self.table_state.select(Some(index));
 
-                if actual_index < flat.len() {
-                    self.table_state.select(Some(actual_index));
+            if is_double_click {
+                self.toggle_expand();
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn handle_right_click(&mut self, x: u16, y: u16) {
+        if self.input_mode == InputMode::ContextMenu {
+            self.close_context_menu();
+        }
+        if self.input_mode != InputMode::None || self.page != Page::Processes {
+            return;
+        }
+        let Some(index) = self.row_at(x, y) else {
+            return;
+        };
+        self.table_state.select(Some(index));
+        if self.refuse_in_replay() {
+            return;
+        }
+        if let Some(node) = self.get_process_at_flat_index(index) {
+            let pid = node.info.pid;
+            self.open_context_menu(pid, x, y);
+        }
+    }
 
-                    if is_double_click {
-                        self.toggle_expand();
-                        return true;
+    fn handle_drag(&mut self, x: u16) {
+        match self.drag {
+            Some(Drag::Split) => {
+                let page = self.table_area.union(self.detail_area);
+                if page.width == 0 {
+                    return;
+                }
+                let detail = page.right().saturating_sub(x) as u32 * 100 / page.width as u32;
+                self.preferences.processes.detail_width = (detail as u16).clamp(10, 70);
+            }
+            Some(Drag::Column(index)) => {
+                let bounds = self.column_bounds();
+                let Some(&(column, start, end)) = bounds.get(index) else {
+                    return;
+                };
+                let widths = &mut self.preferences.processes.column_widths;
+                if column == Column::Name {
+                    // Name prende lo spazio rimasto, quindi si allarga o
+                    // stringe la colonna successiva
+                    if let Some(&(next, next_start, next_end)) = bounds.get(index + 1) {
+                        let width = (next_end as i32 - next_start as i32 + end as i32 - x as i32).clamp(4, 60);
+                        widths.insert(next, width as u16);
                     }
+                } else {
+                    widths.insert(column, x.saturating_sub(start).clamp(4, 60));
                 }
             }
+            None => {}
         }
+    }
 
-        false
+    fn handle_scroll(&mut self, x: u16, y: u16, delta: isize) {
+        match self.input_mode {
+            InputMode::ContextMenu => self.move_menu_selection(delta),
+            InputMode::CommandPalette => self.move_palette_selection(delta),
+            InputMode::SelectView => {
+                let last = self.view_names().len().saturating_sub(1);
+                self.view_selected = self.view_selected.saturating_add_signed(delta).min(last);
+            }
+            InputMode::None => {
+                if self.page == Page::Help {
+                    self.scroll_help(delta * 3);
+                } else if self.page == Page::Inspector {
+                    self.scroll_inspector(delta * 3);
+                } else if self.page == Page::Processes && self.detail_area.contains((x, y).into()) {
+                    self.detail_scroll = self.detail_scroll.saturating_add_signed(delta as i16);
+                } else if delta > 0 {
+                    self.select_next();
+                } else {
+                    self.select_prev();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Riga della tabella sotto il cursore, sotto bordo e intestazione
+    fn row_at(&mut self, x: u16, y: u16) -> Option<usize> {
+        if !self.table_area.contains((x, y).into()) {
+            return None;
+        }
+        let first_row = self.table_area.y + 2;
+        if y < first_row || y + 1 >= self.table_area.bottom() {
+            return None;
+        }
+        let index = self.viewport_offset + (y - first_row) as usize;
+        (index < self.flatten_processes().len()).then_some(index)
+    }
+
+    // Inizio e fine sullo schermo di ogni colonna, separate da uno spazio
+    fn column_bounds(&mut self) -> Vec<(Column, u16, u16)> {
+        let line_num_width = self.flatten_processes().len().to_string().len().max(3) as u16;
+        let available_width = self.table_area.width.saturating_sub(4);
+
+        let mut start = self.table_area.x + 1 + line_num_width + 2;
+        column_widths(self, available_width, line_num_width)
+            .into_iter()
+            .map(|(column, width)| {
+                let bounds = (column, start, start + width);
+                start += width + 1;
+                bounds
+            })
+            .collect()
+    }
+
+    fn menu_item_at(&self, x: u16, y: u16) -> Option<usize> {
+        let area = self.context_menu.as_ref()?.area;
+        if !area.contains((x, y).into()) || y == area.y {
+            return None;
+        }
+        let index = (y - area.y - 1) as usize;
+        (index < MenuItem::ALL.len()).then_some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind};
+
+    use super::handle_mouse;
+    use crate::{
+        App, Column, InputMode, Page,
+        helpers::{fake, keyboard::handle_key_event, keymap::Action, ui::ui},
+    };
+
+    fn click(app: &mut App, button: MouseButton, x: u16, y: u16) -> bool {
+        handle_mouse(app, MouseEventKind::Down(button), x, y).unwrap()
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        handle_key_event(app, code, KeyModifiers::NONE).unwrap();
+    }
+
+    fn draw(app: &mut App) {
+        fake::render(120, 20, |f| ui(f, app));
     }
-}
\ No newline at end of file
+
+    // Ordinati per CPU: cargo, firefox, bash, init, sshd
+    fn flat_app() -> (App, fake::ControlLog) {
+        let (mut app, log) = fake::app_with_log(vec![fake::machine()]);
+        app.tree_mode = false;
+        app.force_refresh();
+        draw(&mut app);
+        (app, log)
+    }
+
+    #[test]
+    fn context_menu_on_a_row() {
+        let (mut app, (signals, niced)) = flat_app();
+        let row = app.table_area.y + 3;
+
+        click(&mut app, MouseButton::Right, 20, row);
+        assert!(app.input_mode == InputMode::ContextMenu);
+        assert_eq!(app.table_state.selected(), Some(1));
+        let area = app.context_menu.as_ref().unwrap().area;
+
+        // Terza voce: Suspend
+        click(&mut app, MouseButton::Left, area.x + 2, area.y + 3);
+        assert!(app.input_mode == InputMode::None);
+        assert_eq!(signals.lock().unwrap().last(), Some(&(400, libc::SIGSTOP)));
+
+        click(&mut app, MouseButton::Right, 20, row);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.input_mode == InputMode::SendSignal);
+        for c in "hup".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(signals.lock().unwrap().last(), Some(&(400, libc::SIGHUP)));
+
+        click(&mut app, MouseButton::Right, 20, row);
+        app.move_menu_selection(4);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.input_mode == InputMode::Renice);
+        press(&mut app, KeyCode::Char('-'));
+        press(&mut app, KeyCode::Char('5'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(niced.lock().unwrap().get(&400), Some(&-5));
+
+        // Un click fuori chiude il menu senza fare nulla
+        click(&mut app, MouseButton::Right, 20, row);
+        click(&mut app, MouseButton::Left, 0, 0);
+        assert!(app.input_mode == InputMode::None);
+        assert!(app.context_menu.is_none());
+    }
+
+    #[test]
+    fn footer_entries_run_their_action() {
+        let (mut app, _) = flat_app();
+        let &(start, _, _) = app
+            .footer_targets
+            .iter()
+            .find(|&&(_, _, action)| action == Action::ShowStats)
+            .unwrap();
+        let footer = app.footer_area.y + 1;
+        click(&mut app, MouseButton::Left, start, footer);
+        assert!(app.page == Page::SystemStats);
+
+        // Su 120 colonne il footer è troncato prima di Exit
+        assert!(app.footer_targets.iter().all(|&(_, end, action)| end < 120 && action != Action::Quit));
+        let lines = fake::render(160, 20, |f| ui(f, &mut app));
+        let &(start, end, _) = app
+            .footer_targets
+            .iter()
+            .find(|&&(_, _, action)| action == Action::Quit)
+            .unwrap();
+        let text: String = lines[footer as usize].chars().skip(start as usize).take((end - start) as usize).collect();
+        assert_eq!(text, "q: Exit");
+        assert!(click(&mut app, MouseButton::Left, start, footer));
+    }
+
+    #[test]
+    fn dragging_the_split_and_column_borders() {
+        let (mut app, _) = flat_app();
+        let (border, y) = (app.detail_area.x, app.detail_area.y + 5);
+
+        click(&mut app, MouseButton::Left, border, y);
+        handle_mouse(&mut app, MouseEventKind::Drag(MouseButton::Left), 60, 5).unwrap();
+        handle_mouse(&mut app, MouseEventKind::Up(MouseButton::Left), 60, 5).unwrap();
+        assert_eq!(app.preferences.processes.detail_width, 50);
+        draw(&mut app);
+        assert_eq!(app.detail_area.x, 60);
+
+        // Il bordo destro di PID è lo spazio dopo la colonna
+        let header = app.header_area.y + 1;
+        let pid_end = app.column_bounds()[0].2;
+        click(&mut app, MouseButton::Left, pid_end, header);
+        handle_mouse(&mut app, MouseEventKind::Drag(MouseButton::Left), pid_end - 3, header).unwrap();
+        assert_eq!(app.preferences.processes.column_widths.get(&Column::Pid), Some(&7));
+        draw(&mut app);
+        assert_eq!(app.column_bounds()[0].2, pid_end - 3);
+    }
+
+    #[test]
+    fn wheel_scrolls_overlays_and_details() {
+        let (mut app, _) = flat_app();
+        let (x, y) = (app.detail_area.x + 2, app.detail_area.y + 2);
+        handle_mouse(&mut app, MouseEventKind::ScrollDown, x, y).unwrap();
+        assert_eq!(app.detail_scroll, 1);
+        assert_eq!(app.table_state.selected(), Some(0));
+
+        let first_row = app.table_area.y + 2;
+        click(&mut app, MouseButton::Right, 20, first_row);
+        handle_mouse(&mut app, MouseEventKind::ScrollDown, 0, 0).unwrap();
+        handle_mouse(&mut app, MouseEventKind::ScrollDown, 0, 0).unwrap();
+        assert_eq!(app.context_menu.as_ref().unwrap().selected, 2);
+        assert_eq!(app.table_state.selected(), Some(0));
+    }
+}
@@ -0,0 +1,332 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use rtop::fuzzy;
+
+use crate::{
+    App, InputMode,
+    helpers::{keyboard::perform_action, keymap::Action},
+};
+
+const HISTORY_LENGTH: usize = 50;
+
+#[derive(Default)]
+pub struct PaletteState {
+    pub selected: usize,
+    // Comandi eseguiti, il più recente per ultimo
+    pub history: Vec<String>,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Command {
+    Action(Action),
+    Signal,
+    Renice,
+    Interval,
+    Expand,
+    View(String),
+    // Riesegue un comando della cronologia
+    Recent(String),
+}
+
+#[derive(Clone)]
+pub struct PaletteEntry {
+    pub name: String,
+    // Descrizione dell'argomento, vuota se il comando non ne ha
+    pub args: &'static str,
+    pub description: String,
+    pub keys: String,
+    pub command: Command,
+}
+
+impl App {
+    pub fn open_palette(&mut self) {
+        self.input_mode = InputMode::CommandPalette;
+        self.input_buffer.clear();
+        self.palette.selected = 0;
+    }
+
+    pub fn close_palette(&mut self) {
+        self.input_mode = InputMode::None;
+        self.input_buffer.clear();
+    }
+
+    // Tutti i comandi disponibili in questo momento
+    fn palette_entries(&self) -> Vec<PaletteEntry> {
+        let mut entries: Vec<PaletteEntry> = Action::ALL
+            .iter()
+            .filter(|action| {
+                **action != Action::CommandPalette && (action.group() != "Replay" || self.replay.is_some())
+            })
+            .map(|&action| PaletteEntry {
+                name: action.name().replace('_', " "),
+                args: "",
+                description: action.description().to_string(),
+                keys: self.keymap.keys_for(action).join(", "),
+                command: Command::Action(action),
+            })
+            .collect();
+
+        entries.extend([
+            PaletteEntry {
+                name: "signal".to_string(),
+                args: "<name|number>",
+                description: "Send a signal to the selected process".to_string(),
+                keys: String::new(),
+                command: Command::Signal,
+            },
+            PaletteEntry {
+                name: "renice".to_string(),
+                args: "<-20..19>",
+                description: "Change the priority of the selected process".to_string(),
+                keys: String::new(),
+                command: Command::Renice,
+            },
+            PaletteEntry {
+                name: "interval".to_string(),
+                args: "<ms>",
+                description: "Set the update interval (100 - 6000 ms)".to_string(),
+                keys: self.keymap.keys_for(Action::SetInterval).join(", "),
+                command: Command::Interval,
+            },
+            PaletteEntry {
+                name: "expand".to_string(),
+                args: "<depth>",
+                description: "Expand the tree down to a depth, 0 collapses everything".to_string(),
+                keys: String::new(),
+                command: Command::Expand,
+            },
+        ]);
+        entries.extend(self.view_names().into_iter().map(|name| PaletteEntry {
+            description: format!("Apply the saved view '{}'", name),
+            name: format!("view {}", name),
+            args: "",
+            keys: String::new(),
+            command: Command::View(name),
+        }));
+        entries
+    }
+
+    // Voci che corrispondono all'input, le migliori prima. Con l'input vuoto
+    // la cronologia viene prima di tutto il resto
+    pub fn palette_matches(&self) -> Vec<PaletteEntry> {
+        let input = self.input_buffer.trim();
+        let entries = self.palette_entries();
+
+        if input.is_empty() {
+            let recent = self.palette.history.iter().rev().map(|command| PaletteEntry {
+                name: command.clone(),
+                args: "",
+                description: "Recent".to_string(),
+                keys: String::new(),
+                command: Command::Recent(command.clone()),
+            });
+            return recent.chain(entries).collect();
+        }
+
+        // Un comando seguito dal suo argomento resta l'unica voce
+        if let Some((name, _)) = input.split_once(' ')
+            && let Some(entry) = entries.iter().find(|entry| !entry.args.is_empty() && entry.name == name)
+        {
+            return vec![entry.clone()];
+        }
+
+        let mut scored: Vec<(i32, PaletteEntry)> = entries
+            .into_iter()
+            .filter_map(|entry| {
+                // Il nome del comando conta più della descrizione
+                let by_name = fuzzy::score(input, &entry.name).map(|score| score * 2);
+                let by_description = fuzzy::score(input, &entry.description);
+                by_name.max(by_description).map(|score| (score, entry))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    pub fn move_palette_selection(&mut self, delta: isize) {
+        let last = self.palette_matches().len().saturating_sub(1);
+        self.palette.selected = self.palette.selected.saturating_add_signed(delta).min(last);
+    }
+
+    // Completa l'input con la voce selezionata
+    pub fn complete_palette(&mut self) {
+        if let Some(entry) = self.palette_matches().get(self.palette.selected) {
+            self.input_buffer = match &entry.command {
+                Command::Recent(command) => command.clone(),
+                _ if !entry.args.is_empty() => format!("{} ", entry.name),
+                _ => entry.name.clone(),
+            };
+            self.palette.selected = 0;
+        }
+    }
+
+    pub fn run_palette(&mut self) -> Result<bool> {
+        let input = self.input_buffer.trim().to_string();
+        let Some(entry) = self.palette_matches().into_iter().nth(self.palette.selected) else {
+            self.close_palette();
+            return Ok(false);
+        };
+
+        let (line, argument) = match &entry.command {
+            Command::Recent(command) => {
+                // La voce della cronologia si riesegue come se fosse stata digitata
+                self.input_buffer = command.clone();
+                self.palette.selected = 0;
+                return self.run_palette();
+            }
+            _ if entry.args.is_empty() => (entry.name.clone(), String::new()),
+            _ => match input.split_once(' ') {
+                Some((name, argument)) if name == entry.name && !argument.trim().is_empty() => {
+                    (input.clone(), argument.trim().to_string())
+                }
+                // Manca l'argomento: si completa il nome e si aspetta il resto
+                _ => {
+                    self.complete_palette();
+                    return Ok(false);
+                }
+            },
+        };
+
+        self.close_palette();
+        self.push_palette_history(line);
+        self.run_command(entry.command, &argument)
+    }
+
+    fn push_palette_history(&mut self, line: String) {
+        let history = &mut self.palette.history;
+        history.retain(|command| *command != line);
+        history.push(line);
+        if history.len() > HISTORY_LENGTH {
+            history.remove(0);
+        }
+    }
+
+    fn run_command(&mut self, command: Command, argument: &str) -> Result<bool> {
+        match command {
+            Command::Action(action) => return perform_action(self, action),
+            Command::Signal | Command::Renice => {
+                if self.refuse_in_replay() {
+                    return Ok(false);
+                }
+                let Some(pid) = self
+                    .table_state
+                    .selected()
+                    .and_then(|index| self.get_process_at_flat_index(index))
+                    .map(|node| node.info.pid)
+                else {
+                    self.errors.push(("Command".to_string(), "No process selected".to_string()));
+                    self.input_mode = InputMode::Error;
+                    return Ok(false);
+                };
+                if command == Command::Signal {
+                    self.send_signal(pid, argument);
+                } else {
+                    self.renice(pid, argument);
+                }
+            }
+            Command::Interval => match argument.parse::<u64>() {
+                Ok(ms) => {
+                    let ms = ms.clamp(100, 6000);
+                    self.update_interval = Duration::from_millis(ms);
+                    self.preferences.refresh.update_interval_ms = ms;
+                    self.save_preferences().ok();
+                }
+                Err(_) => {
+                    self.errors.push(("Command".to_string(), format!("'{}' is not a number of milliseconds", argument)));
+                    self.input_mode = InputMode::Error;
+                }
+            },
+            Command::Expand => match argument.parse::<usize>() {
+                Ok(depth) => self.expand_to_depth(Some(depth)),
+                Err(_) => {
+                    self.errors.push(("Command".to_string(), format!("'{}' is not a depth", argument)));
+                    self.input_mode = InputMode::Error;
+                }
+            },
+            Command::View(name) => {
+                if let Some(view) = self.preferences.views.get(&name).cloned() {
+                    self.apply_view(&view);
+                }
+            }
+            Command::Recent(_) => {}
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use sysinfo::Pid;
+
+    use crate::{
+        App, InputMode, SortColumn,
+        helpers::{fake, keyboard::handle_key_event},
+    };
+
+    fn press(app: &mut App, code: KeyCode) -> bool {
+        app.flatten_processes();
+        handle_key_event(app, code, KeyModifiers::NONE).unwrap()
+    }
+
+    fn run(app: &mut App, line: &str) -> bool {
+        press(app, KeyCode::Char(':'));
+        assert!(app.input_mode == InputMode::CommandPalette);
+        for c in line.chars() {
+            press(app, KeyCode::Char(c));
+        }
+        press(app, KeyCode::Enter)
+    }
+
+    #[test]
+    fn fuzzy_actions_and_arguments() {
+        let (mut app, (signals, _)) = fake::app_with_log(vec![fake::machine()]);
+        app.tree_mode = false;
+        app.force_refresh();
+
+        app.input_buffer = "srt mem".to_string();
+        assert_eq!(app.palette_matches()[0].name, "sort memory");
+        app.input_buffer.clear();
+
+        run(&mut app, "srt mem");
+        assert!(app.sort_column == SortColumn::Memory);
+        assert!(app.input_mode == InputMode::None);
+
+        run(&mut app, "interval 500");
+        assert_eq!(app.update_interval, Duration::from_millis(500));
+
+        // Il segnale va al processo selezionato
+        app.select_pid(Pid::from_u32(400));
+        run(&mut app, "signal hup");
+        assert_eq!(signals.lock().unwrap().last(), Some(&(400, libc::SIGHUP)));
+
+        run(&mut app, "signal nope");
+        assert!(app.input_mode == InputMode::Error);
+        press(&mut app, KeyCode::Esc);
+
+        // Senza argomento il comando viene completato e la palette resta aperta
+        run(&mut app, "interval");
+        assert!(app.input_mode == InputMode::CommandPalette);
+        assert_eq!(app.input_buffer, "interval ");
+        press(&mut app, KeyCode::Esc);
+
+        // La cronologia viene prima, la più recente in cima
+        press(&mut app, KeyCode::Char(':'));
+        let recent: Vec<String> = app.palette_matches().iter().take(4).map(|entry| entry.name.clone()).collect();
+        assert_eq!(recent, ["signal nope", "signal hup", "interval 500", "sort memory"]);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.palette.history.last().map(String::as_str), Some("interval 500"));
+        assert_eq!(app.palette.history.len(), 4);
+    }
+
+    #[test]
+    fn quit_from_the_palette() {
+        let mut app = fake::app(vec![fake::machine()]);
+        assert!(run(&mut app, "quit"));
+    }
+}
@@ -0,0 +1,62 @@
+use rtop::pressure::SystemPressure;
+
+use crate::App;
+
+impl App {
+    // Il cgroup del processo selezionato, l'unico di cui si legge la pressure
+    pub fn selected_cgroup(&self) -> Option<String> {
+        let selected = self.table_state.selected()?;
+        let node = self.get_process_at_flat_index(selected)?;
+        (!node.info.cgroup.is_empty()).then(|| node.info.cgroup.clone())
+    }
+
+    // Dopo un cambio di selezione resta vuota fino al campione successivo
+    pub fn selected_cgroup_pressure(&self) -> Option<(String, SystemPressure)> {
+        let (cgroup, pressure) = self.cgroup_pressure.as_ref()?;
+        (self.selected_cgroup().as_ref() == Some(cgroup)).then(|| (cgroup.clone(), *pressure))
+    }
+
+    // Senza collector la pressure si legge qui, subito dopo il campione
+    pub fn read_cgroup_pressure(&mut self) {
+        let cgroup = self.selected_cgroup();
+        self.cgroup_pressure = cgroup.and_then(|cgroup| {
+            let pressure = self.source.as_mut()?.cgroup_pressure(&cgroup)?;
+            Some((cgroup, pressure))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sysinfo::Pid;
+
+    use crate::helpers::fake;
+
+    #[test]
+    fn only_the_selected_cgroup_is_read() {
+        let mut second = fake::machine();
+        for process in &mut second.processes {
+            if process.pid == 400 {
+                process.cgroup = "/system.slice/firefox.scope".to_string();
+            }
+        }
+        let mut app = fake::app(vec![fake::machine(), second]);
+
+        assert!(app.select_pid(Pid::from_u32(400)));
+        app.read_cgroup_pressure();
+        let (name, pressure) = app.selected_cgroup_pressure().unwrap();
+        assert_eq!(name, "/user.slice");
+        assert_eq!(pressure.memory.some.avg10, 12.5);
+
+        // Nel campione successivo il processo è in un cgroup senza dati
+        app.refresh();
+        assert!(app.select_pid(Pid::from_u32(400)));
+        assert!(app.selected_cgroup_pressure().is_none());
+
+        // Cambiando selezione la lettura precedente non vale più
+        assert!(app.select_pid(Pid::from_u32(100)));
+        assert!(app.selected_cgroup_pressure().is_none());
+        app.read_cgroup_pressure();
+        assert_eq!(app.selected_cgroup_pressure().unwrap().0, "/user.slice");
+    }
+}
@@ -6,6 +6,16 @@ use std::io::Write;
 
 impl App {
     pub fn process_open_files(&mut self) {
+        // /proc è quello della macchina locale
+        if self.remote.is_some() {
+            self.errors.push((
+                "Open files".to_string(),
+                "Not available when monitoring a remote host".to_string(),
+            ));
+            self.input_mode = crate::InputMode::Error;
+            return;
+        }
+
         let Some(selected) = self.table_state.selected() else {
             return;
         };
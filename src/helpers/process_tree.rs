@@ -1,102 +1,68 @@
-use std::collections::{HashMap, HashSet};
-use sysinfo::Pid;
+use rtop::{
+    collector::{PreparedTree, TreeView},
+    tree::Layout,
+};
 
-use crate::{App, ProcessInfo};
+use crate::App;
 
 impl App {
+    pub fn tree_layout(&self) -> Layout {
+        Layout {
+            tree_mode: self.tree_mode,
+            sort_column: self.sort_column,
+            reverse_sort: self.reverse_sort,
+        }
+    }
+
     pub fn build_process_tree(&mut self) {
         let selected_line = self.table_state.selected();
 
-        let mut process_infos: HashMap<Pid, ProcessInfo> = HashMap::new();
-        let mut children_map: HashMap<Pid, Vec<Pid>> = HashMap::new();
-        let mut has_parent: HashSet<Pid> = HashSet::new();
-
-        let process_count = self.system.processes().len();
-        process_infos.reserve(process_count);
-
-        let cpu_number = self.system.cpus().len() as f32;
-        for (pid, process) in self.system.processes() {
-            let user_id = process.user_id().map(|uid| **uid);
-
-            // Format più clean
-            let status_str = match process.status() {
-                sysinfo::ProcessStatus::Run => "Running".to_string(),
-                sysinfo::ProcessStatus::Sleep => "Sleeping".to_string(),
-                sysinfo::ProcessStatus::Idle => "Idle".to_string(),
-                sysinfo::ProcessStatus::Zombie => "Zombie".to_string(),
-                sysinfo::ProcessStatus::Stop => "Stopped".to_string(),
-                sysinfo::ProcessStatus::Tracing => "Tracing".to_string(),
-                sysinfo::ProcessStatus::Dead => "Dead".to_string(),
-                sysinfo::ProcessStatus::Wakekill => "Wakekill".to_string(),
-                sysinfo::ProcessStatus::Waking => "Waking".to_string(),
-                sysinfo::ProcessStatus::Parked => "Parked".to_string(),
-                sysinfo::ProcessStatus::LockBlocked => "LockBlocked".to_string(),
-                sysinfo::ProcessStatus::UninterruptibleDiskSleep => "DiskSleep".to_string(),
-                _ => format!("{:?}", process.status()),
-            };
-
-            let info = ProcessInfo {
-                pid: *pid,
-                name: process.name().to_string_lossy().to_string(),
-                cpu_usage: process.cpu_usage() / cpu_number,
-                memory: process.memory(),
-                user_id,
-                status: status_str,
-            };
-            process_infos.insert(*pid, info);
-
-            if let Some(parent_pid) = process.parent() {
-                children_map
-                    .entry(parent_pid)
-                    .or_insert_with(Vec::new)
-                    .push(*pid);
-                has_parent.insert(*pid);
-            }
+        let layout = self.tree_layout();
+        let added = self.processes.update(&self.snapshot.processes, layout, &mut self.expanded_pids);
+        // Se il filtro è cambiato ci pensa flatten_processes ad aprire tutti i match
+        if self.filter.is_active() && self.expanded_filter.as_ref() == Some(&self.filter) {
+            self.processes.expand_new_matches(&self.filter, &added, &mut self.expanded_pids);
         }
 
-        // Processi root del sistema da skippare (systemd and kthreadd)
-        let skip_pids: HashSet<u32> = [1, 2].iter().copied().collect();
-
-        let mut roots = Vec::new();
+        self.finish_tree_update(selected_line);
+    }
 
-        // Faccio diventare root i processi senza un parent e i figli diretti dei processi 1 e 2 (systemd e kthreadd)
-        for (pid, _info) in &process_infos {
-            let pid_u32 = pid.as_u32();
+    fn finish_tree_update(&mut self, selected_line: Option<usize>) {
+        self.cached_flat_processes = None;
 
-            if skip_pids.contains(&pid_u32) {
-                continue;
-            }
+        let flat_len = self.flatten_processes().len();
+        self.clamp_selection(selected_line, flat_len);
+    }
 
-            let should_be_root = if !has_parent.contains(pid) {
-                true
-            } else {
-                if let Some(process) = self.system.process(*pid) {
-                    if let Some(parent_pid) = process.parent() {
-                        skip_pids.contains(&parent_pid.as_u32())
-                    } else {
-                        true
-                    }
-                } else {
-                    false
-                }
-            };
-
-            if should_be_root {
-                roots.push(self.build_node(*pid, &process_infos, &children_map));
-            }
-        }
+    // Usa l'albero preparato dal collector: filtri e ordinamento sono già fatti
+    pub fn swap_process_tree(&mut self, prepared: PreparedTree) {
+        let selected_line = self.table_state.selected();
 
-        self.sort_processes(&mut roots);
-        self.processes = roots;
+        let flat_len = prepared.rows.len();
+        self.processes = prepared.tree;
+        self.expanded_pids = prepared.expanded_pids;
+        self.cached_flat_processes = Some(prepared.rows);
+        self.flatten_generation += 1;
 
-        // Aggiorno expanded_pids in modo che contenga processi ancora esistenti
-        let existing_pids: HashSet<Pid> = process_infos.keys().copied().collect();
-        self.expanded_pids
-            .retain(|pid, _| existing_pids.contains(pid));
+        self.clamp_selection(selected_line, flat_len);
+    }
 
-        self.cached_flat_processes = None;
+    // Il collector prepara i prossimi alberi con la vista appena cambiata
+    pub fn send_tree_view(&mut self) {
+        let Some(collector) = &self.collector else {
+            return;
+        };
+        self.tree_view_generation += 1;
+        collector.set_view(TreeView {
+            layout: self.tree_layout(),
+            filter: self.filter.clone(),
+            expanded_pids: self.expanded_pids.clone(),
+            expanded_filter: self.expanded_filter.clone(),
+            generation: self.tree_view_generation,
+        });
+    }
 
-        let flat_len = self.flatten_processes().len();
+    fn clamp_selection(&mut self, selected_line: Option<usize>, flat_len: usize) {
         if flat_len > 0 {
             if let Some(idx) = selected_line {
                 self.table_state.select(Some(idx.min(flat_len - 1)));
@@ -107,4 +73,86 @@ impl App {
             self.table_state.select(None);
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use sysinfo::Pid;
+
+    use crate::{App, SortColumn, helpers::fake};
+
+    fn names(app: &App, slots: &[usize]) -> Vec<String> {
+        slots.iter().map(|&slot| app.processes.get(slot).unwrap().info.name.clone()).collect()
+    }
+
+    fn tree_app(second: rtop::snapshot::Snapshot) -> App {
+        let mut app = fake::app(vec![fake::machine(), second]);
+        app.tree_mode = true;
+        app.sort_column = SortColumn::Cpu;
+        app.reverse_sort = true;
+        app.build_process_tree();
+        app
+    }
+
+    #[test]
+    fn exited_parent_relinks_children() {
+        let mut second = fake::machine();
+        second.processes.retain(|p| p.pid != 100);
+        let mut app = tree_app(second);
+        assert_eq!(names(&app, app.processes.roots()), ["firefox", "sshd"]);
+
+        app.refresh();
+        assert_eq!(names(&app, app.processes.roots()), ["firefox", "bash"]);
+        assert!(app.processes.slot_of(Pid::from_u32(100)).is_none());
+        assert_eq!(app.processes.len(), 4);
+    }
+
+    #[test]
+    fn updates_keep_slots_and_resort_siblings() {
+        let mut second = fake::machine();
+        for process in &mut second.processes {
+            if process.pid == 100 {
+                process.cpu_usage = 80.0;
+            }
+        }
+        second.processes.retain(|p| p.pid != 300);
+        second.processes.push(fake::process(500, Some(200), "make", 3.0, 50));
+        let mut app = tree_app(second);
+
+        let firefox = app.processes.slot_of(Pid::from_u32(400));
+
+        app.refresh();
+        assert_eq!(app.processes.slot_of(Pid::from_u32(400)), firefox);
+        assert_eq!(names(&app, app.processes.roots()), ["sshd", "firefox"]);
+
+        let bash = app.processes.get(app.processes.slot_of(Pid::from_u32(200)).unwrap()).unwrap();
+        assert_eq!(names(&app, &bash.children), ["make"]);
+        assert!(app.processes.slot_of(Pid::from_u32(300)).is_none());
+    }
+
+    #[test]
+    fn new_matches_of_the_current_filter_are_expanded() {
+        let mut second = fake::machine();
+        second.processes.push(fake::process(500, Some(200), "make", 3.0, 50));
+        let mut app = tree_app(second);
+        app.filter.search = "make".to_string();
+        app.cached_flat_processes = None;
+        assert!(app.flatten_processes().is_empty());
+
+        app.refresh();
+        let rows: Vec<usize> = app.flatten_processes().iter().map(|&(_, slot)| slot).collect();
+        assert_eq!(names(&app, &rows), ["sshd", "bash", "make"]);
+    }
+
+    #[test]
+    fn switching_to_flat_mode_relinks_everything() {
+        let mut app = tree_app(fake::machine());
+        app.tree_mode = false;
+        app.build_process_tree();
+        assert_eq!(
+            names(&app, app.processes.roots()),
+            ["cargo", "firefox", "bash", "init", "sshd"]
+        );
+        assert!(app.processes.roots().iter().all(|&slot| app.processes.get(slot).unwrap().children.is_empty()));
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+
+use rtop::snapshot::Snapshot;
+
+use crate::App;
+
+// Versione del formato, da incrementare se cambia Snapshot in modo incompatibile
+const RECORDING_VERSION: u32 = 1;
+
+// Prima riga del file: i campioni seguono uno per riga, tutto compresso con gzip
+#[derive(Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub version: u32,
+    pub interval_ms: u64,
+    pub hostname: Option<String>,
+}
+
+pub struct Recorder {
+    writer: GzEncoder<BufWriter<File>>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, interval: Duration, hostname: Option<String>) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("Cannot create {}", path.display()))?;
+        let mut recorder = Self {
+            writer: GzEncoder::new(BufWriter::new(file), Compression::default()),
+        };
+
+        let header = RecordingHeader {
+            version: RECORDING_VERSION,
+            interval_ms: interval.as_millis() as u64,
+            hostname,
+        };
+        recorder.write_line(&header)?;
+        Ok(recorder)
+    }
+
+    // Ogni campione viene scaricato su disco subito, così un'interruzione
+    // perde al massimo l'ultimo
+    pub fn write(&mut self, snapshot: &Snapshot) -> Result<()> {
+        self.write_line(snapshot)
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, value)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<()> {
+        self.writer.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+pub fn load_recording(path: &Path) -> Result<(RecordingHeader, Vec<Snapshot>)> {
+    let file = File::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+
+    // Una registrazione interrotta termina senza il trailer gzip: read_to_end
+    // restituisce errore ma mantiene i dati decompressi fino a quel punto
+    let mut data = Vec::new();
+    GzDecoder::new(file).read_to_end(&mut data).ok();
+    let mut lines = data.split(|&b| b == b'\n');
+
+    let header: RecordingHeader = lines
+        .next()
+        .and_then(|line| serde_json::from_slice(line).ok())
+        .with_context(|| format!("{} is not an rtop recording", path.display()))?;
+    if header.version != RECORDING_VERSION {
+        bail!("Unsupported recording version {}", header.version);
+    }
+
+    // L'ultima riga di un file troncato può essere incompleta
+    let snapshots: Vec<Snapshot> = lines
+        .filter(|line| !line.is_empty())
+        .map_while(|line| serde_json::from_slice(line).ok())
+        .collect();
+
+    if snapshots.is_empty() {
+        bail!("{} contains no samples", path.display());
+    }
+    Ok((header, snapshots))
+}
+
+pub struct Replay {
+    pub snapshots: Vec<Snapshot>,
+    pub position: usize,
+    pub hostname: Option<String>,
+}
+
+impl App {
+    // Registra `iterations` campioni senza interfaccia (0 = senza fine)
+    pub fn run_record(&mut self, path: &Path, iterations: u64) -> Result<()> {
+        // Registrando un host remoto vale il suo nome, non quello locale
+        let hostname = self.remote.clone().or_else(sysinfo::System::host_name);
+        let mut recorder = Recorder::create(path, self.update_interval, hostname)?;
+        eprintln!(
+            "Recording to {} every {} ms, press Ctrl+C to stop",
+            path.display(),
+            self.update_interval.as_millis()
+        );
+
+        // auto_refresh = false nel config non deve fermare la registrazione
+        self.refresh = true;
+        let mut count = 0;
+        while iterations == 0 || count < iterations {
+            thread::sleep(self.update_interval);
+            self.refresh();
+            recorder.write(&self.snapshot)?;
+            count += 1;
+        }
+
+        recorder.finish()
+    }
+
+    pub fn start_replay(&mut self, path: &Path) -> Result<()> {
+        let (header, snapshots) = load_recording(path)?;
+        self.update_interval = Duration::from_millis(header.interval_ms.clamp(100, 6000));
+        self.replay = Some(Replay {
+            snapshots,
+            position: 0,
+            hostname: header.hostname,
+        });
+        self.replay_seek(0);
+        Ok(())
+    }
+
+    pub fn replay_step(&mut self, delta: isize) {
+        let Some(replay) = &self.replay else {
+            return;
+        };
+        let last = replay.snapshots.len() - 1;
+        let position = replay.position.saturating_add_signed(delta).min(last);
+
+        // Arrivato alla fine la riproduzione si mette in pausa
+        if position == last {
+            self.refresh = false;
+        }
+        if position != replay.position {
+            self.replay_seek(position);
+        }
+    }
+
+    // Salta di una frazione della registrazione (es. 0.1 = 10%)
+    pub fn replay_jump(&mut self, fraction: f64) {
+        if let Some(replay) = &self.replay {
+            let delta = (replay.snapshots.len() as f64 * fraction).round() as isize;
+            self.replay_step(if delta == 0 { fraction.signum() as isize } else { delta });
+        }
+    }
+
+    // Mostra il campione in posizione `position`, ricostruendo le history fino a lì
+    pub fn replay_seek(&mut self, position: usize) {
+        let Some(mut replay) = self.replay.take() else {
+            return;
+        };
+        let previous = replay.position;
+        let position = position.min(replay.snapshots.len() - 1);
+        replay.position = position;
+
+        // Nella riproduzione normale basta aggiungere il nuovo campione
+        if position == previous + 1 {
+            self.push_snapshot_history(&replay.snapshots[position]);
+        } else {
+            let start = (position + 1).saturating_sub(self.preferences.refresh.history_length);
+            self.clear_history();
+            for snapshot in &replay.snapshots[start..=position] {
+                self.push_snapshot_history(snapshot);
+            }
+        }
+
+        let snapshot = replay.snapshots[position].clone();
+        self.replay = Some(replay);
+        self.set_snapshot(snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{self, File},
+        io::Write,
+        path::PathBuf,
+        time::Duration,
+    };
+
+    use flate2::{Compression, write::GzEncoder};
+
+    use super::{Recorder, Replay, load_recording};
+    use crate::helpers::fake;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rtop-record-{}-{}.rtop", name, std::process::id()))
+    }
+
+    fn timestamps(snapshots: &[rtop::snapshot::Snapshot]) -> Vec<i64> {
+        snapshots.iter().map(|snapshot| snapshot.timestamp).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_file("round-trip");
+        let frames = fake::timeline(3);
+        let mut recorder = Recorder::create(&path, Duration::from_millis(1500), Some("box".to_string())).unwrap();
+        for frame in &frames {
+            recorder.write(frame).unwrap();
+        }
+        recorder.finish().unwrap();
+
+        let (header, snapshots) = load_recording(&path).unwrap();
+        assert_eq!(header.interval_ms, 1500);
+        assert_eq!(header.hostname.as_deref(), Some("box"));
+        assert_eq!(timestamps(&snapshots), timestamps(&frames));
+        assert_eq!(snapshots[2].cpu_usage, frames[2].cpu_usage);
+        assert_eq!(snapshots[0].processes.len(), 5);
+
+        let mut app = fake::app(vec![fake::machine()]);
+        app.start_replay(&path).unwrap();
+        assert_eq!(app.update_interval, Duration::from_millis(1500));
+        assert_eq!(app.snapshot.timestamp, frames[0].timestamp);
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn interrupted_recordings_keep_the_earlier_frames() {
+        // Gzip senza trailer, tagliato a metà del terzo campione
+        let path = temp_file("truncated");
+        let frames = fake::timeline(3);
+        let mut recorder = Recorder::create(&path, Duration::from_secs(1), None).unwrap();
+        recorder.write(&frames[0]).unwrap();
+        recorder.write(&frames[1]).unwrap();
+        let flushed = fs::metadata(&path).unwrap().len();
+        recorder.write(&frames[2]).unwrap();
+        recorder.finish().unwrap();
+        File::options().write(true).open(&path).unwrap().set_len(flushed + 20).unwrap();
+
+        let (_, snapshots) = load_recording(&path).unwrap();
+        assert_eq!(timestamps(&snapshots), timestamps(&frames[..2]));
+
+        // Gzip completo ma con l'ultima riga scritta a metà
+        let line = serde_json::to_string(&frames[1]).unwrap();
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        writeln!(encoder, "{{\"version\":1,\"interval_ms\":1000,\"hostname\":null}}").unwrap();
+        writeln!(encoder, "{}", serde_json::to_string(&frames[0]).unwrap()).unwrap();
+        write!(encoder, "{}", &line[..line.len() / 2]).unwrap();
+        encoder.finish().unwrap();
+
+        let (_, snapshots) = load_recording(&path).unwrap();
+        assert_eq!(timestamps(&snapshots), timestamps(&frames[..1]));
+
+        // Senza campioni non c'è nulla da riprodurre
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        writeln!(encoder, "{{\"version\":1,\"interval_ms\":1000,\"hostname\":null}}").unwrap();
+        encoder.finish().unwrap();
+        assert!(load_recording(&path).is_err());
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn seek_and_step_stay_in_range() {
+        let frames = fake::timeline(5);
+        let mut app = fake::app(vec![fake::machine()]);
+        app.replay = Some(Replay {
+            snapshots: frames.clone(),
+            position: 0,
+            hostname: None,
+        });
+        app.replay_seek(0);
+        let position = |app: &crate::App| app.replay.as_ref().unwrap().position;
+
+        app.replay_step(-1);
+        assert_eq!(position(&app), 0);
+        assert_eq!(app.snapshot.timestamp, frames[0].timestamp);
+
+        app.replay_step(2);
+        assert_eq!(position(&app), 2);
+        assert!(app.refresh);
+
+        // Oltre la fine si ferma sull'ultimo campione e mette in pausa
+        app.replay_step(10);
+        assert_eq!(position(&app), 4);
+        assert_eq!(app.snapshot.timestamp, frames[4].timestamp);
+        assert!(!app.refresh);
+
+        app.replay_seek(100);
+        assert_eq!(position(&app), 4);
+
+        app.replay_jump(-1.0);
+        assert_eq!(position(&app), 0);
+        app.replay_jump(0.01);
+        assert_eq!(position(&app), 1);
+        assert_eq!(app.cpu_history.first().map_or(0, |history| history.len()), 2);
+    }
+}
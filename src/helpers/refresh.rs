@@ -1,75 +1,196 @@
-use std::time::Instant;
-use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, UpdateKind};
+use std::{sync::Arc, time::Instant};
+
+use rtop::{
+    collector::{Collector, Sample, SampleHook},
+    snapshot::Snapshot,
+};
 
 use crate::App;
 
 impl App {
+    // Campionamento sincrono, usato senza interfaccia (batch, record) e nei test
     pub fn refresh(&mut self) {
-        if self.refresh == true {
-            self.system.refresh_cpu_all();
-            self.system.refresh_memory();
-
-            use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, UpdateKind};
-            
-            // Refresh degli status
-            self.system.refresh_processes_specifics(
-                ProcessesToUpdate::All,
-                true,
-                ProcessRefreshKind::nothing()
-                    .with_cpu()
-                    .with_memory()
-                    .with_user(UpdateKind::Always),
-            );
-
-            self.networks.refresh(true);
-
-            // Aggiorna memoria CPU
-            for (i, cpu) in self.system.cpus().iter().enumerate() {
-                if i >= self.cpu_history.len() {
-                    self.cpu_history.push(Vec::new());
-                }
-                self.cpu_history[i].push(cpu.cpu_usage());
-                if self.cpu_history[i].len() > 60 {
-                    self.cpu_history[i].remove(0);
-                }
+        if self.refresh {
+            // In replay il refresh avanza di un campione registrato
+            if self.replay.is_some() {
+                self.replay_step(1);
+                return;
             }
 
-            // Aggiorna history memoria
-            let used_mem = self.system.used_memory() as f64 / 1024.0 / 1024.0 / 1024.0;
-            self.memory_history.push(used_mem);
-            if self.memory_history.len() > 60 {
-                self.memory_history.remove(0);
+            if let Some(source) = &mut self.source {
+                let snapshot = source.sample();
+                self.apply_sample(Sample {
+                    snapshot,
+                    periodic: true,
+                    tree: None,
+                    cgroup_pressure: None,
+                });
+                self.read_cgroup_pressure();
             }
+        }
+    }
 
-            // Aggiorna memoria network
-            let (rx, tx) = self.networks.iter().fold((0, 0), |(rx, tx), (_, net)| {
-                (rx + net.received(), tx + net.transmitted())
-            });
-            self.network_history.push((rx, tx));
-            if self.network_history.len() > 60 {
-                self.network_history.remove(0);
+    pub fn force_refresh(&mut self) {
+        if self.replay.is_none() {
+            if let Some(source) = &mut self.source {
+                let snapshot = source.sample();
+                self.set_snapshot(snapshot);
+                self.read_cgroup_pressure();
+            } else {
+                // Il nuovo campione arriverà dal collector, intanto si
+                // riapplicano filtri e ordinamento a quello corrente
+                if let Some(collector) = &self.collector {
+                    collector.request_sample();
+                }
+                self.build_process_tree();
             }
-
-            self.last_update = Instant::now();
+        } else {
             self.build_process_tree();
         }
+        self.cached_flat_processes = None;
     }
-    
-     pub fn force_refresh(&mut self) {
-        self.system.refresh_cpu_all();
-        self.system.refresh_memory();
-
-        self.system.refresh_processes_specifics(
-            ProcessesToUpdate::All,
-            true,
-            ProcessRefreshKind::nothing()
-                .with_cpu()
-                .with_memory()
-                .with_user(UpdateKind::Always),
-        );
-
-        self.networks.refresh(true);
+
+    // Ricerca, filtri e ordinamento lavorano sul campione corrente: chiedere
+    // un campione a ogni tasto intaserebbe il collector
+    pub fn reapply_filters(&mut self) {
         self.build_process_tree();
-        self.cached_flat_processes = None;
     }
-}
\ No newline at end of file
+
+    fn apply_sample(&mut self, sample: Sample) {
+        if sample.periodic {
+            self.push_snapshot_history(&sample.snapshot);
+            self.last_update = Instant::now();
+        }
+        self.cgroup_pressure = sample.cgroup_pressure;
+        match sample.tree {
+            Some(prepared) if prepared.generation == self.tree_view_generation => {
+                self.replace_snapshot(sample.snapshot);
+                self.swap_process_tree(prepared);
+            }
+            // Preparato prima dell'ultima modifica della vista: l'albero attuale
+            // è già aggiornato a quella, il prossimo campione lo sostituirà
+            Some(_) => self.replace_snapshot(sample.snapshot),
+            None => self.set_snapshot(sample.snapshot),
+        }
+        // Con il collector gli allarmi sono già stati valutati nel suo thread
+        if sample.periodic && self.collector.is_none() {
+            self.evaluate_alerts();
+        }
+    }
+
+    // Sposta la sorgente in un thread dedicato; da qui in poi i campioni
+    // vengono applicati da tick()
+    pub fn start_collector(&mut self) {
+        if self.replay.is_some() {
+            return;
+        }
+        if let Some(source) = self.source.take() {
+            let alerts = Arc::clone(&self.alerts);
+            let hook: SampleHook = Box::new(move |snapshot: &Snapshot| alerts.lock().unwrap().run(snapshot));
+            self.collector = Some(Collector::spawn(source, self.update_interval, self.refresh, Some(hook)));
+            // Da qui i campioni arrivano con l'albero già pronto
+            self.send_tree_view();
+        }
+    }
+
+    // Chiamata a ogni giro del ciclo dell'interfaccia, non blocca mai
+    pub fn tick(&mut self) {
+        if self.replay.is_some() {
+            if self.refresh && self.last_update.elapsed() >= self.update_interval {
+                self.last_update = Instant::now();
+                self.replay_step(1);
+            }
+            return;
+        }
+
+        let cgroup = self.selected_cgroup();
+        let Some(collector) = &mut self.collector else {
+            return;
+        };
+        collector.configure(self.update_interval, self.refresh);
+        collector.watch_cgroup(cgroup.as_deref());
+        for sample in collector.drain() {
+            self.apply_sample(sample);
+        }
+        self.show_alert_failures();
+        self.poll_inspector();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use crate::{SortColumn, helpers::fake};
+
+    #[test]
+    fn collector_samples_are_applied_by_tick() {
+        let mut app = fake::app(fake::timeline(4));
+        let first = app.snapshot.timestamp;
+        app.update_interval = Duration::from_millis(10);
+        app.start_collector();
+        assert!(app.source.is_none());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.cpu_history.first().map_or(0, |h| h.len()) < 2 {
+            assert!(Instant::now() < deadline, "no samples from the collector");
+            app.tick();
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(app.snapshot.timestamp > first);
+    }
+
+    #[test]
+    fn paused_collector_only_samples_on_request() {
+        let mut app = fake::app(fake::timeline(4));
+        let first = app.snapshot.timestamp;
+        app.update_interval = Duration::from_millis(10);
+        app.refresh = false;
+        app.start_collector();
+
+        thread::sleep(Duration::from_millis(50));
+        app.tick();
+        assert_eq!(app.snapshot.timestamp, first);
+
+        app.force_refresh();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.snapshot.timestamp == first {
+            assert!(Instant::now() < deadline, "requested sample never arrived");
+            app.tick();
+            thread::sleep(Duration::from_millis(5));
+        }
+        // I campioni chiesti a mano non finiscono nei grafici
+        assert!(app.cpu_history.is_empty());
+    }
+
+    #[test]
+    fn tick_only_swaps_in_the_collector_tree() {
+        let mut app = fake::app(fake::timeline(4));
+        let first = app.snapshot.timestamp;
+        app.tree_mode = false;
+        app.sort_column = SortColumn::Cpu;
+        app.reverse_sort = true;
+        app.reapply_filters();
+        app.update_interval = Duration::from_millis(10);
+        app.start_collector();
+
+        // Cambiato senza mandare la vista al collector: se tick() rifacesse
+        // l'albero, le righe sarebbero in ordine di nome
+        app.sort_column = SortColumn::Name;
+        app.reverse_sort = false;
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.snapshot.timestamp == first {
+            assert!(Instant::now() < deadline, "no samples from the collector");
+            app.tick();
+            thread::sleep(Duration::from_millis(5));
+        }
+        let names: Vec<String> = (0..5)
+            .map(|index| app.get_process_at_flat_index(index).unwrap().info.name.clone())
+            .collect();
+        assert_eq!(names, ["cargo", "firefox", "bash", "init", "sshd"]);
+    }
+}
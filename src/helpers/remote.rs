@@ -0,0 +1,160 @@
+use anyhow::Result;
+use rtop::remote::{TOKEN_VARIABLE, connect};
+
+use crate::{App, helpers::cli::Cli};
+
+impl App {
+    // Stessa interfaccia, ma i campioni e le azioni passano dall'agent remoto
+    pub fn connect(cli: &Cli, target: &str) -> Result<Self> {
+        let token = std::env::var(TOKEN_VARIABLE).ok().filter(|token| !token.is_empty());
+        let remote = connect(target, token.as_deref())?;
+        let mut app = Self::with_source(cli, Box::new(remote.source), Box::new(remote.control));
+        app.remote = Some(remote.hostname.unwrap_or_else(|| target.to_string()));
+        Ok(app)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        io::{BufReader, Cursor},
+        net::TcpListener,
+        os::unix::net::UnixListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use rtop::{
+        remote::{connect, serve},
+        source::{MetricsSource, ProcessControl},
+    };
+
+    use crate::{
+        App,
+        helpers::{cli::Cli, fake},
+    };
+
+    // Agent su una porta locale con la sorgente finta, per un solo client
+    fn tcp_agent() -> (String, Arc<Mutex<HashMap<u32, i32>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let (mut source, control) = fake::scripted(fake::timeline(3));
+        let niced = control.niced.clone();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            serve(reader, stream, &mut source, &control, None).unwrap();
+        });
+        (addr, niced)
+    }
+
+    #[test]
+    fn samples_and_actions_over_tcp() {
+        let (addr, niced) = tcp_agent();
+        let mut remote = connect(&addr, None).unwrap();
+        assert_eq!(remote.hostname, sysinfo::System::host_name());
+
+        let first = remote.source.sample();
+        assert_eq!(first.timestamp, fake::machine().timestamp);
+        assert!(first.process(300).is_some());
+
+        assert!(remote.control.kill(300));
+        assert!(!remote.control.kill(300));
+        remote.control.renice(400, 5).unwrap();
+        assert_eq!(niced.lock().unwrap().get(&400), Some(&5));
+
+        let second = remote.source.sample();
+        assert_eq!(second.timestamp, first.timestamp + 1000);
+        assert!(second.process(300).is_none());
+    }
+
+    #[test]
+    fn app_over_unix_socket() {
+        let path = std::env::temp_dir().join(format!("rtop-agent-test-{}.sock", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let listener = UnixListener::bind(&path).unwrap();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let (mut source, control) = fake::scripted(fake::timeline(2));
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            serve(reader, stream, &mut source, &control, None).unwrap();
+        });
+
+        let cli = Cli {
+            config: Some("/dev/null".into()),
+            ..Default::default()
+        };
+        let mut app = App::connect(&cli, &format!("unix:{}", path.display())).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(app.remote.is_some());
+        assert_eq!(app.snapshot.processes.len(), 5);
+        app.refresh();
+        assert_eq!(app.snapshot.timestamp, fake::machine().timestamp + 1000);
+    }
+
+    #[test]
+    fn agent_rejects_other_versions() {
+        let (mut source, control) = fake::scripted(fake::timeline(1));
+        let mut output = Vec::new();
+        let input = Cursor::new("{\"type\":\"hello\",\"version\":99}\n{\"type\":\"sample\"}\n");
+        serve(input, &mut output, &mut source, &control, None).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("\"type\":\"error\""));
+        assert!(output.contains("Unsupported protocol version 99"));
+    }
+
+    #[test]
+    fn token_is_checked_on_hello_and_actions() {
+        let (mut source, control) = fake::scripted(fake::timeline(1));
+        let mut output = Vec::new();
+        let input = Cursor::new("{\"type\":\"hello\",\"version\":2}\n{\"type\":\"sample\"}\n");
+        serve(input, &mut output, &mut source, &control, Some("secret")).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("Invalid or missing token"));
+
+        // Dopo un hello valido ogni azione deve comunque portare il token
+        let mut output = Vec::new();
+        let input = Cursor::new(
+            "{\"type\":\"hello\",\"version\":2,\"token\":\"secret\"}\n\
+             {\"type\":\"signal\",\"id\":1,\"pid\":300,\"signal\":9}\n\
+             {\"type\":\"signal\",\"id\":2,\"pid\":300,\"signal\":9,\"token\":\"wrong\"}\n\
+             {\"type\":\"renice\",\"id\":3,\"pid\":400,\"nice\":5,\"token\":\"secret\"}\n",
+        );
+        let niced = control.niced.clone();
+        let signals = control.signals.clone();
+        serve(input, &mut output, &mut source, &control, Some("secret")).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].contains("invalid or missing token"));
+        assert!(lines[2].contains("invalid or missing token"));
+        assert!(lines[3].contains("\"error\":null"));
+        assert!(signals.lock().unwrap().is_empty());
+        assert_eq!(niced.lock().unwrap().get(&400), Some(&5));
+    }
+
+    #[test]
+    fn client_sends_the_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let (mut source, control) = fake::scripted(fake::timeline(1));
+        let niced = control.niced.clone();
+        thread::spawn(move || {
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().unwrap();
+                let reader = BufReader::new(stream.try_clone().unwrap());
+                serve(reader, stream, &mut source, &control, Some("secret")).unwrap();
+            }
+        });
+
+        assert!(connect(&addr, None).is_err());
+        let remote = connect(&addr, Some("secret")).unwrap();
+        remote.control.renice(400, 3).unwrap();
+        assert_eq!(niced.lock().unwrap().get(&400), Some(&3));
+    }
+}
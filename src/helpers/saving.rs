@@ -1,26 +1,216 @@
-use crate::{App, Preferences};
-use anyhow::Result;
-use std::{fs, path::PathBuf};
+use crate::{
+    App,
+    helpers::config::{DEFAULT_CONFIG, LegacyPreferences, Preferences},
+};
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use toml_edit::{DocumentMut, Item};
 
 impl App {
-    pub fn config_path() -> PathBuf {
+    pub fn config_dir() -> PathBuf {
         let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
         path.push("rtop");
         fs::create_dir_all(&path).ok();
-        path.push("preferences.json");
         path
     }
 
-    pub fn load_preferences() -> Option<Preferences> {
-        let path = Self::config_path();
-        let contents = fs::read_to_string(path).ok()?;
-        serde_json::from_str(&contents).ok()
+    pub fn default_config_path() -> PathBuf {
+        Self::config_dir().join("config.toml")
+    }
+
+    fn legacy_preferences_path() -> PathBuf {
+        Self::config_dir().join("preferences.json")
+    }
+
+    // Con --config la migrazione dal vecchio preferences.json non viene fatta
+    pub fn load_preferences(path: &Path) -> Result<Preferences> {
+        let legacy_path = (path == Self::default_config_path()).then(Self::legacy_preferences_path);
+        load_preferences_from(path, legacy_path.as_deref())
     }
 
     pub fn save_preferences(&self) -> Result<()> {
-        let path = Self::config_path();
-        let contents = serde_json::to_string_pretty(&self.preferences)?;
-        fs::write(path, contents)?;
-        Ok(())
+        // Non sovrascrivo un file che l'utente deve ancora correggere
+        if self.config_error {
+            return Ok(());
+        }
+        write_preferences(&self.config_path, &self.preferences)
+    }
+}
+
+fn load_preferences_from(path: &Path, legacy_path: Option<&Path>) -> Result<Preferences> {
+    if !path.exists() {
+        return migrate_preferences(path, legacy_path);
+    }
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Cannot read {}", path.display()))?;
+    let mut preferences: Preferences = toml_edit::de::from_str(&contents)
+        .with_context(|| format!("Invalid config {}", path.display()))?;
+    preferences.normalize();
+    Ok(preferences)
+}
+
+// Crea il file di configurazione partendo dal vecchio preferences.json, se presente
+fn migrate_preferences(path: &Path, legacy_path: Option<&Path>) -> Result<Preferences> {
+    let mut preferences = Preferences::default();
+
+    if let Some(legacy_path) = legacy_path
+        && let Ok(contents) = fs::read_to_string(legacy_path)
+    {
+        let legacy: LegacyPreferences = serde_json::from_str(&contents)
+            .with_context(|| format!("Cannot migrate {}", legacy_path.display()))?;
+        legacy.apply(&mut preferences);
+        preferences.normalize();
+    }
+
+    write_preferences(path, &preferences)?;
+
+    if let Some(legacy_path) = legacy_path
+        && legacy_path.exists()
+    {
+        fs::rename(legacy_path, legacy_path.with_extension("json.bak")).ok();
+    }
+
+    Ok(preferences)
+}
+
+// Aggiorna solo i valori cambiati mantenendo commenti e formattazione del file
+fn write_preferences(path: &Path, preferences: &Preferences) -> Result<()> {
+    let existing = fs::read_to_string(path).unwrap_or_else(|_| DEFAULT_CONFIG.to_string());
+    let mut document: DocumentMut = existing
+        .parse()
+        .unwrap_or_else(|_| DEFAULT_CONFIG.parse().expect("default config is valid TOML"));
+    let updated = toml_edit::ser::to_document(preferences)?;
+
+    for (section, item) in updated.iter() {
+        let Some(table) = item.as_table_like() else {
+            document[section] = item.clone();
+            continue;
+        };
+
+        if table.is_empty() && !document.contains_key(section) {
+            continue;
+        }
+
+        if !document.contains_table(section) {
+            document[section] = Item::Table(toml_edit::Table::new());
+        }
+
+        // Le viste eliminate dall'interfaccia vanno tolte anche dal file
+        if section == "views"
+            && let Some(existing) = document[section].as_table_like_mut()
+        {
+            let removed: Vec<String> = existing
+                .iter()
+                .map(|(key, _)| key.to_string())
+                .filter(|key| !table.contains_key(key))
+                .collect();
+            for key in removed {
+                existing.remove(&key);
+            }
+        }
+
+        for (key, value) in table.iter() {
+            let current = &mut document[section][key];
+            if current.to_string().trim() == value.to_string().trim() {
+                continue;
+            }
+
+            let mut value = value.clone();
+            if let (Some(new), Some(old)) = (value.as_value_mut(), current.as_value()) {
+                *new.decor_mut() = old.decor().clone();
+            }
+            *current = value;
+        }
+    }
+
+    fs::write(path, document.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::{load_preferences_from, write_preferences};
+    use crate::SortColumn;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rtop-saving-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn legacy_preferences_are_migrated() {
+        let dir = temp_dir("migrate");
+        let config = dir.join("config.toml");
+        let legacy = dir.join("preferences.json");
+        fs::write(
+            &legacy,
+            r#"{"update_interval_ms": 2500, "sort_column": "Memory", "reverse_sort": true, "history_length": 120}"#,
+        )
+        .unwrap();
+
+        let preferences = load_preferences_from(&config, Some(&legacy)).unwrap();
+        assert_eq!(preferences.refresh.update_interval_ms, 2500);
+        assert!(preferences.processes.sort_column == SortColumn::Memory);
+        assert!(preferences.processes.reverse_sort);
+        assert_eq!(preferences.refresh.history_length, 120);
+
+        assert!(!legacy.exists());
+        assert!(dir.join("preferences.json.bak").exists());
+        let written = fs::read_to_string(&config).unwrap();
+        assert!(written.contains("update_interval_ms = 2500"));
+        assert!(written.starts_with("# rtop configuration file"));
+
+        // Il file creato si rilegge con gli stessi valori
+        let reloaded = load_preferences_from(&config, Some(&legacy)).unwrap();
+        assert_eq!(reloaded.refresh.update_interval_ms, 2500);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn unknown_or_invalid_keys_are_reported() {
+        let dir = temp_dir("invalid");
+        let config = dir.join("config.toml");
+
+        fs::write(&config, "[general]\nmouse = true\nbogus = 1\n").unwrap();
+        let err = format!("{:#}", load_preferences_from(&config, None).err().unwrap());
+        assert!(err.contains("Invalid config"), "{}", err);
+        assert!(err.contains("bogus"), "{}", err);
+
+        fs::write(&config, "[refresh]\nupdate_interval_ms = \"fast\"\n").unwrap();
+        let err = format!("{:#}", load_preferences_from(&config, None).err().unwrap());
+        assert!(err.contains("update_interval_ms"), "{}", err);
+
+        // Il file sbagliato resta com'era
+        assert_eq!(fs::read_to_string(&config).unwrap(), "[refresh]\nupdate_interval_ms = \"fast\"\n");
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn writing_keeps_comments() {
+        let dir = temp_dir("comments");
+        let config = dir.join("config.toml");
+        fs::write(
+            &config,
+            "# my settings\n[refresh]\n# how often\nupdate_interval_ms = 1000 # fast enough\n",
+        )
+        .unwrap();
+
+        let mut preferences = load_preferences_from(&config, None).unwrap();
+        preferences.refresh.update_interval_ms = 3000;
+        write_preferences(&config, &preferences).unwrap();
+
+        let written = fs::read_to_string(&config).unwrap();
+        let expected = "# my settings\n[refresh]\n# how often\nupdate_interval_ms = 3000 # fast enough\n";
+        assert!(written.starts_with(expected), "{}", written);
+        assert_eq!(load_preferences_from(&config, None).unwrap().refresh.update_interval_ms, 3000);
+        fs::remove_dir_all(dir).ok();
     }
-}
\ No newline at end of file
+}
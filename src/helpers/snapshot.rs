@@ -0,0 +1,75 @@
+use rtop::snapshot::Snapshot;
+
+use crate::{App, Page, helpers::utils::push_history};
+
+impl App {
+    // Aggiunge il campione a tutte le history dei grafici
+    pub fn push_snapshot_history(&mut self, snapshot: &Snapshot) {
+        let capacity = self.preferences.refresh.history_length;
+        let time = snapshot.timestamp;
+
+        for (i, usage) in snapshot.cpu_usage.iter().enumerate() {
+            if i >= self.cpu_history.len() {
+                self.cpu_history.push(Default::default());
+            }
+            push_history(&mut self.cpu_history[i], (time, *usage), capacity);
+        }
+
+        for (i, freq) in snapshot.cpu_freq.iter().enumerate() {
+            if i >= self.cpu_freq_history.len() {
+                self.cpu_freq_history.push(Default::default());
+            }
+            push_history(&mut self.cpu_freq_history[i], (time, freq.current_mhz), capacity);
+        }
+
+        let used_mem = snapshot.memory_used as f64 / 1024.0 / 1024.0 / 1024.0;
+        push_history(&mut self.memory_history, (time, used_mem), capacity);
+
+        let (rx, tx) = snapshot
+            .networks
+            .iter()
+            .fold((0, 0), |(rx, tx), net| (rx + net.received, tx + net.transmitted));
+        push_history(&mut self.network_history, (time, (rx, tx)), capacity);
+
+        if let Some(pressure) = snapshot.pressure {
+            push_history(
+                &mut self.pressure_history,
+                (
+                    time,
+                    (
+                        pressure.cpu.some.avg10,
+                        pressure.memory.some.avg10,
+                        pressure.io.some.avg10,
+                    ),
+                ),
+                capacity,
+            );
+        }
+    }
+
+    pub fn clear_history(&mut self) {
+        self.cpu_history.clear();
+        self.cpu_freq_history.clear();
+        self.memory_history.clear();
+        self.network_history.clear();
+        self.pressure_history.clear();
+    }
+
+    // Rende il campione quello mostrato dalla TUI
+    pub fn set_snapshot(&mut self, snapshot: Snapshot) {
+        self.replace_snapshot(snapshot);
+        self.build_process_tree();
+    }
+
+    // Come set_snapshot, ma l'albero dei processi resta quello attuale
+    pub fn replace_snapshot(&mut self, snapshot: Snapshot) {
+        self.cpu_freq = snapshot.cpu_freq.clone();
+        self.pressure = snapshot.pressure;
+        self.power = snapshot.power.clone();
+        self.snapshot = snapshot;
+        // L'ispettore aperto si aggiorna insieme al resto
+        if self.page == Page::Inspector {
+            self.refresh_inspector();
+        }
+    }
+}
@@ -5,7 +5,7 @@ use ratatui::{
 
 use crate::{
     App, Page,
-    gui::{footer::draw_footer, help::draw_help, processes::draw_processes, stats::draw_stats},
+    gui::{footer::draw_footer, help::draw_help, inspector::draw_inspector, processes::draw_processes, stats::draw_stats},
 };
 
 pub fn ui(f: &mut Frame, app: &mut App) {
@@ -18,6 +18,7 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         Page::Processes => draw_processes(f, app, chunks[0]),
         Page::SystemStats => draw_stats(f, app, chunks[0]),
         Page::Help => draw_help(f, app, chunks[0]),
+        Page::Inspector => draw_inspector(f, app, chunks[0]),
     }
 
     draw_footer(f, app, chunks[1]);
@@ -1,171 +1,68 @@
+use std::{collections::VecDeque, io::Write};
+
 use crate::{App, ProcessNode};
 
 impl App {
-    pub fn flatten_processes(&mut self) -> &Vec<(usize, Vec<usize>)> {
+    pub fn flatten_processes(&mut self) -> &Vec<(usize, usize)> {
         if self.cached_flat_processes.is_none() {
-            let mut result = Vec::with_capacity(self.processes.len() * 2);
-            for (idx, node) in self.processes.iter().enumerate() {
-                let mut path = vec![idx];
-                self.flatten_node_with_path(node, 0, &mut path, &mut result);
+            // Ogni nuovo filtro apre gli antenati dei processi che lo soddisfano;
+            // quello che l'utente chiude dopo resta chiuso
+            if !self.filter.is_active() {
+                self.expanded_filter = None;
+            } else if self.expanded_filter.as_ref() != Some(&self.filter) {
+                self.processes.expand_matches(&self.filter, &mut self.expanded_pids);
+                self.expanded_filter = Some(self.filter.clone());
             }
-            self.cached_flat_processes = Some(result);
+            self.cached_flat_processes = Some(self.processes.flatten(&self.filter));
+            self.flatten_generation += 1;
+            self.send_tree_view();
         }
         self.cached_flat_processes.as_ref().unwrap()
     }
 
-    fn flatten_node_with_path(
-        &self,
-        node: &ProcessNode,
-        depth: usize,
-        path: &mut Vec<usize>,
-        result: &mut Vec<(usize, Vec<usize>)>,
-    ) {
-        let (node_matches, has_matching_children) = self.check_node_and_children_match(node);
-
-        // Skippo il subtree se ne il nodo ne il processo figlio hanno un match
-        if !node_matches && !has_matching_children {
-            return;
-        }
-
-        // Aggiungo il nodo al risultato per dare contesto
-        result.push((depth, path.clone()));
-
-        // Se il nodo è expanded appiattischo tutti i processi figli
-        if node.expanded && !node.children.is_empty() {
-            for (child_idx, child) in node.children.iter().enumerate() {
-                path.push(child_idx);
-                self.flatten_node_with_path(child, depth + 1, path, result);
-                path.pop();
-            }
-        }
-    }
-
-    fn check_node_and_children_match(&self, node: &ProcessNode) -> (bool, bool) {
-        let node_matches = self.node_matches_filters(node);
-
-        // Ricerca ricorsiva di un match sui processi figli
-        let has_matching_children = if !self.search_query.is_empty()
-            || self.user_filter.is_some()
-            || self.status_filter.is_some()
-            || self.cpu_threshold.is_some()
-            || self.memory_threshold.is_some()
-        {
-            node.children.iter().any(|child| {
-                let (child_matches, child_has_matching) = self.check_node_and_children_match(child);
-                child_matches || child_has_matching
-            })
-        } else {
-            false
-        };
-
-        (node_matches, has_matching_children)
-    }
-
-    fn node_matches_filters(&self, node: &ProcessNode) -> bool {
-        // Filtro ricerca
-        if !self.search_query.is_empty() {
-            let query_lower = self.search_query.to_lowercase();
-            let name_lower = node.info.name.to_lowercase();
-            let pid_str = node.info.pid.to_string();
-
-            if !name_lower.contains(&query_lower) && !pid_str.contains(&self.search_query) {
-                return false;
-            }
-        }
-
-        // Filtro utente
-        if let Some(ref user_filter) = self.user_filter {
-            if let Some(uid) = node.info.user_id {
-                if !uid.to_string().contains(user_filter) {
-                    return false;
-                }
-            } else {
-                return false;
-            }
-        }
-
-        // Filtro stato - case insensitive
-        if let Some(ref status_filter) = self.status_filter {
-            let status_lower = node.info.status.to_lowercase();
-            let filter_lower = status_filter.to_lowercase();
-
-            if !status_lower.contains(&filter_lower) {
-                return false;
-            }
-        }
-
-        // Filtro soglia CPU
-        if let Some(threshold) = self.cpu_threshold {
-            if node.info.cpu_usage < threshold {
-                return false;
-            }
-        }
-
-        // Filtro soglia memoria
-        if let Some(threshold) = self.memory_threshold {
-            if node.info.memory < threshold {
-                return false;
-            }
-        }
-
-        true
-    }
-
     pub fn get_process_at_flat_index(&self, flat_idx: usize) -> Option<&ProcessNode> {
-        let cached = self.cached_flat_processes.as_ref()?;
-        if flat_idx >= cached.len() {
-            return None;
-        }
-        let (_, path) = &cached[flat_idx];
-
-        // Navigo direttamente usando il path (0 depth complexity)
-        let first_idx = *path.get(0)?;
-        let mut current = self.processes.get(first_idx)?;
-
-        for &child_idx in &path[1..] {
-            current = current.children.get(child_idx)?;
-        }
-
-        Some(current)
+        let (_, slot) = self.cached_flat_processes.as_ref()?.get(flat_idx)?;
+        self.processes.get(*slot)
     }
 
     pub fn toggle_expand(&mut self) {
         let Some(selected) = self.table_state.selected() else {
             return;
         };
-
-        // Prendo il path prima di clonare il processo
-        let path = match self.cached_flat_processes.as_ref() {
-            Some(cache) => match cache.get(selected) {
-                Some((_, p)) => p.clone(),
-                None => return,
-            },
-            None => return,
+        let Some(&(_, slot)) = self.cached_flat_processes.as_ref().and_then(|cache| cache.get(selected)) else {
+            return;
         };
-
-        // Navigo con il path clonato
-        let Some(first_idx) = path.get(0) else { return };
-        let Some(root) = self.processes.get_mut(*first_idx) else {
+        let Some(node) = self.processes.get_mut(slot) else {
             return;
         };
 
-        let mut current = root;
-        for &child_idx in &path[1..] {
-            let Some(child) = current.children.get_mut(child_idx) else {
-                return;
-            };
-            current = child;
-        }
-
         // Se il processo ha figli faccio il toggle
-        if !current.children.is_empty() {
-            current.expanded = !current.expanded;
-            let pid = current.info.pid;
-            self.expanded_pids.insert(pid, current.expanded);
+        if !node.children.is_empty() {
+            node.expanded = !node.expanded;
+            self.expanded_pids.insert(node.info.pid, node.expanded);
             self.cached_flat_processes = None;
         }
     }
 
+    // None espande tutto, Some(0) lascia visibili solo le radici
+    pub fn expand_to_depth(&mut self, depth: Option<usize>) {
+        let selected = self
+            .table_state
+            .selected()
+            .and_then(|index| self.get_process_at_flat_index(index))
+            .map(|node| node.info.pid);
+
+        self.processes.expand_to_depth(depth, &mut self.expanded_pids);
+        self.cached_flat_processes = None;
+
+        // La selezione segue il processo se è ancora visibile
+        if !selected.is_some_and(|pid| self.select_pid(pid)) {
+            let has_rows = !self.flatten_processes().is_empty();
+            self.table_state.select(has_rows.then_some(0));
+            self.viewport_offset = 0;
+        }
+    }
+
     pub fn select_next(&mut self) {
         if let Some(ref cached) = self.cached_flat_processes {
             let flat_len = cached.len();
@@ -220,7 +117,7 @@ impl App {
         if index < self.viewport_offset {
             self.viewport_offset = index;
         } else if index >= self.viewport_offset + visible_rows {
-            self.viewport_offset = index.saturating_sub(visible_rows - 1);
+            self.viewport_offset = index.saturating_sub(visible_rows.saturating_sub(1));
         }
     }
 
@@ -240,7 +137,7 @@ impl App {
             let last_idx = flat_len - 1;
             self.table_state.select(Some(last_idx));
             let visible_rows = self.table_area.height.saturating_sub(4) as usize;
-            self.viewport_offset = last_idx.saturating_sub(visible_rows - 1);
+            self.viewport_offset = last_idx.saturating_sub(visible_rows.saturating_sub(1));
         }
     }
 
@@ -277,67 +174,57 @@ impl App {
     }
 
     pub fn select_first_matching(&mut self) {
-        let flat = self.flatten_processes();
+        let flat = self.flatten_processes().clone();
         if !flat.is_empty() {
-            self.table_state.select(Some(0));
+            // Il match con il punteggio più alto, saltando gli antenati mostrati
+            // solo come contesto; a parità vince il primo
+            let mut first = 0;
+            let mut best = None;
+            for (index, &(_, slot)) in flat.iter().enumerate() {
+                let Some(node) = self.processes.get(slot) else {
+                    continue;
+                };
+                if !self.filter.matches(&node.info) {
+                    continue;
+                }
+                let score = self.filter.search_score(&node.info);
+                if best.is_none_or(|best| score > best) {
+                    best = Some(score);
+                    first = index;
+                }
+            }
+            self.table_state.select(Some(first));
             self.viewport_offset = 0;
+            self.ensure_visible(first);
         } else {
             self.table_state.select(None);
         }
     }
 
     pub fn clear_filters(&mut self) {
-        self.user_filter = None;
-        self.status_filter = None;
-        self.cpu_threshold = None;
-        self.memory_threshold = None;
-        self.search_query.clear();
-        self.cached_flat_processes = None;
-        if self.refresh {self.force_refresh()}
+        self.filter.user = None;
+        self.filter.status = None;
+        self.filter.cpu = None;
+        self.filter.memory = None;
+        self.filter.search.clear();
+        self.reapply_filters();
     }
 }
 
 pub fn calculate_avg_cpu(app: &App) -> f32 {
-    app.system.cpus().iter().map(|c| c.cpu_usage()).sum::<f32>() / app.system.cpus().len() as f32
-}
-
-pub fn generate_sparkline(data: &[f32]) -> String {
-    if data.is_empty() {
-        return String::new();
-    }
-
-    let chars = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
-    let max = data.iter().cloned().fold(0.0f32, f32::max);
-
-    if max == 0.0 {
-        return "▁".repeat(data.len());
-    }
-
-    data.iter()
-        .map(|&val| {
-            let normalized = (val / max * (chars.len() - 1) as f32) as usize;
-            chars[normalized]
-        })
-        .collect()
+    let cpus = &app.snapshot.cpu_usage;
+    cpus.iter().sum::<f32>() / cpus.len().max(1) as f32
 }
 
-pub fn generate_sparkline_with_max(data: &[f32], max_value: f32) -> String {
-    if data.is_empty() {
-        return String::new();
-    }
+// Valori dei grafici con il timestamp in millisecondi del campione da cui vengono
+pub type History<T> = VecDeque<(i64, T)>;
 
-    let chars = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
-
-    if max_value == 0.0 {
-        return "▁".repeat(data.len());
+// Ring buffer a capacità fissa: scarta i campioni più vecchi
+pub fn push_history<T>(history: &mut VecDeque<T>, value: T, capacity: usize) {
+    while history.len() >= capacity.max(1) {
+        history.pop_front();
     }
-
-    data.iter()
-        .map(|&val| {
-            let normalized = ((val / max_value) * (chars.len() - 1) as f32) as usize;
-            chars[normalized.min(chars.len() - 1)]
-        })
-        .collect()
+    history.push_back(value);
 }
 
 pub fn detect_terminal() -> Option<&'static str> {
@@ -369,11 +256,28 @@ pub fn detect_terminal() -> Option<&'static str> {
         "termite",
     ];
 
-    for term in &candidates {
-        if which::which(term).is_ok() {
-            return Some(term);
+    candidates.iter().find(|term| which::which(term).is_ok()).copied()
+}
+
+// Copia negli appunti con la sequenza OSC 52, supportata dalla maggior parte
+// dei terminali anche via ssh
+pub fn copy_to_clipboard(text: &str) {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+    for chunk in text.as_bytes().chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(value >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
         }
     }
 
-    None
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded).ok();
+    stdout.flush().ok();
 }
@@ -0,0 +1,256 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use rtop::filter::SearchKind;
+use serde::{Deserialize, Serialize};
+use sysinfo::Pid;
+
+use crate::{App, Column, SortColumn};
+
+// Una voce [views.<nome>] del file di configurazione. I filtri assenti vengono
+// azzerati, le altre impostazioni restano quelle correnti
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ViewConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_mode: Option<SearchKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_column: Option<SortColumn>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverse_sort: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Column>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExpandedProcess {
+    pid: u32,
+    // Un PID riusato da un altro processo non deve ereditarne lo stato
+    name: String,
+    expanded: bool,
+}
+
+// Salvato accanto al file di configurazione all'uscita se [general]
+// restore_session è attivo
+#[derive(Serialize, Deserialize, Default)]
+pub struct SessionState {
+    view: ViewConfig,
+    expanded: Vec<ExpandedProcess>,
+    // Cronologia della command palette
+    #[serde(default)]
+    commands: Vec<String>,
+}
+
+impl App {
+    // config.toml usa session.json, un altro file <nome>.session.json nella
+    // sua stessa directory: due configurazioni non si sovrascrivono la sessione
+    pub fn session_path(&self) -> PathBuf {
+        let dir = self
+            .config_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        match self.config_path.file_stem().map(|stem| stem.to_string_lossy()) {
+            Some(stem) if stem != "config" => dir.join(format!("{}.session.json", stem)),
+            _ => dir.join("session.json"),
+        }
+    }
+
+    // Fotografia di filtri, ordinamento, colonne e modalità correnti
+    pub fn current_view(&self) -> ViewConfig {
+        ViewConfig {
+            search: Some(self.filter.search.clone()).filter(|query| !query.is_empty()),
+            search_mode: Some(self.filter.search_kind),
+            user: self.filter.user.clone(),
+            status: self.filter.status.clone(),
+            cpu: self.filter.cpu,
+            memory_mb: self.filter.memory.map(|bytes| bytes / 1024 / 1024),
+            sort_column: Some(self.sort_column),
+            reverse_sort: Some(self.reverse_sort),
+            tree_mode: Some(self.tree_mode),
+            columns: Some(self.preferences.processes.columns.clone()),
+        }
+    }
+
+    // I filtri della vista sostituiscono sempre quelli correnti
+    fn apply_filters(&mut self, view: &ViewConfig) {
+        self.filter.search = view.search.clone().unwrap_or_default();
+        if let Some(search_kind) = view.search_mode {
+            self.filter.search_kind = search_kind;
+        }
+        self.filter.user = view.user.clone();
+        self.filter.status = view.status.clone();
+        self.filter.cpu = view.cpu;
+        self.filter.memory = view.memory_mb.map(|mb| mb * 1024 * 1024);
+    }
+
+    pub fn apply_view(&mut self, view: &ViewConfig) {
+        self.apply_filters(view);
+
+        if let Some(sort_column) = view.sort_column {
+            self.sort_column = sort_column;
+            self.preferences.processes.sort_column = sort_column;
+        }
+        if let Some(reverse_sort) = view.reverse_sort {
+            self.reverse_sort = reverse_sort;
+            self.preferences.processes.reverse_sort = reverse_sort;
+        }
+        if let Some(tree_mode) = view.tree_mode {
+            self.tree_mode = tree_mode;
+            self.preferences.processes.tree_mode = tree_mode;
+        }
+        if let Some(columns) = &view.columns
+            && !columns.is_empty()
+        {
+            self.preferences.processes.columns = columns.clone();
+        }
+
+        self.cached_flat_processes = None;
+        self.force_refresh();
+        self.table_state.select(Some(0));
+        self.viewport_offset = 0;
+    }
+
+    pub fn view_names(&self) -> Vec<String> {
+        self.preferences.views.keys().cloned().collect()
+    }
+
+    pub fn save_view(&mut self, name: &str) -> Result<()> {
+        let view = self.current_view();
+        self.preferences.views.insert(name.to_string(), view);
+        self.save_preferences()
+    }
+
+    pub fn delete_view(&mut self, name: &str) -> Result<()> {
+        self.preferences.views.remove(name);
+        self.save_preferences()
+    }
+
+    pub fn save_session(&self) -> Result<()> {
+        // Una registrazione non ha nulla da ricordare per la prossima sessione
+        if !self.preferences.general.restore_session || self.replay.is_some() {
+            return Ok(());
+        }
+
+        let expanded = self
+            .expanded_pids
+            .iter()
+            .filter_map(|(pid, expanded)| {
+                self.snapshot.process(pid.as_u32()).map(|process| ExpandedProcess {
+                    pid: pid.as_u32(),
+                    name: process.name.clone(),
+                    expanded: *expanded,
+                })
+            })
+            .collect();
+        let state = SessionState {
+            view: self.current_view(),
+            expanded,
+            commands: self.palette.history.clone(),
+        };
+
+        let path = self.session_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(&state)?)?;
+        Ok(())
+    }
+
+    pub fn load_session(&self) -> Option<SessionState> {
+        let data = fs::read_to_string(self.session_path()).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    // La vista va applicata prima delle opzioni da riga di comando, che hanno la precedenza
+    pub fn restore_session_view(&mut self, session: &SessionState) {
+        self.apply_filters(&session.view);
+        self.palette.history = session.commands.clone();
+    }
+
+    // Va chiamata quando lo snapshot è disponibile, per confrontare i nomi
+    pub fn restore_session_expansion(&mut self, session: &SessionState) {
+        for entry in &session.expanded {
+            if self
+                .snapshot
+                .process(entry.pid)
+                .is_some_and(|process| process.name == entry.name)
+            {
+                self.expanded_pids.insert(Pid::from_u32(entry.pid), entry.expanded);
+            }
+        }
+        self.build_process_tree();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use sysinfo::Pid;
+
+    use super::{ExpandedProcess, SessionState};
+    use crate::helpers::fake;
+
+    #[test]
+    fn session_follows_the_config_file() {
+        let dir = std::env::temp_dir().join(format!("rtop-session-{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+
+        let mut app = fake::app(vec![fake::machine()]);
+        app.config_path = dir.join("config.toml");
+        assert_eq!(app.session_path(), dir.join("session.json"));
+        app.config_path = dir.join("work.toml");
+        assert_eq!(app.session_path(), dir.join("work.session.json"));
+
+        app.preferences.general.restore_session = true;
+        app.filter.search = "cargo".to_string();
+        app.save_session().unwrap();
+        assert!(dir.join("work.session.json").exists());
+        assert!(!dir.join("session.json").exists());
+
+        let session = app.load_session().unwrap();
+        let mut other = fake::app(vec![fake::machine()]);
+        other.restore_session_view(&session);
+        assert_eq!(other.filter.search, "cargo");
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn expansion_is_restored_only_for_the_same_process() {
+        let mut app = fake::app(vec![fake::machine()]);
+        app.tree_mode = true;
+        let entry = |pid: u32, name: &str| ExpandedProcess {
+            pid,
+            name: name.to_string(),
+            expanded: true,
+        };
+        let session = SessionState {
+            view: Default::default(),
+            // Il PID 200 ora è di bash: lo stato salvato era di un altro processo
+            expanded: vec![entry(100, "sshd"), entry(200, "zsh")],
+            commands: Vec::new(),
+        };
+
+        app.restore_session_expansion(&session);
+        assert_eq!(app.expanded_pids.get(&Pid::from_u32(100)), Some(&true));
+        assert_eq!(app.expanded_pids.get(&Pid::from_u32(200)), None);
+        let sshd = app.processes.slot_of(Pid::from_u32(100)).unwrap();
+        assert!(app.processes.get(sshd).unwrap().expanded);
+    }
+}
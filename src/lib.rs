@@ -0,0 +1,12 @@
+// Raccolta dei campioni, albero dei processi e filtri di rtop, usabili anche
+// senza interfaccia. Il binario rtop è costruito sopra questi moduli
+pub mod collector;
+pub mod cpu_freq;
+pub mod filter;
+pub mod fuzzy;
+pub mod power;
+pub mod pressure;
+pub mod remote;
+pub mod snapshot;
+pub mod source;
+pub mod tree;
@@ -2,6 +2,7 @@ mod gui;
 mod helpers;
 
 use anyhow::Result;
+use clap::{Parser, ValueEnum};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
@@ -15,35 +16,106 @@ use ratatui::{
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     io,
-    sync::{
-        Arc, Mutex,
-        atomic::{AtomicBool, Ordering},
-    },
-    thread,
+    path::PathBuf,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
-use sysinfo::{Networks, Pid, System};
+use sysinfo::Pid;
 
-use crate::helpers::{keyboard::handle_key_event, mouse::handle_mouse, ui::ui};
+use rtop::{
+    collector::Collector,
+    cpu_freq::CpuFreqInfo,
+    filter::ProcessFilter,
+    power::PowerStatus,
+    pressure::SystemPressure,
+    remote::{TOKEN_VARIABLE, run_agent},
+    snapshot::Snapshot,
+    source::{LiveControl, LiveSource, MetricsSource, ProcessControl},
+    tree::{ProcessNode, ProcessTree, SortColumn},
+};
+
+use crate::gui::theme::{Theme, no_color_requested};
+use crate::helpers::{
+    alerts::Alerts,
+    cli::{Cli, Command},
+    config::Preferences,
+    context_menu::ContextMenu,
+    find::FindState,
+    help::HelpState,
+    inspector::InspectorState,
+    keyboard::handle_key_event,
+    keymap::{Action, Keymap},
+    metrics::{run_headless, spawn_metrics_server},
+    mouse::{Drag, handle_mouse},
+    palette::PaletteState,
+    record::Replay,
+    ui::ui,
+    utils::History,
+};
 
-#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
-enum SortColumn {
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Column {
     Pid,
     Name,
     Cpu,
     Memory,
+    User,
+    Status,
+}
+
+impl Column {
+    fn title(&self) -> &'static str {
+        match self {
+            Column::Pid => "PID",
+            Column::Name => "Name",
+            Column::Cpu => "CPU%",
+            Column::Memory => "Memory",
+            Column::User => "User",
+            Column::Status => "Status",
+        }
+    }
+
+    // None per la colonna Name, che si adatta alla larghezza disponibile
+    fn fixed_width(&self) -> Option<u16> {
+        match self {
+            Column::Pid => Some(10),
+            Column::Name => None,
+            Column::Cpu => Some(12),
+            Column::Memory => Some(15),
+            Column::User => Some(10),
+            Column::Status => Some(12),
+        }
+    }
+
+    fn sort_column(&self) -> Option<SortColumn> {
+        match self {
+            Column::Pid => Some(SortColumn::Pid),
+            Column::Name => Some(SortColumn::Name),
+            Column::Cpu => Some(SortColumn::Cpu),
+            Column::Memory => Some(SortColumn::Memory),
+            Column::User | Column::Status => None,
+        }
+    }
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
 enum Page {
     Processes,
+    #[serde(rename = "stats")]
+    #[value(name = "stats")]
     SystemStats,
     Help,
+    // Si apre solo su un processo, non come pagina iniziale
+    #[serde(skip)]
+    #[value(skip)]
+    Inspector,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 enum InputMode {
     None,
     SelectFilter,
@@ -53,172 +125,272 @@ enum InputMode {
     StatusFilter,
     CpuThreshold,
     MemoryThreshold,
+    SelectView,
+    SaveView,
+    ContextMenu,
+    SendSignal,
+    Renice,
+    CommandPalette,
     Error,
 }
 
-#[derive(Clone)]
-struct ProcessInfo {
-    pid: Pid,
-    name: String,
-    cpu_usage: f32,
-    memory: u64,
-    user_id: Option<u32>,
-    status: String,
-}
-
-struct ProcessNode {
-    info: ProcessInfo,
-    children: Vec<ProcessNode>,
-    expanded: bool,
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-struct Preferences {
-    update_interval_ms: u64,
-    sort_column: SortColumn,
-    reverse_sort: bool,
-}
-
-impl Default for Preferences {
-    fn default() -> Self {
-        Self {
-            update_interval_ms: 1000,
-            sort_column: SortColumn::Cpu,
-            reverse_sort: true,
-        }
-    }
-}
-
 struct App {
-    system: System,
-    networks: Networks,
+    // None quando la sorgente è passata al thread del collector
+    source: Option<Box<dyn MetricsSource>>,
+    collector: Option<Collector>,
+    control: Box<dyn ProcessControl>,
     page: Page,
     sort_column: SortColumn,
     reverse_sort: bool,
     table_state: TableState,
-    processes: Vec<ProcessNode>,
+    processes: ProcessTree,
     expanded_pids: HashMap<Pid, bool>,
+    // Ultimo filtro per cui sono stati espansi gli antenati dei match
+    expanded_filter: Option<ProcessFilter>,
     search_mode: bool,
-    search_query: String,
+    find: FindState,
     last_update: Instant,
-    cpu_history: Vec<Vec<f32>>,
-    memory_history: Vec<f64>,
-    network_history: Vec<(u64, u64)>,
+    // Le history sono (timestamp del campione, valore)
+    cpu_history: Vec<History<f32>>,
+    cpu_freq: Vec<CpuFreqInfo>,
+    cpu_freq_history: Vec<History<u64>>,
+    pressure: Option<SystemPressure>,
+    // Solo per il cgroup del processo selezionato, letta insieme al campione
+    cgroup_pressure: Option<(String, SystemPressure)>,
+    pressure_history: History<(f32, f32, f32)>,
+    power: PowerStatus,
+    memory_history: History<f64>,
+    network_history: History<(u64, u64)>,
     table_area: Rect,
     last_click: Option<(Instant, u16, u16)>,
     header_area: Rect,
+    detail_area: Rect,
+    // Scorrimento del pannello dettagli, azzerato quando cambia processo
+    detail_scroll: u16,
+    detail_pid: Option<Pid>,
+    footer_area: Rect,
+    // (inizio, fine) sullo schermo delle voci cliccabili del footer
+    footer_targets: Vec<(u16, u16, Action)>,
+    drag: Option<Drag>,
+    context_menu: Option<ContextMenu>,
     update_interval: Duration,
     viewport_offset: usize,
-    cached_flat_processes: Option<Vec<(usize, Vec<usize>)>>,
+    // (profondità, slot del nodo) per ogni riga visibile
+    cached_flat_processes: Option<Vec<(usize, usize)>>,
+    // Incrementata a ogni ricalcolo delle righe, invalida le cache che ne dipendono
+    flatten_generation: u64,
+    // Ultima vista mandata al collector, gli alberi preparati con una
+    // precedente vengono scartati
+    tree_view_generation: u64,
     input_mode: InputMode,
     input_buffer: String,
     pending_kill_pid: Option<Pid>,
+    view_selected: usize,
+    help: HelpState,
+    inspector: InspectorState,
+    palette: PaletteState,
     preferences: Preferences,
-    user_filter: Option<String>,
-    status_filter: Option<String>,
-    cpu_threshold: Option<f32>,
-    memory_threshold: Option<u64>,
+    filter: ProcessFilter,
     refresh: bool,
     errors: Vec<(String, String)>,
+    config_error: bool,
+    keymap: Keymap,
+    // Condivisi con il collector, che li valuta a ogni campione
+    alerts: Arc<Mutex<Alerts>>,
+    theme: Theme,
+    tree_mode: bool,
+    mouse: bool,
+    config_path: PathBuf,
+    snapshot: Snapshot,
+    replay: Option<Replay>,
+    // Nome dell'host quando i dati arrivano da rtop agent
+    remote: Option<String>,
 }
 
 impl App {
-    fn new() -> Self {
-        let mut system = System::new_all();
-        system.refresh_all();
-        std::thread::sleep(Duration::from_millis(200));
-        system.refresh_cpu_all();
-        std::thread::sleep(Duration::from_millis(200));
-        system.refresh_cpu_all();
+    fn new(cli: &Cli) -> Self {
+        Self::with_source(cli, Box::new(LiveSource::new()), Box::new(LiveControl))
+    }
 
-        let networks = Networks::new_with_refreshed_list();
+    fn with_source(cli: &Cli, source: Box<dyn MetricsSource>, control: Box<dyn ProcessControl>) -> Self {
+        // Gli errori di configurazione vengono mostrati nell'overlay invece di essere ignorati
+        let mut errors = Vec::new();
+        let mut config_error = false;
+        let config_path = cli.config.clone().unwrap_or_else(Self::default_config_path);
+        let preferences = match Self::load_preferences(&config_path) {
+            Ok(preferences) => preferences,
+            Err(err) => {
+                errors.push(("Config error".to_string(), format!("{:#}", err)));
+                config_error = true;
+                Preferences::default()
+            }
+        };
 
-        let mut preferences = Self::load_preferences().unwrap_or_default();
-        preferences.update_interval_ms = preferences.update_interval_ms.clamp(100, 6000);
+        let (theme, color_errors) = Theme::from_config(
+            &preferences.colors,
+            &preferences.themes,
+            no_color_requested(),
+        );
+        let (keymap, key_errors) = Keymap::from_config(&preferences.keybindings);
+        let (alerts, alert_errors) = Alerts::from_config(&preferences.alerts);
+        for message in color_errors.into_iter().chain(key_errors).chain(alert_errors) {
+            errors.push(("Config error".to_string(), message));
+        }
 
         let mut app = Self {
-            system,
-            networks,
-            page: Page::Processes,
-            sort_column: preferences.sort_column,
-            reverse_sort: preferences.reverse_sort,
+            source: Some(source),
+            collector: None,
+            control,
+            page: preferences.general.default_page,
+            sort_column: preferences.processes.sort_column,
+            reverse_sort: preferences.processes.reverse_sort,
+            tree_mode: preferences.processes.tree_mode,
             table_state: TableState::default(),
-            processes: Vec::new(),
+            processes: ProcessTree::default(),
             expanded_pids: HashMap::new(),
+            expanded_filter: None,
             search_mode: false,
-            search_query: String::new(),
+            find: FindState::default(),
             last_update: Instant::now(),
-            cpu_history: vec![vec![]; 60],
-            memory_history: Vec::new(),
-            network_history: vec![(0, 0); 60],
+            cpu_history: Vec::new(),
+            cpu_freq: Vec::new(),
+            cpu_freq_history: Vec::new(),
+            pressure: None,
+            cgroup_pressure: None,
+            pressure_history: VecDeque::new(),
+            power: PowerStatus::default(),
+            memory_history: VecDeque::new(),
+            network_history: VecDeque::new(),
             table_area: Rect::default(),
             last_click: None,
             header_area: Rect::default(),
-            update_interval: Duration::from_millis(preferences.update_interval_ms),
+            detail_area: Rect::default(),
+            detail_scroll: 0,
+            detail_pid: None,
+            footer_area: Rect::default(),
+            footer_targets: Vec::new(),
+            drag: None,
+            context_menu: None,
+            update_interval: Duration::from_millis(preferences.refresh.update_interval_ms),
             viewport_offset: 0,
             cached_flat_processes: None,
-            input_mode: InputMode::None,
+            flatten_generation: 0,
+            tree_view_generation: 0,
+            input_mode: if errors.is_empty() { InputMode::None } else { InputMode::Error },
             input_buffer: String::new(),
             pending_kill_pid: None,
+            view_selected: 0,
+            help: HelpState::default(),
+            inspector: InspectorState::default(),
+            palette: PaletteState::default(),
+            filter: ProcessFilter {
+                search_kind: preferences.processes.search_mode,
+                ..Default::default()
+            },
+            refresh: preferences.refresh.auto_refresh,
+            errors,
+            config_error,
+            keymap,
+            alerts: Arc::new(Mutex::new(alerts)),
+            theme,
+            mouse: preferences.general.mouse,
+            config_path,
+            snapshot: Snapshot::default(),
+            replay: None,
+            remote: None,
             preferences,
-            user_filter: None,
-            status_filter: None,
-            cpu_threshold: None,
-            memory_threshold: None,
-            refresh: true,
-            errors: Vec::new(),
         };
 
-        app.build_process_tree();
+        let session = app
+            .preferences
+            .general
+            .restore_session
+            .then(|| app.load_session())
+            .flatten();
+        if let Some(session) = &session {
+            app.restore_session_view(session);
+        }
+
+        app.apply_cli(cli);
+
+        app.force_refresh();
+        if let Some(session) = &session {
+            app.restore_session_expansion(session);
+        }
         app.table_state.select(Some(0));
         app
     }
 }
 
 fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if let Some(Command::Agent { listen, insecure }) = &cli.command {
+        let token = std::env::var(TOKEN_VARIABLE).ok().filter(|token| !token.is_empty());
+        return run_agent(listen.as_deref(), token.as_deref(), *insecure);
+    }
+    let mut app = match &cli.remote {
+        Some(target) => App::connect(&cli, target)?,
+        None => App::new(&cli),
+    };
+
+    // L'opzione da riga di comando ha la precedenza sul config
+    let metrics_addr = cli
+        .metrics
+        .clone()
+        .or_else(|| Some(app.preferences.metrics.listen.clone()).filter(|addr| !addr.is_empty()));
+    if cli.headless && metrics_addr.is_none() {
+        anyhow::bail!("--headless needs --metrics <ADDR> or [metrics] listen in the config");
+    }
+
+    // Senza interfaccia gli errori di configurazione vanno su stderr
+    if cli.batch || cli.record.is_some() || cli.headless {
+        for (title, message) in &app.errors {
+            eprintln!("{}: {}", title, message);
+        }
+    }
+    if cli.batch {
+        return app.run_batch(cli.iterations.unwrap_or(1), cli.format);
+    }
+    if let Some(path) = &cli.record {
+        return app.run_record(path, cli.iterations.unwrap_or(0));
+    }
+    if cli.headless {
+        let addr = metrics_addr.unwrap_or_default();
+        let app = Arc::new(Mutex::new(app));
+        spawn_metrics_server(&addr, Arc::clone(&app))?;
+        eprintln!("Serving metrics on http://{}/metrics, press Ctrl+C to stop", addr);
+        return run_headless(app);
+    }
+    if let Some(path) = &cli.replay {
+        app.start_replay(path)?;
+    }
+
+    let mouse = app.mouse;
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let app = Arc::new(Mutex::new(App::new()));
-    let should_stop = Arc::new(AtomicBool::new(false));
+    // I campioni arrivano da un thread dedicato, l'interfaccia li applica in run_app
+    app.start_collector();
+    let app = Arc::new(Mutex::new(app));
 
-    // Thread per il refresh in background
-    let app_bg = Arc::clone(&app);
-    let stop_flag = Arc::clone(&should_stop);
-    let bg_thread = thread::spawn(move || {
-        loop {
-            if stop_flag.load(Ordering::Relaxed) {
-                break;
-            }
-            let sleep_duration = {
-                let mut app = app_bg.lock().unwrap();
-                app.refresh();
-                app.update_interval
-            };
-
-            // Chunk minimi per uscire più velocemente
-            let chunk_size = Duration::from_millis(50);
-            let mut remaining = sleep_duration;
-            while remaining > Duration::ZERO {
-                if stop_flag.load(Ordering::Relaxed) {
-                    break;
-                }
-                let to_sleep = remaining.min(chunk_size);
-                thread::sleep(to_sleep);
-                remaining = remaining.saturating_sub(to_sleep);
-            }
-        }
-    });
+    // L'exporter legge lo stesso campione mostrato dalla TUI
+    if let Some(addr) = &metrics_addr
+        && let Err(err) = spawn_metrics_server(addr, Arc::clone(&app))
+    {
+        let mut app = app.lock().unwrap();
+        app.errors.push(("Metrics".to_string(), format!("{:#}", err)));
+        app.input_mode = InputMode::Error;
+    }
 
     let res = run_app(&mut terminal, Arc::clone(&app));
 
-    // Mando il segnale di stop al thread
-    should_stop.store(true, Ordering::Relaxed);
-    bg_thread.join().ok();
-
     // Cleanup
     disable_raw_mode()?;
     execute!(
@@ -239,6 +411,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: Arc<Mutex<App>>) -> Resu
     loop {
         {
             let mut app_guard = app.lock().unwrap();
+            app_guard.tick();
             terminal.draw(|f| ui(f, &mut app_guard))?;
         }
 
@@ -252,7 +425,9 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: Arc<Mutex<App>>) -> Resu
                 }
                 Event::Mouse(mouse) => {
                     let mut app_guard = app.lock().unwrap();
-                    handle_mouse(&mut app_guard, mouse.kind, mouse.column, mouse.row);
+                    if handle_mouse(&mut app_guard, mouse.kind, mouse.column, mouse.row)? {
+                        return Ok(());
+                    }
                 }
                 Event::Resize(_, _) => {
                     let mut app_guard = app.lock().unwrap();
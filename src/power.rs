@@ -0,0 +1,224 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+pub const POWER_SUPPLY_ROOT: &str = "/sys/class/power_supply";
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BatteryInfo {
+    pub name: String,
+    pub capacity: Option<u8>,
+    pub status: String,
+    pub power_watts: Option<f64>,
+    pub time_remaining: Option<Duration>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct PowerStatus {
+    pub batteries: Vec<BatteryInfo>,
+    // None se il sistema non espone alcun alimentatore di tipo Mains
+    pub ac_online: Option<bool>,
+}
+
+fn read_string(dir: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(dir.join(file))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn read_number(dir: &Path, file: &str) -> Option<f64> {
+    read_string(dir, file)?.parse::<f64>().ok()
+}
+
+// I valori del sysfs sono in µW, µWh, µA, µAh e µV
+pub fn parse_battery(dir: &Path) -> BatteryInfo {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let status = read_string(dir, "status").unwrap_or_else(|| "Unknown".to_string());
+    let voltage = read_number(dir, "voltage_now").map(|uv| uv / 1e6);
+
+    // Alcuni driver espongono solo corrente e tensione invece della potenza
+    let power_watts = read_number(dir, "power_now")
+        .map(|uw| uw / 1e6)
+        .or_else(|| Some(read_number(dir, "current_now")? / 1e6 * voltage?))
+        .filter(|w| *w > 0.0);
+
+    // Energia in Wh, convertendo da carica (Ah) quando necessario
+    let energy = |energy_file: &str, charge_file: &str| {
+        read_number(dir, energy_file)
+            .map(|uwh| uwh / 1e6)
+            .or_else(|| Some(read_number(dir, charge_file)? / 1e6 * voltage?))
+    };
+    let energy_now = energy("energy_now", "charge_now");
+    let energy_full = energy("energy_full", "charge_full");
+
+    let capacity = read_number(dir, "capacity")
+        .map(|c| c.clamp(0.0, 100.0) as u8)
+        .or_else(|| match (energy_now, energy_full) {
+            (Some(now), Some(full)) if full > 0.0 => Some((now / full * 100.0).clamp(0.0, 100.0) as u8),
+            _ => None,
+        });
+
+    let time_remaining = match (power_watts, energy_now, energy_full, status.as_str()) {
+        (Some(power), Some(now), _, "Discharging") => Some(now / power),
+        (Some(power), Some(now), Some(full), "Charging") => Some((full - now).max(0.0) / power),
+        _ => None,
+    }
+    .map(|hours| Duration::from_secs_f64(hours * 3600.0));
+
+    BatteryInfo {
+        name,
+        capacity,
+        status,
+        power_watts,
+        time_remaining,
+    }
+}
+
+pub fn read_power_status(root: &Path) -> PowerStatus {
+    let mut status = PowerStatus::default();
+
+    let Ok(entries) = fs::read_dir(root) else {
+        return status;
+    };
+
+    let mut dirs: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    dirs.sort();
+
+    for dir in dirs {
+        match read_string(&dir, "type").as_deref() {
+            Some("Battery") => {
+                // Esclude le batterie di periferiche (mouse, tastiere, ...)
+                if read_string(&dir, "scope").as_deref() == Some("Device") {
+                    continue;
+                }
+                status.batteries.push(parse_battery(&dir));
+            }
+            Some("Mains") => {
+                let online = read_string(&dir, "online").as_deref() == Some("1");
+                status.ac_online = Some(status.ac_online.unwrap_or(false) || online);
+            }
+            _ => {}
+        }
+    }
+
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf, time::Duration};
+
+    use super::read_power_status;
+
+    // Finto /sys/class/power_supply con un file per attributo
+    fn fixture(name: &str, supplies: &[(&str, &[(&str, &str)])]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("rtop-power-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(&root).unwrap();
+        for (supply, files) in supplies {
+            let dir = root.join(supply);
+            fs::create_dir(&dir).unwrap();
+            for (file, value) in *files {
+                fs::write(dir.join(file), format!("{}\n", value)).unwrap();
+            }
+        }
+        root
+    }
+
+    #[test]
+    fn energy_battery() {
+        let root = fixture(
+            "energy",
+            &[(
+                "BAT0",
+                &[
+                    ("type", "Battery"),
+                    ("status", "Discharging"),
+                    ("energy_now", "30000000"),
+                    ("energy_full", "60000000"),
+                    ("power_now", "15000000"),
+                ],
+            )],
+        );
+        let status = read_power_status(&root);
+        fs::remove_dir_all(&root).ok();
+
+        let battery = &status.batteries[0];
+        assert_eq!(battery.name, "BAT0");
+        assert_eq!(battery.capacity, Some(50));
+        assert_eq!(battery.power_watts, Some(15.0));
+        assert_eq!(battery.time_remaining, Some(Duration::from_secs(2 * 3600)));
+        assert_eq!(status.ac_online, None);
+    }
+
+    #[test]
+    fn charge_battery_uses_the_voltage() {
+        let root = fixture(
+            "charge",
+            &[
+                (
+                    "BAT1",
+                    &[
+                        ("type", "Battery"),
+                        ("status", "Charging"),
+                        ("charge_now", "2000000"),
+                        ("charge_full", "4000000"),
+                        ("current_now", "1000000"),
+                        ("voltage_now", "12000000"),
+                    ],
+                ),
+                ("AC", &[("type", "Mains"), ("online", "1")]),
+            ],
+        );
+        let status = read_power_status(&root);
+        fs::remove_dir_all(&root).ok();
+
+        let battery = &status.batteries[0];
+        assert_eq!(battery.status, "Charging");
+        assert_eq!(battery.capacity, Some(50));
+        assert_eq!(battery.power_watts, Some(12.0));
+        // 24 Wh mancanti a 12 W
+        assert_eq!(battery.time_remaining, Some(Duration::from_secs(2 * 3600)));
+        assert_eq!(status.ac_online, Some(true));
+    }
+
+    #[test]
+    fn no_battery_or_ac_only() {
+        let empty = fixture("empty", &[]);
+        let status = read_power_status(&empty);
+        assert!(status.batteries.is_empty());
+        assert_eq!(status.ac_online, None);
+
+        let missing = read_power_status(&empty.join("missing"));
+        fs::remove_dir_all(&empty).ok();
+        assert!(missing.batteries.is_empty());
+
+        let root = fixture("ac", &[("ACAD", &[("type", "Mains"), ("online", "0")])]);
+        let status = read_power_status(&root);
+        fs::remove_dir_all(&root).ok();
+        assert!(status.batteries.is_empty());
+        assert_eq!(status.ac_online, Some(false));
+    }
+
+    #[test]
+    fn device_batteries_are_ignored() {
+        let root = fixture(
+            "device",
+            &[(
+                "hidpp_battery_0",
+                &[("type", "Battery"), ("scope", "Device"), ("capacity", "80"), ("status", "Discharging")],
+            )],
+        );
+        let status = read_power_status(&root);
+        fs::remove_dir_all(&root).ok();
+        assert!(status.batteries.is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PressureLine {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    pub total: u64,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PressureStats {
+    pub some: PressureLine,
+    // La CPU a livello di sistema espone "full" solo sui kernel più recenti
+    pub full: Option<PressureLine>,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SystemPressure {
+    pub cpu: PressureStats,
+    pub memory: PressureStats,
+    pub io: PressureStats,
+}
+
+// Formato: "some avg10=0.00 avg60=0.00 avg300=0.00 total=0"
+fn parse_pressure_line(line: &str) -> Option<(&str, PressureLine)> {
+    let mut parts = line.split_whitespace();
+    let kind = parts.next()?;
+    let mut parsed = PressureLine::default();
+
+    for part in parts {
+        let (key, value) = part.split_once('=')?;
+        match key {
+            "avg10" => parsed.avg10 = value.parse().ok()?,
+            "avg60" => parsed.avg60 = value.parse().ok()?,
+            "avg300" => parsed.avg300 = value.parse().ok()?,
+            "total" => parsed.total = value.parse().ok()?,
+            _ => {}
+        }
+    }
+
+    Some((kind, parsed))
+}
+
+pub fn parse_pressure(contents: &str) -> Option<PressureStats> {
+    let mut some = None;
+    let mut full = None;
+
+    for line in contents.lines() {
+        match parse_pressure_line(line) {
+            Some(("some", parsed)) => some = Some(parsed),
+            Some(("full", parsed)) => full = Some(parsed),
+            _ => {}
+        }
+    }
+
+    Some(PressureStats { some: some?, full })
+}
+
+fn read_pressure_file(path: PathBuf) -> Option<PressureStats> {
+    parse_pressure(&fs::read_to_string(path).ok()?)
+}
+
+pub fn read_system_pressure() -> Option<SystemPressure> {
+    Some(SystemPressure {
+        cpu: read_pressure_file(PathBuf::from("/proc/pressure/cpu"))?,
+        memory: read_pressure_file(PathBuf::from("/proc/pressure/memory"))?,
+        io: read_pressure_file(PathBuf::from("/proc/pressure/io"))?,
+    })
+}
+
+// Percorso del cgroup v2 del processo, per esempio /user.slice/user-1000.slice
+pub fn read_cgroup(pid: u32) -> Option<String> {
+    let contents = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|relative| relative.trim().to_string())
+}
+
+// Radice della gerarchia cgroup v2 (gestisce sia unified che hybrid)
+fn cgroup_v2_root() -> PathBuf {
+    if PathBuf::from("/sys/fs/cgroup/cgroup.controllers").exists() {
+        PathBuf::from("/sys/fs/cgroup")
+    } else {
+        PathBuf::from("/sys/fs/cgroup/unified")
+    }
+}
+
+// Pressure di un cgroup a partire dal percorso letto in /proc/<pid>/cgroup
+pub fn read_cgroup_pressure(cgroup: &str) -> Option<SystemPressure> {
+    let dir = cgroup_v2_root().join(cgroup.trim_start_matches('/'));
+    Some(SystemPressure {
+        cpu: read_pressure_file(dir.join("cpu.pressure"))?,
+        memory: read_pressure_file(dir.join("memory.pressure"))?,
+        io: read_pressure_file(dir.join("io.pressure"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_pressure;
+
+    #[test]
+    fn some_and_full_lines() {
+        let stats = parse_pressure(
+            "some avg10=1.50 avg60=0.75 avg300=0.25 total=123456\n\
+             full avg10=0.50 avg60=0.10 avg300=0.00 total=789\n",
+        )
+        .unwrap();
+        assert_eq!(stats.some.avg10, 1.5);
+        assert_eq!(stats.some.avg60, 0.75);
+        assert_eq!(stats.some.avg300, 0.25);
+        assert_eq!(stats.some.total, 123456);
+        let full = stats.full.unwrap();
+        assert_eq!(full.avg10, 0.5);
+        assert_eq!(full.total, 789);
+    }
+
+    #[test]
+    fn cpu_without_full_line() {
+        // I kernel più vecchi non hanno la riga "full" per la CPU
+        let stats = parse_pressure("some avg10=3.00 avg60=2.00 avg300=1.00 total=42\n").unwrap();
+        assert_eq!(stats.some.avg10, 3.0);
+        assert!(stats.full.is_none());
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert!(parse_pressure("").is_none());
+        assert!(parse_pressure("full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n").is_none());
+        assert!(parse_pressure("some avg10=abc avg60=0.00 avg300=0.00 total=0\n").is_none());
+        assert!(parse_pressure("some avg10 avg60=0.00\n").is_none());
+
+        // Una riga rovinata non cancella quelle valide
+        let stats = parse_pressure("some avg10=1.00 avg60=0.00 avg300=0.00 total=1\nfull garbage\n").unwrap();
+        assert_eq!(stats.some.avg10, 1.0);
+        assert!(stats.full.is_none());
+    }
+}
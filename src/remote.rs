@@ -0,0 +1,439 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    os::unix::net::{UnixListener, UnixStream},
+    process::{Child, Command, Stdio},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    snapshot::Snapshot,
+    source::{LiveControl, LiveSource, MetricsSource, ProcessControl},
+};
+
+// Da incrementare a ogni modifica incompatibile dei messaggi o di Snapshot
+pub const PROTOCOL_VERSION: u32 = 2;
+
+// Token condiviso tra agent e client, letto dall'ambiente per non comparire in ps
+pub const TOKEN_VARIABLE: &str = "RTOP_AGENT_TOKEN";
+
+// Oltre questo tempo un'azione senza risposta viene considerata fallita
+const ACTION_TIMEOUT: Duration = Duration::from_secs(10);
+const SAMPLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Messaggi dal client all'agent, un oggetto JSON per riga. Il primo deve essere hello
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    Hello {
+        version: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    Sample,
+    // Il token viaggia con ogni azione, che l'agent ricontrolla
+    Signal {
+        id: u64,
+        pid: u32,
+        signal: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    Renice {
+        id: u64,
+        pid: u32,
+        nice: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+}
+
+// Risposte dell'agent, nello stesso ordine delle richieste
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Hello { version: u32, hostname: Option<String> },
+    Snapshot(Box<Snapshot>),
+    Result { id: u64, error: Option<String> },
+    Error { message: String },
+}
+
+fn send<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+// Confronto a tempo costante, per non rivelare il token un byte alla volta
+fn token_matches(expected: Option<&str>, given: Option<&str>) -> bool {
+    let Some(expected) = expected else {
+        return true;
+    };
+    let given = given.unwrap_or_default();
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn action_result(id: u64, result: io::Result<()>) -> Response {
+    Response::Result {
+        id,
+        error: result.err().map(|err| err.to_string()),
+    }
+}
+
+// Lato agent: risponde a un client finché non chiude la connessione. Con un
+// token, hello e ogni azione devono riportarlo
+pub fn serve(
+    reader: impl BufRead,
+    mut writer: impl Write,
+    source: &mut dyn MetricsSource,
+    control: &dyn ProcessControl,
+    token: Option<&str>,
+) -> Result<()> {
+    let mut lines = reader.lines();
+
+    let Some(line) = lines.next() else {
+        return Ok(());
+    };
+    match serde_json::from_str::<Request>(&line?) {
+        Ok(Request::Hello { token: given, .. }) if !token_matches(token, given.as_deref()) => {
+            let message = "Invalid or missing token".to_string();
+            send(&mut writer, &Response::Error { message })?;
+            return Ok(());
+        }
+        Ok(Request::Hello { version, .. }) if version == PROTOCOL_VERSION => {
+            let hostname = sysinfo::System::host_name();
+            send(&mut writer, &Response::Hello { version, hostname })?;
+        }
+        Ok(Request::Hello { version, .. }) => {
+            let message = format!(
+                "Unsupported protocol version {}, this agent speaks version {}",
+                version, PROTOCOL_VERSION
+            );
+            send(&mut writer, &Response::Error { message })?;
+            return Ok(());
+        }
+        _ => {
+            let message = "Expected hello as the first message".to_string();
+            send(&mut writer, &Response::Error { message })?;
+            return Ok(());
+        }
+    }
+
+    for line in lines {
+        let response = match serde_json::from_str::<Request>(&line?) {
+            Ok(Request::Sample) => Response::Snapshot(Box::new(source.sample())),
+            Ok(Request::Signal { id, token: given, .. } | Request::Renice { id, token: given, .. })
+                if !token_matches(token, given.as_deref()) =>
+            {
+                action_result(id, Err(io::Error::new(io::ErrorKind::PermissionDenied, "invalid or missing token")))
+            }
+            Ok(Request::Signal { id, pid, signal, .. }) => action_result(id, control.signal(pid, signal)),
+            Ok(Request::Renice { id, pid, nice, .. }) => action_result(id, control.renice(pid, nice)),
+            Ok(Request::Hello { .. }) => Response::Error {
+                message: "Already connected".to_string(),
+            },
+            // Un messaggio sconosciuto non chiude la connessione
+            Err(err) => Response::Error {
+                message: format!("Invalid request: {}", err),
+            },
+        };
+        send(&mut writer, &response)?;
+    }
+
+    Ok(())
+}
+
+// Il socket nasce già accessibile solo al proprietario: chi può connettersi
+// può anche uccidere processi
+fn bind_private_socket(path: &str) -> io::Result<UnixListener> {
+    // umask vale per tutto il processo, ma qui l'agent ha ancora un solo thread
+    let previous = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(previous) };
+    listener
+}
+
+// Senza token si accettano solo indirizzi locali, salvo richiesta esplicita
+fn check_tcp_address(addr: &str, token: Option<&str>, insecure: bool) -> Result<()> {
+    if token.is_some() || insecure {
+        return Ok(());
+    }
+    let addresses: Vec<_> = addr
+        .to_socket_addrs()
+        .with_context(|| format!("Invalid address {}", addr))?
+        .collect();
+    if addresses.iter().any(|address| !address.ip().is_loopback()) {
+        bail!(
+            "Refusing to listen on {} without authentication: set {} on both sides, \
+             or pass --insecure to accept anyone who can reach the port",
+            addr,
+            TOKEN_VARIABLE
+        );
+    }
+    Ok(())
+}
+
+// rtop agent: su stdin/stdout oppure in ascolto su HOST:PORT o unix:PATH,
+// con una sorgente separata per ogni client
+pub fn run_agent(listen: Option<&str>, token: Option<&str>, insecure: bool) -> Result<()> {
+    // Su stdin/stdout l'autenticazione è quella di ssh
+    let Some(addr) = listen else {
+        let stdin = io::stdin().lock();
+        let stdout = io::stdout().lock();
+        return serve(stdin, stdout, &mut LiveSource::new(), &LiveControl, None);
+    };
+    let token: Option<Arc<str>> = token.map(Arc::from);
+
+    if let Some(path) = addr.strip_prefix("unix:") {
+        let listener = bind_private_socket(path).with_context(|| format!("Cannot listen on {}", path))?;
+        eprintln!("Agent listening on {}", path);
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let reader = BufReader::new(stream.try_clone()?);
+            let token = token.clone();
+            thread::spawn(move || serve_client(reader, stream, token.as_deref()));
+        }
+    } else {
+        check_tcp_address(addr, token.as_deref(), insecure)?;
+        let listener = TcpListener::bind(addr).with_context(|| format!("Cannot listen on {}", addr))?;
+        eprintln!("Agent listening on {}", listener.local_addr()?);
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let reader = BufReader::new(stream.try_clone()?);
+            let token = token.clone();
+            thread::spawn(move || serve_client(reader, stream, token.as_deref()));
+        }
+    }
+    Ok(())
+}
+
+fn serve_client(reader: impl BufRead, writer: impl Write, token: Option<&str>) {
+    if let Err(err) = serve(reader, writer, &mut LiveSource::new(), &LiveControl, token) {
+        eprintln!("Agent: {:#}", err);
+    }
+}
+
+// Scrittura condivisa tra sorgente e controllo; il processo ssh (o il comando)
+// viene terminato quando non serve più
+struct Connection {
+    writer: Mutex<Box<dyn Write + Send>>,
+    child: Mutex<Option<Child>>,
+}
+
+impl Connection {
+    fn send(&self, request: &Request) -> io::Result<()> {
+        send(&mut *self.writer.lock().unwrap(), request)
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.lock().unwrap().take() {
+            child.kill().ok();
+            child.wait().ok();
+        }
+    }
+}
+
+pub struct RemoteSource {
+    connection: Arc<Connection>,
+    snapshots: Receiver<Snapshot>,
+    last: Snapshot,
+}
+
+impl MetricsSource for RemoteSource {
+    // Se la connessione cade resta l'ultimo campione ricevuto
+    fn sample(&mut self) -> Snapshot {
+        // Un campione arrivato dopo il timeout precedente è già vecchio
+        while self.snapshots.try_recv().is_ok() {}
+
+        if self.connection.send(&Request::Sample).is_ok()
+            && let Ok(snapshot) = self.snapshots.recv_timeout(SAMPLE_TIMEOUT)
+        {
+            self.last = snapshot;
+        }
+        self.last.clone()
+    }
+}
+
+pub struct RemoteControl {
+    connection: Arc<Connection>,
+    token: Option<String>,
+    results: Mutex<Receiver<(u64, Option<String>)>>,
+    next_id: AtomicU64,
+}
+
+impl RemoteControl {
+    fn action(&self, request: impl FnOnce(u64, Option<String>) -> Request) -> io::Result<()> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let results = self.results.lock().unwrap();
+        self.connection.send(&request(id, self.token.clone()))?;
+
+        loop {
+            match results.recv_timeout(ACTION_TIMEOUT) {
+                Ok((result_id, error)) if result_id == id => {
+                    return match error {
+                        Some(message) => Err(io::Error::other(message)),
+                        None => Ok(()),
+                    };
+                }
+                // Risposta a un'azione già scaduta
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => return Err(io::ErrorKind::TimedOut.into()),
+                Err(RecvTimeoutError::Disconnected) => return Err(io::ErrorKind::NotConnected.into()),
+            }
+        }
+    }
+}
+
+impl ProcessControl for RemoteControl {
+    fn signal(&self, pid: u32, signal: i32) -> io::Result<()> {
+        self.action(|id, token| Request::Signal { id, pid, signal, token })
+    }
+
+    fn renice(&self, pid: u32, nice: i32) -> io::Result<()> {
+        self.action(|id, token| Request::Renice { id, pid, nice, token })
+    }
+}
+
+pub struct Remote {
+    pub source: RemoteSource,
+    pub control: RemoteControl,
+    pub hostname: Option<String>,
+}
+
+// ssh:HOST esegue "rtop agent" sull'host, exec:COMMAND un comando qualsiasi
+// che parli il protocollo su stdin/stdout
+fn agent_command(target: &str) -> Option<Command> {
+    if let Some(host) = target.strip_prefix("ssh:") {
+        let mut command = Command::new("ssh");
+        command.arg(host).arg("rtop agent");
+        return Some(command);
+    }
+    let line = target.strip_prefix("exec:")?;
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(line);
+    Some(command)
+}
+
+// TARGET può essere HOST:PORT, unix:PATH, ssh:HOST oppure exec:COMMAND
+pub fn connect(target: &str, token: Option<&str>) -> Result<Remote> {
+    let (reader, writer, child): (Box<dyn BufRead + Send>, Box<dyn Write + Send>, _) =
+        if let Some(path) = target.strip_prefix("unix:") {
+            let stream = UnixStream::connect(path).with_context(|| format!("Cannot connect to {}", path))?;
+            (Box::new(BufReader::new(stream.try_clone()?)), Box::new(stream), None)
+        } else if let Some(mut command) = agent_command(target) {
+            let mut child = command
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .with_context(|| format!("Cannot start the agent for {}", target))?;
+            let stdout = child.stdout.take().unwrap();
+            let stdin = child.stdin.take().unwrap();
+            (Box::new(BufReader::new(stdout)), Box::new(stdin), Some(child))
+        } else {
+            let addr = target.strip_prefix("tcp:").unwrap_or(target);
+            let stream = TcpStream::connect(addr).with_context(|| format!("Cannot connect to {}", addr))?;
+            stream.set_nodelay(true).ok();
+            (Box::new(BufReader::new(stream.try_clone()?)), Box::new(stream), None)
+        };
+
+    let connection = Connection {
+        writer: Mutex::new(writer),
+        child: Mutex::new(child),
+    };
+    handshake(reader, connection, token.map(str::to_string)).with_context(|| format!("Remote {}", target))
+}
+
+fn handshake(mut reader: Box<dyn BufRead + Send>, connection: Connection, token: Option<String>) -> Result<Remote> {
+    connection.send(&Request::Hello {
+        version: PROTOCOL_VERSION,
+        token: token.clone(),
+    })?;
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        bail!("The agent closed the connection");
+    }
+    let hostname = match serde_json::from_str::<Response>(&line) {
+        Ok(Response::Hello { version, hostname }) if version == PROTOCOL_VERSION => hostname,
+        Ok(Response::Hello { version, .. }) => bail!("The agent speaks protocol version {}", version),
+        Ok(Response::Error { message }) => bail!("{}", message),
+        _ => bail!("Unexpected answer from the agent: {}", line.trim()),
+    };
+    let connection = Arc::new(connection);
+
+    // Un thread smista le risposte: i campioni alla sorgente, gli esiti delle azioni al controllo
+    let (snapshot_tx, snapshots) = mpsc::channel();
+    let (result_tx, results) = mpsc::channel();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else { break };
+            match serde_json::from_str::<Response>(&line) {
+                Ok(Response::Snapshot(snapshot)) => {
+                    snapshot_tx.send(*snapshot).ok();
+                }
+                Ok(Response::Result { id, error }) => {
+                    result_tx.send((id, error)).ok();
+                }
+                _ => {}
+            }
+        }
+    });
+
+    Ok(Remote {
+        source: RemoteSource {
+            connection: Arc::clone(&connection),
+            snapshots,
+            last: Snapshot::default(),
+        },
+        control: RemoteControl {
+            connection,
+            token,
+            results: Mutex::new(results),
+            next_id: AtomicU64::new(1),
+        },
+        hostname,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    use super::{bind_private_socket, check_tcp_address, token_matches};
+
+    #[test]
+    fn agent_listens_safely() {
+        assert!(check_tcp_address("127.0.0.1:7070", None, false).is_ok());
+        assert!(check_tcp_address("[::1]:7070", None, false).is_ok());
+        assert!(check_tcp_address("0.0.0.0:7070", None, false).is_err());
+        assert!(check_tcp_address("0.0.0.0:7070", Some("secret"), false).is_ok());
+        assert!(check_tcp_address("0.0.0.0:7070", None, true).is_ok());
+
+        assert!(token_matches(None, None));
+        assert!(token_matches(Some("secret"), Some("secret")));
+        assert!(!token_matches(Some("secret"), Some("secreT")));
+        assert!(!token_matches(Some("secret"), None));
+
+        let path = std::env::temp_dir().join(format!("rtop-private-{}.sock", std::process::id()));
+        fs::remove_file(&path).ok();
+        let _listener = bind_private_socket(path.to_str().unwrap()).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_file(&path).ok();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
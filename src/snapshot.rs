@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{cpu_freq::CpuFreqInfo, power::PowerStatus, pressure::SystemPressure};
+
+// Tutto ciò che serve per disegnare un campione: viene prodotto dal refresh
+// dal vivo oppure letto da una registrazione
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    // Millisecondi dall'epoch Unix
+    pub timestamp: i64,
+    pub cpu_usage: Vec<f32>,
+    pub cpu_freq: Vec<CpuFreqInfo>,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub swap_used: u64,
+    pub swap_total: u64,
+    // Della macchina campionata, che con --remote non è quella locale
+    #[serde(default)]
+    pub load_average: [f64; 3],
+    #[serde(default)]
+    pub uptime: u64,
+    pub networks: Vec<NetworkSample>,
+    pub pressure: Option<SystemPressure>,
+    pub power: PowerStatus,
+    pub processes: Vec<ProcessSample>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NetworkSample {
+    pub name: String,
+    // Byte dall'ultimo campione
+    pub received: u64,
+    pub transmitted: u64,
+    // Totali dall'avvio, usati dall'exporter
+    #[serde(default)]
+    pub total_received: u64,
+    #[serde(default)]
+    pub total_transmitted: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    // Già divisa per il numero di core
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub virtual_memory: u64,
+    pub user_id: Option<u32>,
+    pub status: String,
+    // Secondi dall'epoch Unix
+    pub start_time: u64,
+    pub cmd: Vec<String>,
+    // Eseguibile, nome dell'utente e cgroup v2, cercati insieme al nome
+    #[serde(default)]
+    pub exe: String,
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub cgroup: String,
+    // Byte letti e scritti su disco dall'avvio del processo
+    #[serde(default)]
+    pub read_bytes: u64,
+    #[serde(default)]
+    pub written_bytes: u64,
+}
+
+// Formato più leggibile degli stati di sysinfo
+pub fn format_status(status: sysinfo::ProcessStatus) -> String {
+    match status {
+        sysinfo::ProcessStatus::Run => "Running".to_string(),
+        sysinfo::ProcessStatus::Sleep => "Sleeping".to_string(),
+        sysinfo::ProcessStatus::Idle => "Idle".to_string(),
+        sysinfo::ProcessStatus::Zombie => "Zombie".to_string(),
+        sysinfo::ProcessStatus::Stop => "Stopped".to_string(),
+        sysinfo::ProcessStatus::Tracing => "Tracing".to_string(),
+        sysinfo::ProcessStatus::Dead => "Dead".to_string(),
+        sysinfo::ProcessStatus::Wakekill => "Wakekill".to_string(),
+        sysinfo::ProcessStatus::Waking => "Waking".to_string(),
+        sysinfo::ProcessStatus::Parked => "Parked".to_string(),
+        sysinfo::ProcessStatus::LockBlocked => "LockBlocked".to_string(),
+        sysinfo::ProcessStatus::UninterruptibleDiskSleep => "DiskSleep".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+impl Snapshot {
+    pub fn process(&self, pid: u32) -> Option<&ProcessSample> {
+        self.processes.iter().find(|p| p.pid == pid)
+    }
+}
@@ -0,0 +1,197 @@
+use std::{collections::HashMap, io, path::Path, thread, time::Duration};
+
+use sysinfo::{Networks, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users};
+
+use crate::{
+    cpu_freq::{CpuFreqInfo, read_cpu_frequencies},
+    power::{POWER_SUPPLY_ROOT, read_power_status},
+    pressure::{SystemPressure, read_cgroup, read_cgroup_pressure, read_system_pressure},
+    snapshot::{NetworkSample, ProcessSample, Snapshot, format_status},
+};
+
+// Da dove arrivano i campioni: il sistema reale oppure, nei test, una sequenza
+// preparata in anticipo. Può vivere nel thread del collector
+pub trait MetricsSource: Send {
+    // Aggiorna le letture e restituisce il nuovo campione
+    fn sample(&mut self) -> Snapshot;
+
+    // Pressure di un solo cgroup v2, letta solo per quello che l'interfaccia
+    // mostra; le sorgenti remote non la hanno
+    fn cgroup_pressure(&mut self, _cgroup: &str) -> Option<SystemPressure> {
+        None
+    }
+}
+
+// Operazioni sui singoli processi, usate dal thread dell'interfaccia senza
+// passare dalla sorgente dei campioni
+pub trait ProcessControl: Send {
+    fn signal(&self, pid: u32, signal: i32) -> io::Result<()>;
+
+    // Priorità tra -20 e 19, come nice(1)
+    fn renice(&self, pid: u32, nice: i32) -> io::Result<()>;
+
+    // Manda SIGKILL, false se il segnale fallisce
+    fn kill(&self, pid: u32) -> bool {
+        self.signal(pid, libc::SIGKILL).is_ok()
+    }
+}
+
+// sysinfo per processi, CPU, memoria e rete; /sys e /proc per frequenze,
+// pressure e alimentazione
+pub struct LiveSource {
+    system: System,
+    networks: Networks,
+    cpu_freq: Vec<CpuFreqInfo>,
+    users: Users,
+    // Il cgroup si legge una volta per processo: (start_time, percorso) per PID
+    cgroups: HashMap<u32, (u64, String)>,
+}
+
+impl LiveSource {
+    pub fn new() -> Self {
+        let mut system = System::new_all();
+        system.refresh_all();
+        thread::sleep(Duration::from_millis(200));
+        system.refresh_cpu_all();
+        thread::sleep(Duration::from_millis(200));
+        system.refresh_cpu_all();
+
+        Self {
+            system,
+            networks: Networks::new_with_refreshed_list(),
+            cpu_freq: Vec::new(),
+            users: Users::new_with_refreshed_list(),
+            cgroups: HashMap::new(),
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.system.refresh_cpu_all();
+        self.system.refresh_memory();
+
+        // Refresh degli status
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_cpu()
+                .with_memory()
+                .with_disk_usage()
+                .with_user(UpdateKind::Always)
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_exe(UpdateKind::OnlyIfNotSet),
+        );
+
+        self.networks.refresh(true);
+        self.cpu_freq = read_cpu_frequencies(self.system.cpus(), &self.cpu_freq);
+    }
+}
+
+impl Default for LiveSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for LiveSource {
+    fn sample(&mut self) -> Snapshot {
+        self.refresh();
+        let cpu_number = self.system.cpus().len().max(1) as f32;
+
+        // Dimentico i processi terminati e leggo il cgroup di quelli nuovi
+        let processes = self.system.processes();
+        self.cgroups.retain(|pid, (start_time, _)| {
+            processes
+                .get(&sysinfo::Pid::from_u32(*pid))
+                .is_some_and(|process| process.start_time() == *start_time)
+        });
+        for (pid, process) in processes {
+            self.cgroups
+                .entry(pid.as_u32())
+                .or_insert_with(|| (process.start_time(), read_cgroup(pid.as_u32()).unwrap_or_default()));
+        }
+
+        let processes = processes
+            .iter()
+            .map(|(pid, process)| ProcessSample {
+                pid: pid.as_u32(),
+                parent: process.parent().map(|p| p.as_u32()),
+                name: process.name().to_string_lossy().to_string(),
+                cpu_usage: process.cpu_usage() / cpu_number,
+                memory: process.memory(),
+                virtual_memory: process.virtual_memory(),
+                user_id: process.user_id().map(|uid| **uid),
+                status: format_status(process.status()),
+                start_time: process.start_time(),
+                cmd: process
+                    .cmd()
+                    .iter()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .collect(),
+                exe: process.exe().map(|exe| exe.to_string_lossy().to_string()).unwrap_or_default(),
+                user: process
+                    .user_id()
+                    .and_then(|uid| self.users.get_user_by_id(uid))
+                    .map(|user| user.name().to_string())
+                    .unwrap_or_default(),
+                cgroup: self.cgroups.get(&pid.as_u32()).map(|(_, cgroup)| cgroup.clone()).unwrap_or_default(),
+                read_bytes: process.disk_usage().total_read_bytes,
+                written_bytes: process.disk_usage().total_written_bytes,
+            })
+            .collect();
+
+        let mut networks: Vec<NetworkSample> = self
+            .networks
+            .iter()
+            .map(|(name, net)| NetworkSample {
+                name: name.clone(),
+                received: net.received(),
+                transmitted: net.transmitted(),
+                total_received: net.total_received(),
+                total_transmitted: net.total_transmitted(),
+            })
+            .collect();
+        networks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Snapshot {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            cpu_usage: self.system.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
+            cpu_freq: self.cpu_freq.clone(),
+            memory_used: self.system.used_memory(),
+            memory_total: self.system.total_memory(),
+            swap_used: self.system.used_swap(),
+            swap_total: self.system.total_swap(),
+            load_average: {
+                let load = System::load_average();
+                [load.one, load.five, load.fifteen]
+            },
+            uptime: System::uptime(),
+            networks,
+            pressure: read_system_pressure(),
+            power: read_power_status(Path::new(POWER_SUPPLY_ROOT)),
+            processes,
+        }
+    }
+
+    fn cgroup_pressure(&mut self, cgroup: &str) -> Option<SystemPressure> {
+        read_cgroup_pressure(cgroup)
+    }
+}
+
+pub struct LiveControl;
+
+impl ProcessControl for LiveControl {
+    fn signal(&self, pid: u32, signal: i32) -> io::Result<()> {
+        if unsafe { libc::kill(pid as i32, signal) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn renice(&self, pid: u32, nice: i32) -> io::Result<()> {
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid, nice) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
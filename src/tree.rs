@@ -0,0 +1,392 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sysinfo::Pid;
+
+use crate::snapshot::ProcessSample;
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SortColumn {
+    #[serde(alias = "Pid")]
+    Pid,
+    #[serde(alias = "Name")]
+    Name,
+    #[serde(alias = "Cpu")]
+    Cpu,
+    #[serde(alias = "Memory")]
+    Memory,
+}
+
+#[derive(Clone)]
+pub struct ProcessInfo {
+    pub pid: Pid,
+    pub name: String,
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub user_id: Option<u32>,
+    pub status: String,
+    // Campi usati solo dalla ricerca
+    pub cmd: String,
+    pub exe: String,
+    pub user: String,
+    pub cgroup: String,
+}
+
+#[derive(Clone)]
+pub struct ProcessNode {
+    pub info: ProcessInfo,
+    // Slot dei figli nel ProcessTree, già ordinati
+    pub children: Vec<usize>,
+    pub expanded: bool,
+}
+
+// A parità di valore decide il PID, così l'ordine non cambia da un refresh all'altro
+pub fn compare_processes(a: &ProcessInfo, b: &ProcessInfo, sort_column: SortColumn, reverse_sort: bool) -> Ordering {
+    let ordering = match sort_column {
+        SortColumn::Pid => a.pid.cmp(&b.pid),
+        SortColumn::Name => a.name.cmp(&b.name),
+        SortColumn::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+        SortColumn::Memory => a.memory.cmp(&b.memory),
+    }
+    .then_with(|| a.pid.cmp(&b.pid));
+
+    if reverse_sort {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+fn same_cmd(joined: &str, cmd: &[String]) -> bool {
+    let mut rest = joined;
+    for (i, arg) in cmd.iter().enumerate() {
+        if i > 0 {
+            let Some(stripped) = rest.strip_prefix(' ') else {
+                return false;
+            };
+            rest = stripped;
+        }
+        let Some(stripped) = rest.strip_prefix(arg.as_str()) else {
+            return false;
+        };
+        rest = stripped;
+    }
+    rest.is_empty()
+}
+
+// Impostazioni con cui sono state costruite le liste dei figli
+#[derive(Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub tree_mode: bool,
+    pub sort_column: SortColumn,
+    pub reverse_sort: bool,
+}
+
+// Lista in cui si trova un nodo
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Place {
+    Root,
+    Child(usize),
+    // systemd e kthreadd in modalità albero
+    Hidden,
+}
+
+#[derive(Clone)]
+struct Slot {
+    node: ProcessNode,
+    parent: Option<Pid>,
+    place: Place,
+    generation: u64,
+}
+
+// Arena indicizzata per PID: a ogni campione si aggiornano i nodi esistenti,
+// si aggiungono i nuovi e si tolgono i terminati. Gli indici restano validi
+// finché il processo esiste e vengono riusati solo dopo
+#[derive(Default, Clone)]
+pub struct ProcessTree {
+    slots: Vec<Option<Slot>>,
+    free: Vec<usize>,
+    index: HashMap<Pid, usize>,
+    roots: Vec<usize>,
+    generation: u64,
+    layout: Option<Layout>,
+}
+
+impl ProcessTree {
+    pub fn get(&self, slot: usize) -> Option<&ProcessNode> {
+        self.slots.get(slot)?.as_ref().map(|s| &s.node)
+    }
+
+    pub fn get_mut(&mut self, slot: usize) -> Option<&mut ProcessNode> {
+        self.slots.get_mut(slot)?.as_mut().map(|s| &mut s.node)
+    }
+
+    pub fn slot_of(&self, pid: Pid) -> Option<usize> {
+        self.index.get(&pid).copied()
+    }
+
+    // Gli slot sono sempre minori di questo valore, anche quelli liberi
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    // None per le radici e per i nodi nascosti
+    pub fn parent_slot(&self, slot: usize) -> Option<usize> {
+        match self.slots.get(slot)?.as_ref()?.place {
+            Place::Child(parent) => Some(parent),
+            Place::Root | Place::Hidden => None,
+        }
+    }
+
+    pub fn roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    // Espande i nodi fino alla profondità indicata (None: tutti) e chiude gli
+    // altri; con Some(0) restano visibili solo le radici
+    pub fn expand_to_depth(&mut self, depth: Option<usize>, expanded_pids: &mut HashMap<Pid, bool>) {
+        expanded_pids.clear();
+        let mut stack: Vec<(usize, usize)> = self.roots.iter().map(|&slot| (slot, 0)).collect();
+        while let Some((slot, level)) = stack.pop() {
+            let Some(node) = self.get_mut(slot) else {
+                continue;
+            };
+            node.expanded = !node.children.is_empty() && depth.is_none_or(|depth| level < depth);
+            if node.expanded {
+                expanded_pids.insert(node.info.pid, true);
+            }
+            stack.extend(node.children.iter().map(|&child| (child, level + 1)));
+        }
+    }
+
+    // Restituisce gli slot dei processi comparsi in questo campione
+    pub fn update(
+        &mut self,
+        processes: &[ProcessSample],
+        layout: Layout,
+        expanded_pids: &mut HashMap<Pid, bool>,
+    ) -> Vec<usize> {
+        self.generation += 1;
+        let generation = self.generation;
+
+        // Cambiando modalità o ordinamento si ricostruiscono tutte le liste
+        let relayout = self.layout != Some(layout);
+        self.layout = Some(layout);
+
+        let mut moved = Vec::new();
+        let mut added = HashSet::new();
+        let mut added_slots = Vec::new();
+        let mut dirty: HashSet<Place> = HashSet::new();
+
+        for process in processes {
+            let pid = Pid::from_u32(process.pid);
+            let parent = process.parent.map(Pid::from_u32);
+            let expanded = expanded_pids.get(&pid).copied().unwrap_or(false);
+
+            if let Some(&slot) = self.index.get(&pid) {
+                let entry = self.slots[slot].as_mut().unwrap();
+                let info = &mut entry.node.info;
+
+                // Solo le liste in cui cambia la chiave di ordinamento vanno riordinate
+                let key_changed = match layout.sort_column {
+                    SortColumn::Pid => false,
+                    SortColumn::Name => info.name != process.name,
+                    SortColumn::Cpu => info.cpu_usage != process.cpu_usage,
+                    SortColumn::Memory => info.memory != process.memory,
+                };
+
+                if info.name != process.name {
+                    info.name = process.name.clone();
+                }
+                if info.status != process.status {
+                    info.status = process.status.clone();
+                }
+                if info.exe != process.exe || info.user != process.user || info.cgroup != process.cgroup {
+                    info.exe = process.exe.clone();
+                    info.user = process.user.clone();
+                    info.cgroup = process.cgroup.clone();
+                }
+                // La riga di comando cambia di rado, evito di ricostruirla a ogni campione
+                if !same_cmd(&info.cmd, &process.cmd) {
+                    info.cmd = process.cmd.join(" ");
+                }
+                info.cpu_usage = process.cpu_usage;
+                info.memory = process.memory;
+                info.user_id = process.user_id;
+
+                entry.node.expanded = expanded;
+                entry.generation = generation;
+
+                if entry.parent != parent {
+                    entry.parent = parent;
+                    moved.push(slot);
+                } else if key_changed {
+                    dirty.insert(entry.place);
+                }
+            } else {
+                let entry = Slot {
+                    node: ProcessNode {
+                        info: ProcessInfo {
+                            pid,
+                            name: process.name.clone(),
+                            cpu_usage: process.cpu_usage,
+                            memory: process.memory,
+                            user_id: process.user_id,
+                            status: process.status.clone(),
+                            cmd: process.cmd.join(" "),
+                            exe: process.exe.clone(),
+                            user: process.user.clone(),
+                            cgroup: process.cgroup.clone(),
+                        },
+                        children: Vec::new(),
+                        expanded,
+                    },
+                    parent,
+                    place: Place::Hidden,
+                    generation,
+                };
+                let slot = match self.free.pop() {
+                    Some(slot) => {
+                        self.slots[slot] = Some(entry);
+                        slot
+                    }
+                    None => {
+                        self.slots.push(Some(entry));
+                        self.slots.len() - 1
+                    }
+                };
+                self.index.insert(pid, slot);
+                added.insert(pid);
+                added_slots.push(slot);
+                moved.push(slot);
+            }
+        }
+
+        // Tolgo i processi terminati, i loro figli vanno ricollegati
+        let exited: Vec<usize> = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.as_ref().is_some_and(|e| e.generation != generation))
+            .map(|(slot, _)| slot)
+            .collect();
+        for slot in exited {
+            let entry = self.slots[slot].take().unwrap();
+            self.free.push(slot);
+            self.index.remove(&entry.node.info.pid);
+            expanded_pids.remove(&entry.node.info.pid);
+            self.detach(slot, entry.place);
+            moved.extend(entry.node.children);
+        }
+
+        // Un figlio visto prima del suo parent è finito tra le radici: lo
+        // ricollego quando il parent compare
+        if !added.is_empty() && layout.tree_mode && !relayout {
+            moved.extend(self.slots.iter().enumerate().filter_map(|(slot, entry)| {
+                let entry = entry.as_ref()?;
+                (entry.place == Place::Root && entry.parent.is_some_and(|parent| added.contains(&parent))).then_some(slot)
+            }));
+        }
+
+        if relayout {
+            self.roots.clear();
+            for entry in self.slots.iter_mut().flatten() {
+                entry.node.children.clear();
+                entry.place = Place::Hidden;
+            }
+            moved = (0..self.slots.len()).collect();
+        }
+
+        for slot in moved {
+            let Some(entry) = &self.slots[slot] else {
+                continue;
+            };
+            let old = entry.place;
+            let new = self.place_for(entry, layout.tree_mode);
+            if old == new && !relayout {
+                continue;
+            }
+
+            self.detach(slot, old);
+            match new {
+                Place::Root => self.roots.push(slot),
+                Place::Child(parent) => self.slots[parent].as_mut().unwrap().node.children.push(slot),
+                Place::Hidden => {}
+            }
+            self.slots[slot].as_mut().unwrap().place = new;
+            dirty.insert(new);
+        }
+
+        for place in dirty {
+            self.sort_place(place, layout);
+        }
+        added_slots
+    }
+
+    fn place_for(&self, entry: &Slot, tree_mode: bool) -> Place {
+        if !tree_mode {
+            return Place::Root;
+        }
+
+        // Processi root del sistema da skippare (systemd and kthreadd)
+        let is_skipped = |pid: Pid| matches!(pid.as_u32(), 1 | 2);
+        if is_skipped(entry.node.info.pid) {
+            return Place::Hidden;
+        }
+
+        // Diventano root i processi senza un parent (o con il parent già terminato)
+        // e i figli diretti dei processi 1 e 2
+        match entry.parent {
+            Some(parent) if !is_skipped(parent) => self.slot_of(parent).map_or(Place::Root, Place::Child),
+            _ => Place::Root,
+        }
+    }
+
+    // Togliere un elemento lascia la lista ordinata
+    fn detach(&mut self, slot: usize, place: Place) {
+        let list = match place {
+            Place::Root => &mut self.roots,
+            Place::Child(parent) => match &mut self.slots[parent] {
+                Some(entry) => &mut entry.node.children,
+                None => return,
+            },
+            Place::Hidden => return,
+        };
+        if let Some(position) = list.iter().position(|&s| s == slot) {
+            list.remove(position);
+        }
+    }
+
+    fn sort_place(&mut self, place: Place, layout: Layout) {
+        let mut list = match place {
+            Place::Root => std::mem::take(&mut self.roots),
+            Place::Child(parent) => match &mut self.slots[parent] {
+                Some(entry) => std::mem::take(&mut entry.node.children),
+                None => return,
+            },
+            Place::Hidden => return,
+        };
+
+        let info = |slot: usize| &self.slots[slot].as_ref().unwrap().node.info;
+        list.sort_by(|&a, &b| compare_processes(info(a), info(b), layout.sort_column, layout.reverse_sort));
+
+        match place {
+            Place::Root => self.roots = list,
+            Place::Child(parent) => self.slots[parent].as_mut().unwrap().node.children = list,
+            Place::Hidden => {}
+        }
+    }
+}